@@ -0,0 +1,164 @@
+use std::future::{pending, IntoFuture};
+
+use async_ui_gtk::{
+    components as backend, fragment, futures_lite::FutureExt, gtk::traits::WidgetExt, NodeRef,
+};
+
+use super::{ButtonProps, CheckboxProps, LinkProps, ListProps, TextInputProps, ViewProps};
+
+/** The node ref to give a component, so that [add_class] can find its widget.
+ *
+ * `own` only lives as long as the call, so `children` are re-wrapped into
+ * a shorter-lived [Fragment][async_ui_gtk::Fragment] where needed.
+ */
+fn class_node_ref<'a>(
+    class: &[&str],
+    node_ref: Option<&'a NodeRef>,
+    own: &'a NodeRef,
+) -> Option<&'a NodeRef> {
+    match class.is_empty() {
+        true => node_ref,
+        false => Some(node_ref.unwrap_or(own)),
+    }
+}
+
+/** Add `class` to the widget once it mounts. Never completes.
+ */
+async fn add_class(class: &[&str], node_ref: Option<&NodeRef>) {
+    if let (false, Some(node_ref)) = (class.is_empty(), node_ref) {
+        let widget = node_ref.get().await;
+        for name in class {
+            widget.widget().add_css_class(name);
+        }
+    }
+    pending().await
+}
+
+pub(super) async fn view<'c>(
+    ViewProps {
+        children,
+        class,
+        node_ref,
+        width,
+        height,
+        native: _,
+    }: ViewProps<'c>,
+) {
+    let own = NodeRef::new();
+    let node_ref = class_node_ref(class, node_ref, &own);
+    let size = |px: Option<u32>| px.map_or(-1, |px| px as i32);
+    backend::view(backend::ViewProps {
+        children: fragment((children,)),
+        width: size(width),
+        height: size(height),
+        node_ref,
+    })
+    .or(add_class(class, node_ref))
+    .await
+}
+
+pub(super) async fn button<'c>(
+    ButtonProps {
+        children,
+        on_press,
+        class,
+        node_ref,
+    }: ButtonProps<'c>,
+) {
+    let own = NodeRef::new();
+    let node_ref = class_node_ref(class, node_ref, &own);
+    backend::button(backend::ButtonProps {
+        children: fragment((children,)),
+        on_press,
+        node_ref,
+    })
+    .or(add_class(class, node_ref))
+    .await
+}
+
+pub(super) async fn text_input<'c>(
+    TextInputProps {
+        text,
+        on_change_text,
+        on_submit,
+        on_blur,
+        on_focus,
+        multiline,
+        placeholder,
+        class,
+        node_ref,
+    }: TextInputProps<'c>,
+) {
+    let own = NodeRef::new();
+    let node_ref = class_node_ref(class, node_ref, &own);
+    backend::text_input(backend::TextInputProps {
+        text,
+        on_change_text,
+        on_submit,
+        on_blur,
+        on_focus,
+        multiline,
+        placeholder,
+        node_ref,
+    })
+    .or(add_class(class, node_ref))
+    .await
+}
+
+pub(super) async fn checkbox<'c>(
+    CheckboxProps {
+        value,
+        on_change,
+        class,
+        node_ref,
+    }: CheckboxProps<'c>,
+) {
+    let own = NodeRef::new();
+    let node_ref = class_node_ref(class, node_ref, &own);
+    backend::checkbox(backend::CheckboxProps {
+        value,
+        on_change,
+        node_ref,
+    })
+    .or(add_class(class, node_ref))
+    .await
+}
+
+pub(super) async fn link<'c>(
+    LinkProps {
+        children,
+        href,
+        on_press,
+        class,
+        node_ref,
+    }: LinkProps<'c>,
+) {
+    let own = NodeRef::new();
+    let node_ref = class_node_ref(class, node_ref, &own);
+    backend::link(backend::LinkProps {
+        children: fragment((children,)),
+        href,
+        on_press,
+        node_ref,
+    })
+    .or(add_class(class, node_ref))
+    .await
+}
+
+pub(super) async fn list<'c, T: Clone + 'c, F: IntoFuture + 'c>(
+    ListProps {
+        data,
+        render,
+        class,
+    }: ListProps<'c, T, F>,
+) {
+    let own = NodeRef::new();
+    let node_ref = class_node_ref(class, None, &own);
+    backend::list(backend::ListProps {
+        data,
+        render,
+        node_ref,
+    })
+    .or(add_class(class, node_ref))
+    .await
+}
@@ -0,0 +1,164 @@
+/*! Components that work the same on every backend.
+ *
+ * The props mirror those of the backend components.
+ * `class` is a list of CSS class names (GTK widgets take CSS classes too).
+ * What only one backend supports for [view] is in its `native` field; see [crate::native].
+ */
+use std::future::IntoFuture;
+
+use observables::ObservableAs;
+
+use crate::{
+    native::{Current, NativeProps},
+    platform::components as backend,
+    Fragment, NodeRef,
+};
+
+#[cfg(feature = "gtk")]
+#[path = "gtk.rs"]
+mod imp;
+#[cfg(all(feature = "web", not(feature = "gtk")))]
+#[path = "web.rs"]
+mod imp;
+
+pub use backend::{text, CheckboxChangeEvent, ListModel, PressEvent, TextInputEvent};
+
+#[derive(Default)]
+pub struct ViewProps<'c> {
+    pub children: Fragment<'c>,
+    pub class: &'c [&'c str],
+    pub node_ref: Option<&'c NodeRef>,
+    /** Size in pixels. `None` (the default) leaves it to the layout.
+     * The web sets the inline `width`/`height` style; GTK requests the size of the box.
+     */
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub native: <Current as NativeProps>::View<'c>,
+}
+/** A container laying out its children vertically (on the web, that is up to CSS).
+ */
+pub async fn view<'c>(props: ViewProps<'c>) {
+    imp::view(props).await
+}
+
+pub struct ButtonProps<'c> {
+    pub children: Fragment<'c>,
+    pub on_press: &'c mut (dyn FnMut(PressEvent) + 'c),
+    pub class: &'c [&'c str],
+    pub node_ref: Option<&'c NodeRef>,
+}
+impl<'c> Default for ButtonProps<'c> {
+    fn default() -> Self {
+        Self {
+            children: Default::default(),
+            on_press: backend::ButtonProps::default().on_press,
+            class: &[],
+            node_ref: None,
+        }
+    }
+}
+pub async fn button<'c>(props: ButtonProps<'c>) {
+    imp::button(props).await
+}
+
+pub struct TextInputProps<'c> {
+    pub text: &'c dyn ObservableAs<str>,
+    pub on_change_text: &'c mut (dyn FnMut(TextInputEvent) + 'c),
+    pub on_submit: &'c mut (dyn FnMut(TextInputEvent) + 'c),
+    pub on_blur: &'c mut (dyn FnMut(TextInputEvent) + 'c),
+    pub on_focus: &'c mut (dyn FnMut(TextInputEvent) + 'c),
+    pub multiline: bool,
+    pub placeholder: &'c dyn ObservableAs<str>,
+    pub class: &'c [&'c str],
+    pub node_ref: Option<&'c NodeRef>,
+}
+impl<'c> Default for TextInputProps<'c> {
+    fn default() -> Self {
+        let backend::TextInputProps {
+            on_change_text,
+            on_submit,
+            on_blur,
+            on_focus,
+            ..
+        } = Default::default();
+        Self {
+            text: &[""],
+            on_change_text,
+            on_submit,
+            on_blur,
+            on_focus,
+            multiline: false,
+            placeholder: &[""],
+            class: &[],
+            node_ref: None,
+        }
+    }
+}
+pub async fn text_input<'c>(props: TextInputProps<'c>) {
+    imp::text_input(props).await
+}
+
+pub struct CheckboxProps<'c> {
+    pub value: &'c dyn ObservableAs<bool>,
+    pub on_change: &'c mut (dyn FnMut(CheckboxChangeEvent) + 'c),
+    pub class: &'c [&'c str],
+    pub node_ref: Option<&'c NodeRef>,
+}
+impl<'c> Default for CheckboxProps<'c> {
+    fn default() -> Self {
+        Self {
+            value: &[false],
+            on_change: backend::CheckboxProps::default().on_change,
+            class: &[],
+            node_ref: None,
+        }
+    }
+}
+pub async fn checkbox<'c>(props: CheckboxProps<'c>) {
+    imp::checkbox(props).await
+}
+
+pub struct LinkProps<'c> {
+    pub children: Fragment<'c>,
+    pub href: &'c dyn ObservableAs<str>,
+    pub on_press: &'c mut (dyn FnMut(PressEvent) + 'c),
+    pub class: &'c [&'c str],
+    pub node_ref: Option<&'c NodeRef>,
+}
+impl<'c> Default for LinkProps<'c> {
+    fn default() -> Self {
+        Self {
+            children: Default::default(),
+            href: &[""],
+            on_press: backend::LinkProps::default().on_press,
+            class: &[],
+            node_ref: None,
+        }
+    }
+}
+/** A link to `href`. On GTK, clicking it opens the URI with the default handler.
+ */
+pub async fn link<'c>(props: LinkProps<'c>) {
+    imp::link(props).await
+}
+
+pub struct ListProps<'c, T: Clone, F: IntoFuture> {
+    pub data: &'c dyn ObservableAs<ListModel<T>>,
+    pub render: &'c dyn Fn(T) -> F,
+    /** On GTK, the classes go on the scrolled window around the items.
+     */
+    pub class: &'c [&'c str],
+}
+impl<'c, T: Clone + 'c, F: IntoFuture + 'c> Default for ListProps<'c, T, F> {
+    fn default() -> Self {
+        let backend::ListProps { data, render, .. } = Default::default();
+        Self {
+            data,
+            render,
+            class: &[],
+        }
+    }
+}
+pub async fn list<'c, T: Clone + 'c, F: IntoFuture + 'c>(props: ListProps<'c, T, F>) {
+    imp::list(props).await
+}
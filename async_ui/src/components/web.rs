@@ -0,0 +1,159 @@
+use std::future::{pending, IntoFuture};
+
+use async_ui_web::{
+    components as backend, fragment,
+    futures_lite::FutureExt,
+    utils::class_list::ClassList,
+    web_sys::{wasm_bindgen::JsCast, HtmlElement},
+    NodeRef,
+};
+
+use super::{ButtonProps, CheckboxProps, LinkProps, ListProps, TextInputProps, ViewProps};
+
+/** The backend's [ClassList] for `class`, or `None` if there are no classes.
+ */
+fn class_list<'c>(class: &'c [&'c str]) -> Option<ClassList<'c>> {
+    (!class.is_empty()).then(|| ClassList::new(class.iter().copied()))
+}
+
+pub(super) async fn view<'c>(
+    ViewProps {
+        children,
+        class,
+        node_ref,
+        width,
+        height,
+        native,
+    }: ViewProps<'c>,
+) {
+    let class = class_list(class);
+    let own = NodeRef::new();
+    let node_ref = match (width, height) {
+        (None, None) => node_ref,
+        _ => Some(node_ref.unwrap_or(&own)),
+    };
+    backend::view(backend::ViewProps {
+        children: fragment((children,)),
+        class: class.as_ref(),
+        element_tag: native.element_tag,
+        node_ref,
+    })
+    .or(set_size(width, height, node_ref))
+    .await
+}
+
+/** Set the inline `width` and `height` of the element once it mounts. Never completes.
+ */
+async fn set_size(width: Option<u32>, height: Option<u32>, node_ref: Option<&NodeRef>) {
+    if let (true, Some(node_ref)) = (width.is_some() || height.is_some(), node_ref) {
+        let style = node_ref.get().await.unchecked_into::<HtmlElement>().style();
+        for (name, px) in [("width", width), ("height", height)] {
+            if let Some(px) = px {
+                style
+                    .set_property(name, &format!("{px}px"))
+                    .expect("set style failed");
+            }
+        }
+    }
+    pending().await
+}
+
+pub(super) async fn button<'c>(
+    ButtonProps {
+        children,
+        on_press,
+        class,
+        node_ref,
+    }: ButtonProps<'c>,
+) {
+    let class = class_list(class);
+    backend::button(backend::ButtonProps {
+        children: fragment((children,)),
+        on_press,
+        class: class.as_ref(),
+        node_ref,
+    })
+    .await
+}
+
+pub(super) async fn text_input<'c>(
+    TextInputProps {
+        text,
+        on_change_text,
+        on_submit,
+        on_blur,
+        on_focus,
+        multiline,
+        placeholder,
+        class,
+        node_ref,
+    }: TextInputProps<'c>,
+) {
+    let class = class_list(class);
+    backend::text_input(backend::TextInputProps {
+        text,
+        on_change_text,
+        on_submit,
+        on_blur,
+        on_focus,
+        multiline,
+        placeholder,
+        class: class.as_ref(),
+        node_ref,
+    })
+    .await
+}
+
+pub(super) async fn checkbox<'c>(
+    CheckboxProps {
+        value,
+        on_change,
+        class,
+        node_ref,
+    }: CheckboxProps<'c>,
+) {
+    let class = class_list(class);
+    backend::checkbox(backend::CheckboxProps {
+        value,
+        on_change,
+        class: class.as_ref(),
+        node_ref,
+    })
+    .await
+}
+
+pub(super) async fn link<'c>(
+    LinkProps {
+        children,
+        href,
+        on_press,
+        class,
+        node_ref,
+    }: LinkProps<'c>,
+) {
+    let class = class_list(class);
+    backend::link(backend::LinkProps {
+        children: fragment((children,)),
+        href,
+        on_press,
+        class: class.as_ref(),
+        node_ref,
+    })
+    .await
+}
+
+pub(super) async fn list<'c, T: Clone + 'c, F: IntoFuture + 'c>(
+    ListProps {
+        data,
+        render,
+        class,
+    }: ListProps<'c, T, F>,
+) {
+    let class = class_list(class);
+    backend::list(backend::ListProps {
+        data,
+        render,
+        class: class.as_ref(),
+    })
+    .await
+}
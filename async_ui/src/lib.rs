@@ -1 +1,35 @@
+/*! Async UI, for whichever backend is enabled.
+ *
+ * The `web` feature is on by default. To build for GTK instead, use
+ *
+ * ```toml
+ * async_ui = { version = "...", default-features = false, features = ["gtk"] }
+ * ```
+ *
+ * If both features end up enabled (e.g. `gtk` without `default-features = false`,
+ * or two crates in one workspace asking for different backends), `gtk` is used,
+ * but `async_ui_web` is still compiled for nothing.
+ *
+ * An app written against [components] compiles for either backend without any `cfg`.
+ * Props of [view][components::view] that only one backend understands go in its `native` field; see [native].
+ * Everything else a backend offers is available through [platform].
+ */
+#[cfg(not(any(feature = "web", feature = "gtk")))]
+compile_error!("enable either the `web` or the `gtk` feature of async_ui");
 
+#[cfg(feature = "gtk")]
+pub use async_ui_gtk as platform;
+#[cfg(all(feature = "web", not(feature = "gtk")))]
+pub use async_ui_web as platform;
+
+pub mod components;
+pub mod native;
+
+pub use platform::timer::{sleep, PlatformTimer};
+pub use platform::{
+    create_portal_pair, error_boundary, fragment, futures_lite, get_context, mark_ready, mount,
+    portal_entry, portal_target, show_when, spawn_local, spawn_local_with_priority, suspense,
+    suspense_with_min_display, switch, switch_keep_alive, transition, try_get_context,
+    try_get_reactive_context, use_context_or_default, with_context, Failure, Fragment, NodeRef,
+    PortalTargets, Priority, ReactiveContext, Retry, Transition,
+};
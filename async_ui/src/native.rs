@@ -0,0 +1,53 @@
+/*! Props of [view][crate::components::view] that only one backend understands.
+ *
+ * Each backend is a marker type implementing [NativeProps].
+ * [ViewProps][crate::components::ViewProps] has a `native` field typed by the [Current] backend,
+ * so backend-specific code is confined to the places that set it.
+ * Leave it as `Default::default()` to stay portable.
+ * The other components have no backend-specific props.
+ */
+
+/** The extra props a backend takes for its view.
+ */
+pub trait NativeProps {
+    /** For [view][crate::components::view].
+     */
+    type View<'c>: Default;
+}
+
+/** The `async_ui_web` backend.
+ */
+pub struct Web;
+/** The `async_ui_gtk` backend.
+ */
+pub struct Gtk;
+
+/** The backend enabled by the crate features.
+ */
+#[cfg(all(feature = "web", not(feature = "gtk")))]
+pub type Current = Web;
+#[cfg(feature = "gtk")]
+pub type Current = Gtk;
+
+pub struct WebViewProps<'c> {
+    /** The HTML tag to use. Defaults to `"div"`.
+     */
+    pub element_tag: &'c str,
+}
+impl<'c> Default for WebViewProps<'c> {
+    fn default() -> Self {
+        Self { element_tag: "div" }
+    }
+}
+
+/** GTK takes nothing beyond the portable props for now.
+ */
+#[derive(Default)]
+pub struct GtkViewProps {}
+
+impl NativeProps for Web {
+    type View<'c> = WebViewProps<'c>;
+}
+impl NativeProps for Gtk {
+    type View<'c> = GtkViewProps;
+}
@@ -5,14 +5,35 @@ use scoped_tls::ScopedKey;
 use crate::vnode::VNode;
 
 pub trait BackendTrait: 'static + Sized {
-    type Node: 'static;
+    type Node: 'static + Clone;
     fn add_child_node(
         parent: &mut Self::Node,
         child: &mut Self::Node,
         insert_before_sibling: Option<&Self::Node>,
     );
     fn del_child_node(parent: &mut Self::Node, child: &mut Self::Node);
+    /** Whether the node is attached to the root (e.g. the document), through all its ancestors.
+     *
+     * Backends that cannot tell report every node as connected (the default).
+     */
+    fn is_connected(_node: &Self::Node) -> bool {
+        true
+    }
     fn drive_executor<F: Future<Output = ()> + 'static>(fut: F);
+    /** Whether the executor should stop running tasks for now and give control back to the host,
+     * e.g. because it has used up its time budget. By default, it never does.
+     */
+    fn should_yield() -> bool {
+        false
+    }
+    /** Arrange for [run_idle_period][crate::executor::run_idle_period] to be called when the host is idle.
+     *
+     * Return `false` if the backend cannot tell (the default).
+     * Idle tasks then run whenever nothing else is runnable.
+     */
+    fn request_idle() -> bool {
+        false
+    }
     fn initialize();
     fn get_vnode_key() -> &'static ScopedKey<Rc<VNode<Self>>>;
 }
@@ -4,7 +4,90 @@ use std::{
 };
 
 use im_rc::HashMap;
+use observables::cell::{ReactiveCell, ReactiveCellObservable};
 #[derive(Clone, Default)]
 pub struct ContextMap {
     pub(crate) inner: HashMap<TypeId, Rc<dyn Any>>,
+    /// Shown by the inspector.
+    #[cfg(feature = "inspect")]
+    pub(crate) type_names: HashMap<TypeId, &'static str>,
+}
+
+impl ContextMap {
+    /** Provide `value` to everything rendered with this map (or a clone of it).
+     */
+    pub fn insert<T: 'static>(&mut self, value: Rc<T>) {
+        self.inner.insert(TypeId::of::<T>(), value);
+        #[cfg(feature = "inspect")]
+        self.type_names
+            .insert(TypeId::of::<T>(), std::any::type_name::<T>());
+    }
+}
+
+/** A context value that can be replaced after it is provided.
+ *
+ * Provide it like any other context (as `Rc<ReactiveContext<T>>`).
+ * Descendants that look it up can observe it through [as_observable][Self::as_observable],
+ * so they are notified when the value is [set][Self::set].
+ */
+pub struct ReactiveContext<T> {
+    cell: ReactiveCell<T>,
+}
+
+impl<T> ReactiveContext<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            cell: ReactiveCell::new(value),
+        }
+    }
+    /** Replace the value, notifying every descendant observing it.
+     */
+    pub fn set(&self, value: T) {
+        self.cell.set(value);
+    }
+    pub fn as_observable(&self) -> ReactiveCellObservable<T, &ReactiveCell<T>> {
+        self.cell.as_observable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use observables::ObservableAsExt;
+
+    use crate::{
+        testing::{element, mount, run_until_stalled, TestBackend},
+        vnode::node_context::{try_get_context, try_get_reactive_context, WithContext},
+    };
+
+    use super::ReactiveContext;
+
+    #[test]
+    fn reactive_context_notifies_descendants() {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        enum Theme {
+            Light,
+            Dark,
+        }
+        let theme = Rc::new(ReactiveContext::new(Theme::Light));
+        let seen: Rc<RefCell<Vec<Theme>>> = Default::default();
+        let seen_1 = seen.clone();
+        let descendant = async move {
+            assert!(try_get_context::<TestBackend, Theme>().is_none());
+            let theme = try_get_reactive_context::<TestBackend, Theme>().unwrap();
+            let theme = theme.as_observable();
+            loop {
+                seen_1.borrow_mut().push(theme.get());
+                theme.until_change().await;
+            }
+        };
+        let _root = mount(WithContext::<TestBackend, _>::new(
+            element("div", descendant),
+            theme.clone(),
+        ));
+        theme.set(Theme::Dark);
+        run_until_stalled();
+        assert_eq!(*seen.borrow(), [Theme::Light, Theme::Dark]);
+    }
 }
@@ -0,0 +1,70 @@
+/*! Driving the executor by hand, for backends without an event loop of their own.
+ *
+ * The test backend and server-side rendering have no browser or GTK main loop to poll the executor.
+ * Such a backend keeps a [ManualDriver] (usually in a thread-local),
+ * hands it the driving future in [drive_executor][crate::backend::BackendTrait::drive_executor],
+ * and polls it with [step][ManualDriver::step] for as long as it wants rendering to go on.
+ */
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Wake, Waker},
+};
+
+use crate::lifecycle;
+
+pub struct ManualDriver {
+    flag: Arc<ScheduledFlag>,
+    waker: Waker,
+    future: RefCell<Option<Pin<Box<dyn Future<Output = ()>>>>>,
+}
+struct ScheduledFlag(AtomicBool);
+impl Wake for ScheduledFlag {
+    fn wake(self: Arc<Self>) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+impl Default for ManualDriver {
+    fn default() -> Self {
+        let flag = Arc::new(ScheduledFlag(AtomicBool::new(false)));
+        Self {
+            waker: flag.clone().into(),
+            flag,
+            future: RefCell::new(None),
+        }
+    }
+}
+impl ManualDriver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /** Drive `future` from now on, instead of the previous one, and ask to be polled.
+     */
+    pub fn set_future<F: Future<Output = ()> + 'static>(&self, future: F) {
+        *self.future.borrow_mut() = Some(Box::pin(future));
+        self.flag.0.store(true, Ordering::SeqCst);
+    }
+    /** Whether the future asked to be polled again.
+     */
+    pub fn is_scheduled(&self) -> bool {
+        self.flag.0.load(Ordering::SeqCst)
+    }
+    /** Poll the future once, then fire the pending [mounted][crate::lifecycle::mounted] callbacks.
+     *
+     * Returns whether the future asked to be polled again.
+     */
+    pub fn step(&self) -> bool {
+        self.flag.0.store(false, Ordering::SeqCst);
+        let mut cx = Context::from_waker(&self.waker);
+        if let Some(fu) = self.future.borrow_mut().as_mut() {
+            let _ = fu.as_mut().poll(&mut cx);
+        }
+        lifecycle::notify_connected();
+        self.is_scheduled()
+    }
+}
@@ -0,0 +1,300 @@
+/*! Showing a fallback when part of the UI fails.
+ *
+ * See [error_boundary].
+ */
+use std::{
+    any::{Any, TypeId},
+    cell::Cell,
+    future::{poll_fn, Future, IntoFuture},
+    pin::{pin, Pin},
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use pin_project_lite::pin_project;
+
+use crate::{
+    backend::BackendTrait,
+    context::ContextMap,
+    vnode::{node_context::WithContext, VNode, VNodeTrait},
+};
+
+/** Why the children of an [error_boundary] failed.
+ */
+pub enum Failure<E> {
+    /// The children returned an error.
+    Error(E),
+    /// The children panicked. Holds the panic payload.
+    Panic(Box<dyn Any + Send>),
+}
+
+impl<E> Failure<E> {
+    /** The panic message, if this is a panic with a string message.
+     */
+    pub fn panic_message(&self) -> Option<&str> {
+        match self {
+            Failure::Error(_) => None,
+            Failure::Panic(payload) => payload
+                .downcast_ref::<&'static str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str)),
+        }
+    }
+}
+
+/** Handed to the fallback of an [error_boundary]. Call [retry][Retry::retry] to render the children again.
+ */
+#[derive(Clone, Default)]
+pub struct Retry {
+    inner: Rc<RetryInner>,
+}
+#[derive(Default)]
+struct RetryInner {
+    requested: Cell<bool>,
+    waker: Cell<Option<Waker>>,
+}
+
+impl Retry {
+    /** Unmount the fallback and render the children anew.
+     */
+    pub fn retry(&self) {
+        self.inner.requested.set(true);
+        if let Some(waker) = self.inner.waker.take() {
+            waker.wake();
+        }
+    }
+    fn poll_requested(&self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.inner.requested.get() {
+            Poll::Ready(())
+        } else {
+            self.inner.waker.set(Some(cx.waker().to_owned()));
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(panic = "unwind")]
+fn catch_panic<R>(f: impl FnOnce() -> R) -> Result<R, Box<dyn Any + Send>> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f))
+}
+#[cfg(not(panic = "unwind"))]
+fn catch_panic<R>(f: impl FnOnce() -> R) -> Result<R, Box<dyn Any + Send>> {
+    Ok(f())
+}
+
+/** Where the tasks spawned under an [error_boundary] send their panics.
+ * Put in the context of the children.
+ */
+#[derive(Default)]
+struct PanicSink {
+    payload: Cell<Option<Box<dyn Any + Send>>>,
+    waker: Cell<Option<Waker>>,
+}
+impl PanicSink {
+    fn send(&self, payload: Box<dyn Any + Send>) {
+        // Keep the first panic; later ones are likely caused by it.
+        if let Some(earlier) = self.payload.take() {
+            self.payload.set(Some(earlier));
+        } else {
+            self.payload.set(Some(payload));
+        }
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+    fn poll_received(&self, cx: &mut Context<'_>) -> Poll<Box<dyn Any + Send>> {
+        match self.payload.take() {
+            Some(payload) => Poll::Ready(payload),
+            None => {
+                self.waker.set(Some(cx.waker().to_owned()));
+                Poll::Pending
+            }
+        }
+    }
+    fn find(context: &ContextMap) -> Option<Rc<Self>> {
+        context
+            .inner
+            .get(&TypeId::of::<Self>())
+            .and_then(|sink| sink.clone().downcast::<Self>().ok())
+    }
+}
+
+pin_project! {
+    /** For futures that run in their own task, e.g. the children of a [Fragment][crate::fragment::Fragment].
+     *
+     * A panic while polling `future` is sent to the nearest [error_boundary] around `vnode`,
+     * and the future is then treated as complete.
+     * With no boundary, the panic carries on unwinding.
+     */
+    pub(crate) struct ForwardPanics<B: BackendTrait, F: Future> {
+        #[pin]
+        future: F,
+        vnode: Rc<VNode<B>>,
+    }
+}
+impl<B: BackendTrait, F: Future> ForwardPanics<B, F> {
+    pub(crate) fn new(future: F, vnode: Rc<VNode<B>>) -> Self {
+        Self { future, vnode }
+    }
+}
+impl<B: BackendTrait, F: Future> Future for ForwardPanics<B, F> {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let future = this.future;
+        match catch_panic(|| future.poll(cx)) {
+            Ok(poll) => poll.map(|_| ()),
+            Err(payload) => match PanicSink::find(this.vnode.get_context_map()) {
+                Some(sink) => {
+                    sink.send(payload);
+                    Poll::Ready(())
+                }
+                None => std::panic::resume_unwind(payload),
+            },
+        }
+    }
+}
+
+/** Render `children`, or `fallback` if they fail.
+ *
+ * `children` is called to create the children, which should return a `Result`.
+ * If they return `Err`, or panic while being polled (on targets that unwind),
+ * they are dropped, unmounting all their nodes, and the future from `fallback` is rendered in their place.
+ * The fallback gets the [Failure] and a [Retry] handle; retrying drops the fallback and calls `children` again.
+ *
+ * That includes panics in the tasks the children run in,
+ * such as those of the children of a [Fragment][crate::fragment::Fragment] or of a keyed list.
+ *
+ * If the children return `Ok`, the boundary returns that value.
+ *
+ * ```rust
+ * # use async_ui_core::{error_boundary::{error_boundary, Failure}, testing::{mount, text, TestBackend}};
+ * async fn load() -> Result<String, String> {
+ *     Err("not found".into())
+ * }
+ * let root = mount(error_boundary::<TestBackend, _, _, _, _>(
+ *     &|| async {
+ *         let content = load().await?;
+ *         text(&content).await;
+ *         Ok(())
+ *     },
+ *     &|failure: Failure<String>, _retry| async move {
+ *         if let Failure::Error(error) = failure {
+ *             text(&error).await;
+ *         }
+ *     },
+ * ));
+ * assert_eq!(root.text_content(), "not found");
+ * ```
+ */
+pub async fn error_boundary<'c, B, T, E, C, F>(
+    children: &'c dyn Fn() -> C,
+    fallback: &'c dyn Fn(Failure<E>, Retry) -> F,
+) -> T
+where
+    B: BackendTrait,
+    C: IntoFuture<Output = Result<T, E>>,
+    F: IntoFuture<Output = ()>,
+{
+    loop {
+        // The children, and the tasks they spawned, are dropped at the end of this block.
+        let result = {
+            let sink = Rc::new(PanicSink::default());
+            let mut attempt = pin!(WithContext::<B, _>::new(children(), sink.clone()));
+            poll_fn(|cx| {
+                if let Poll::Ready(payload) = sink.poll_received(cx) {
+                    return Poll::Ready(Err(Failure::Panic(payload)));
+                }
+                match catch_panic(|| attempt.as_mut().poll(cx)) {
+                    Ok(poll) => poll.map(|result| result.map_err(Failure::Error)),
+                    Err(payload) => Poll::Ready(Err(Failure::Panic(payload))),
+                }
+            })
+            .await
+        };
+        let failure = match result {
+            Ok(value) => return value,
+            Err(failure) => failure,
+        };
+        let retry = Retry::default();
+        let mut fallback = Box::pin(fallback(failure, retry.clone()).into_future());
+        let mut fallback_done = false;
+        poll_fn(|cx| {
+            if !fallback_done {
+                fallback_done = fallback.as_mut().poll(cx).is_ready();
+            }
+            retry.poll_requested(cx)
+        })
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::testing::{fragment, mount, run_until_stalled, text, TestBackend};
+
+    use super::{error_boundary, Retry};
+
+    #[test]
+    fn fallback_replaces_children_until_retry() {
+        let retry: Rc<RefCell<Option<Retry>>> = Default::default();
+        let attempts = Rc::new(RefCell::new(0));
+        let root = mount({
+            let retry = retry.clone();
+            let attempts = attempts.clone();
+            async move {
+                fragment((
+                    text("a"),
+                    error_boundary::<TestBackend, _, _, _, _>(
+                        &|| {
+                            *attempts.borrow_mut() += 1;
+                            let attempt = *attempts.borrow();
+                            async move {
+                                if attempt == 1 {
+                                    panic!("first attempt");
+                                }
+                                text("child").await;
+                                Ok::<_, ()>(())
+                            }
+                        },
+                        &|failure, r| {
+                            assert_eq!(failure.panic_message(), Some("first attempt"));
+                            *retry.borrow_mut() = Some(r);
+                            text("fallback")
+                        },
+                    ),
+                    text("c"),
+                ))
+                .await;
+            }
+        });
+        assert_eq!(root.text_content(), "afallbackc");
+        retry.borrow().as_ref().unwrap().retry();
+        run_until_stalled();
+        assert_eq!(root.text_content(), "achildc");
+    }
+
+    #[test]
+    fn catches_panics_in_child_tasks() {
+        let root = mount(async {
+            fragment((
+                text("a"),
+                error_boundary::<TestBackend, _, _, _, _>(
+                    &|| async {
+                        fragment((text("child"), async { panic!("in a child task") })).await;
+                        Ok::<_, ()>(())
+                    },
+                    &|failure, _retry| {
+                        assert_eq!(failure.panic_message(), Some("in a child task"));
+                        text("fallback")
+                    },
+                ),
+            ))
+            .await;
+        });
+        run_until_stalled();
+        assert_eq!(root.text_content(), "afallback");
+    }
+}
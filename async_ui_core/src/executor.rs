@@ -1,22 +1,256 @@
 use std::{
-    future::{pending, Future},
+    cell::{Cell, RefCell},
+    future::{pending, poll_fn, Future},
+    pin::{pin, Pin},
     rc::Rc,
+    task::{Context, Poll, Waker},
 };
 
 use async_executor::{LocalExecutor, Task};
+use pin_project_lite::pin_project;
+use scoped_async_spawn::SpawnGuard;
+
+#[cfg(feature = "inspect")]
+use crate::inspect::TaskIdFuture;
+use crate::{backend::BackendTrait, error_boundary::ForwardPanics, vnode::WithVNode};
+
+/** Which lane a task runs in.
+ *
+ * Whenever a [UserInput][Priority::UserInput] task is runnable, it runs first.
+ * [Idle][Priority::Idle] tasks only run when nothing else is runnable,
+ * and, if the backend supports it, only when the host is idle (see [BackendTrait::request_idle]).
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Priority {
+    UserInput,
+    #[default]
+    Normal,
+    Idle,
+}
+
+struct Lanes {
+    user_input: LocalExecutor<'static>,
+    normal: LocalExecutor<'static>,
+    idle: LocalExecutor<'static>,
+}
+
+struct IdleState {
+    /// Whether idle tasks may run right now.
+    in_period: Cell<bool>,
+    /// Idle tasks that were woken outside of an idle period.
+    waiting: RefCell<Vec<Waker>>,
+}
+
 thread_local! {
-    pub(crate) static EXECUTOR: Rc<LocalExecutor<'static>> = {
-        let exe= LocalExecutor::new();
-        Rc::new(exe)
-    }
+    static LANES: Rc<Lanes> = Rc::new(Lanes {
+        user_input: LocalExecutor::new(),
+        normal: LocalExecutor::new(),
+        idle: LocalExecutor::new(),
+    });
+    static IDLE: IdleState = const {
+        IdleState {
+            in_period: Cell::new(false),
+            waiting: RefCell::new(Vec::new()),
+        }
+    };
+    static POLLS: Cell<u64> = const { Cell::new(0) };
 }
+
+/** How many times driving futures on this thread have polled a task so far.
+ * Backends use this for metrics.
+ */
+pub fn task_poll_count() -> u64 {
+    POLLS.with(Cell::get)
+}
+
 pub fn spawn_local<F: Future + 'static>(fut: F) -> Task<F::Output> {
-    EXECUTOR.with(|exe| exe.spawn(fut))
+    spawn_local_with_priority(Priority::Normal, fut)
 }
 
-pub(crate) fn get_driving_future() -> impl Future<Output = ()> + 'static {
-    EXECUTOR.with(|exe| {
-        let exe_cpy = exe.clone();
-        async move { exe_cpy.run(pending()).await }
+pub fn spawn_local_with_priority<F: Future + 'static>(
+    priority: Priority,
+    fut: F,
+) -> Task<F::Output> {
+    #[cfg(feature = "inspect")]
+    let fut = TaskIdFuture::new(fut);
+    LANES.with(|lanes| match priority {
+        Priority::UserInput => lanes.user_input.spawn(fut),
+        Priority::Normal => lanes.normal.spawn(fut),
+        Priority::Idle => lanes.idle.spawn(IdleGate { future: fut }),
     })
 }
+
+/** Run `fut` in a task of its own in the given lane, inside the current vnode, until this future is dropped.
+ * Never completes.
+ *
+ * Unlike with [spawn_local_with_priority], `fut` may borrow from its surroundings.
+ * Components handle events this way in the [UserInput][Priority::UserInput] lane,
+ * so that reacting to input doesn't wait behind other work.
+ */
+pub async fn run_with_priority<B: BackendTrait, F: Future<Output = ()>>(
+    priority: Priority,
+    fut: F,
+) {
+    let vnode = B::get_vnode_key().with(Clone::clone);
+    let mut guard = pin!(SpawnGuard::new());
+    let fut = guard.as_mut().convert_future(ForwardPanics::new(
+        WithVNode::new(fut, vnode.clone()),
+        vnode,
+    ));
+    let _task = spawn_local_with_priority(priority, fut);
+    pending::<()>().await
+}
+
+/** Like [spawn_local], but returns `None` if the executor of this thread is already gone,
+ * as happens to futures dropped along with it at thread exit.
+ */
+pub(crate) fn try_spawn_local<F: Future + 'static>(fut: F) -> Option<Task<F::Output>> {
+    #[cfg(feature = "inspect")]
+    let fut = TaskIdFuture::new(fut);
+    let mut fut = Some(fut);
+    LANES
+        .try_with(|lanes| lanes.normal.spawn(fut.take().expect("called once")))
+        .ok()
+}
+
+pin_project! {
+    /** Keeps an idle task from making progress outside of idle periods.
+     */
+    struct IdleGate<F: Future> {
+        #[pin]
+        future: F,
+    }
+}
+impl<F: Future> Future for IdleGate<F> {
+    type Output = F::Output;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if IDLE.with(|idle| idle.in_period.get()) {
+            self.project().future.poll(cx)
+        } else {
+            IDLE.with(|idle| idle.waiting.borrow_mut().push(cx.waker().to_owned()));
+            Poll::Pending
+        }
+    }
+}
+
+/** Let idle tasks run while `f` runs.
+ *
+ * Backends that implement [BackendTrait::request_idle] call this when the host is idle,
+ * and run their executor inside `f`.
+ */
+pub fn run_idle_period<R>(f: impl FnOnce() -> R) -> R {
+    let outer = IDLE.with(|idle| idle.in_period.replace(true));
+    wake_idle_tasks();
+    let res = f();
+    IDLE.with(|idle| idle.in_period.set(outer));
+    res
+}
+
+fn wake_idle_tasks() {
+    let waiting = IDLE.with(|idle| std::mem::take(&mut *idle.waiting.borrow_mut()));
+    waiting.into_iter().for_each(Waker::wake);
+}
+
+/** A future that runs the tasks spawned with [spawn_local], lane by lane. Never completes.
+ *
+ * Between tasks, it checks [BackendTrait::should_yield],
+ * and gives control back to the backend (asking to be polled again) if so.
+ * User input tasks run regardless.
+ */
+pub fn get_driving_future<B: BackendTrait>() -> impl Future<Output = ()> + 'static {
+    let lanes = LANES.with(Clone::clone);
+    async move {
+        let mut user_input = pin!(lanes.user_input.tick());
+        let mut normal = pin!(lanes.normal.tick());
+        let mut idle = pin!(lanes.idle.tick());
+        poll_fn(|cx| {
+            let mut own_idle_period = false;
+            let res = loop {
+                if user_input.as_mut().poll(cx).is_ready() {
+                    user_input.set(lanes.user_input.tick());
+                    POLLS.with(|polls| polls.set(polls.get() + 1));
+                    continue;
+                }
+                if B::should_yield() {
+                    cx.waker().wake_by_ref();
+                    break Poll::Pending;
+                }
+                if normal.as_mut().poll(cx).is_ready() {
+                    normal.set(lanes.normal.tick());
+                    POLLS.with(|polls| polls.set(polls.get() + 1));
+                    continue;
+                }
+                if idle.as_mut().poll(cx).is_ready() {
+                    idle.set(lanes.idle.tick());
+                    POLLS.with(|polls| polls.set(polls.get() + 1));
+                    continue;
+                }
+                let idle_waiting =
+                    IDLE.with(|idle| !idle.in_period.get() && !idle.waiting.borrow().is_empty());
+                if idle_waiting && !B::request_idle() {
+                    // Nothing else to do, so this is as idle as it gets.
+                    own_idle_period = true;
+                    IDLE.with(|idle| idle.in_period.set(true));
+                    wake_idle_tasks();
+                    continue;
+                }
+                break Poll::Pending;
+            };
+            if own_idle_period {
+                IDLE.with(|idle| idle.in_period.set(false));
+            }
+            res
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        test_util::Gate,
+        testing::{mount, run_until_stalled, TestBackend},
+    };
+
+    use super::{run_with_priority, spawn_local, spawn_local_with_priority, Priority};
+
+    #[test]
+    fn lanes_run_in_priority_order() {
+        let log: Rc<RefCell<Vec<Priority>>> = Default::default();
+        let _root = mount(async {});
+        for priority in [Priority::Idle, Priority::Normal, Priority::UserInput] {
+            let log = log.clone();
+            spawn_local_with_priority(priority, async move { log.borrow_mut().push(priority) })
+                .detach();
+        }
+        run_until_stalled();
+        assert_eq!(
+            *log.borrow(),
+            [Priority::UserInput, Priority::Normal, Priority::Idle]
+        );
+    }
+
+    #[test]
+    fn input_runs_ahead_of_a_backlog() {
+        let log: Rc<RefCell<Vec<&str>>> = Default::default();
+        let event = Gate::default();
+        let (log_1, event_1) = (log.clone(), event.clone());
+        let _root = mount(run_with_priority::<TestBackend, _>(
+            Priority::UserInput,
+            async move {
+                event_1.wait().await;
+                log_1.borrow_mut().push("input");
+            },
+        ));
+        for _ in 0..3 {
+            let log = log.clone();
+            spawn_local(async move { log.borrow_mut().push("render") }).detach();
+        }
+        // Woken after the backlog was queued, but handled before it.
+        event.open();
+        run_until_stalled();
+        assert_eq!(*log.borrow(), ["input", "render", "render", "render"]);
+    }
+}
@@ -9,7 +9,12 @@ use async_executor::Task;
 use pin_project_lite::pin_project;
 use scoped_async_spawn::SpawnGuard;
 
-use crate::{backend::BackendTrait, executor::spawn_local, vnode::VNode};
+use crate::{
+    backend::BackendTrait,
+    error_boundary::ForwardPanics,
+    executor::spawn_local,
+    vnode::{enter_vnode, VNode},
+};
 
 trait ChildInnerTrait<'c, B>: 'c
 where
@@ -36,9 +41,7 @@ where
     type Output = ();
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.project();
-        B::get_vnode_key()
-            .set(&this.vnode, || this.future.poll(cx))
-            .map(|_| ())
+        enter_vnode(this.vnode, || this.future.poll(cx)).map(|_| ())
     }
 }
 impl<'c, B, F> ChildInnerTrait<'c, B> for ChildInner<F>
@@ -50,10 +53,13 @@ where
     fn spawn(&mut self, vnode: Rc<VNode<B>>, guard: Pin<&mut SpawnGuard<'c>>) {
         match std::mem::replace(self, Self::Null) {
             ChildInner::NotMounted { component } => {
-                let fut = guard.convert_future(ElementFuture {
-                    future: component,
+                let fut = guard.convert_future(ForwardPanics::new(
+                    ElementFuture {
+                        future: component,
+                        vnode: vnode.clone(),
+                    },
                     vnode,
-                });
+                ));
                 let task = spawn_local(fut);
                 *self = Self::Mounted { _task: task };
             }
@@ -111,3 +111,58 @@ where
         Poll::Pending
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_util::{texts, Gate},
+        testing::{element, fragment, mount, run_until_stalled, text, ElementFuture, TestNode},
+    };
+
+    #[test]
+    fn fragment_renders_in_order() {
+        let root = mount(fragment((
+            text("a"),
+            element("div", fragment((text("b"), text("c")))),
+            text("d"),
+        )));
+        assert_eq!(texts(&root), ["a", "bc", "d"]);
+        let div = root.find_by_tag("div").unwrap();
+        assert_eq!(texts(&div), ["b", "c"]);
+        let c = root.find_by_text("c").unwrap();
+        assert!(c.parent().unwrap().ptr_eq(&div));
+    }
+
+    #[test]
+    fn late_child_keeps_position() {
+        let gate = Gate::default();
+        let gate_1 = gate.clone();
+        let root = mount(fragment((
+            async move {
+                gate_1.wait().await;
+                text("first").await;
+            },
+            text("second"),
+        )));
+        assert_eq!(texts(&root), ["second"]);
+        gate.open();
+        run_until_stalled();
+        assert_eq!(texts(&root), ["first", "second"]);
+    }
+
+    #[test]
+    fn finished_child_is_removed() {
+        let gate = Gate::default();
+        let gate_1 = gate.clone();
+        let root = mount(fragment((
+            text("a"),
+            async move { ElementFuture::new(gate_1.wait(), TestNode::new_text("b")).await },
+            text("c"),
+        )));
+        assert_eq!(texts(&root), ["a", "b", "c"]);
+        gate.open();
+        run_until_stalled();
+        assert_eq!(texts(&root), ["a", "c"]);
+        assert!(root.find_by_text("b").is_none());
+    }
+}
@@ -0,0 +1,389 @@
+/*! Looking at the live vnode tree, for debugging. Needs the `inspect` feature.
+ *
+ * Vnodes only point to their parents, so the tree is recorded as vnodes are entered
+ * (see [enter_vnode][crate::vnode::enter_vnode]), once [enable_inspector] has been called.
+ * Call it before mounting to see the whole tree.
+ *
+ * ```rust
+ * # use async_ui_core::{inspect::{enable_inspector, inspect}, testing::{element, fragment, mount, text, TestBackend}};
+ * enable_inspector::<TestBackend>();
+ * let _root = mount(element("div", fragment((text("a"), text("b")))));
+ * let tree = inspect::<TestBackend>();
+ * println!("{}", tree[0].to_json());
+ * ```
+ */
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fmt::{Debug, Write},
+    future::Future,
+    pin::Pin,
+    rc::{Rc, Weak},
+    task::{Context, Poll},
+};
+
+use pin_project_lite::pin_project;
+
+use crate::{backend::BackendTrait, vnode::VNode};
+
+thread_local! {
+    static CURRENT_TASK: Cell<Option<u64>> = const { Cell::new(None) };
+    static NEXT_TASK: Cell<u64> = const { Cell::new(0) };
+    static REGISTRY: RefCell<Option<Box<dyn Any>>> = const { RefCell::new(None) };
+}
+
+pin_project! {
+    /** Gives each task spawned with [spawn_local][crate::executor::spawn_local] an id,
+     * reported as the owning task of the vnodes it creates.
+     */
+    pub(crate) struct TaskIdFuture<F: Future> {
+        #[pin]
+        future: F,
+        id: u64,
+    }
+}
+impl<F: Future> TaskIdFuture<F> {
+    pub(crate) fn new(future: F) -> Self {
+        let id = NEXT_TASK.with(|next| next.replace(next.get() + 1));
+        Self { future, id }
+    }
+}
+impl<F: Future> Future for TaskIdFuture<F> {
+    type Output = F::Output;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let outer = CURRENT_TASK.with(|current| current.replace(Some(*this.id)));
+        let res = this.future.poll(cx);
+        CURRENT_TASK.with(|current| current.set(outer));
+        res
+    }
+}
+
+struct Entry<B: BackendTrait> {
+    vnode: Weak<VNode<B>>,
+    parent: Option<*const VNode<B>>,
+    task: Option<u64>,
+    /// Order of first sighting.
+    seen: u64,
+}
+struct Registry<B: BackendTrait> {
+    entries: HashMap<*const VNode<B>, Entry<B>>,
+    next_seen: u64,
+}
+impl<B: BackendTrait> Registry<B> {
+    /** Record `vnode`, and then its parents that are not recorded yet. */
+    fn record(&mut self, vnode: &Rc<VNode<B>>, current: Option<Rc<VNode<B>>>) -> &mut Entry<B> {
+        let key = Rc::as_ptr(vnode);
+        if !self.entries.contains_key(&key) {
+            let parent = vnode.parent().cloned().or(current);
+            self.entries.insert(
+                key,
+                Entry {
+                    vnode: Rc::downgrade(vnode),
+                    parent: parent.as_ref().map(Rc::as_ptr),
+                    task: None,
+                    seen: self.next_seen,
+                },
+            );
+            self.next_seen += 1;
+            if let Some(parent) = parent {
+                self.record(&parent, None);
+            }
+        }
+        self.entries.get_mut(&key).expect("just recorded")
+    }
+}
+
+/** Start recording the vnode tree of backend `B` on this thread.
+ */
+pub fn enable_inspector<B: BackendTrait>() {
+    REGISTRY.with(|registry| {
+        *registry.borrow_mut() = Some(Box::new(Registry::<B> {
+            entries: HashMap::new(),
+            next_seen: 0,
+        }))
+    });
+}
+
+/** Record that `vnode` is being entered. Does nothing unless the inspector is enabled.
+ */
+pub(crate) fn track<B: BackendTrait>(vnode: &Rc<VNode<B>>) {
+    REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        let Some(registry) = registry
+            .as_mut()
+            .and_then(|r| r.downcast_mut::<Registry<B>>())
+        else {
+            return;
+        };
+        let vnk = B::get_vnode_key();
+        let current = vnk
+            .is_set()
+            .then(|| vnk.with(Clone::clone))
+            .filter(|current| !Rc::ptr_eq(current, vnode));
+        let entry = registry.record(vnode, current);
+        if entry.task.is_none() {
+            entry.task = CURRENT_TASK.with(Cell::get);
+        }
+    })
+}
+
+/** Forget `vnode`, which is being dropped.
+ */
+pub(crate) fn forget<B: BackendTrait>(vnode: *const VNode<B>) {
+    // The registry may be borrowed already (e.g. while a snapshot drops its last handle on a vnode)
+    // or gone (at thread exit).
+    let _ = REGISTRY.try_with(|registry| {
+        if let Ok(mut registry) = registry.try_borrow_mut() {
+            if let Some(registry) = registry
+                .as_mut()
+                .and_then(|r| r.downcast_mut::<Registry<B>>())
+            {
+                registry.entries.remove(&vnode);
+            }
+        }
+    });
+}
+
+/** Which kind of vnode a [VNodeInfo] describes, one per variant of [VNode].
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VNodeKind {
+    Concrete,
+    Context,
+    Pass,
+    Portal,
+    Suspense,
+    Transition,
+}
+
+impl VNodeKind {
+    /** The name used in the JSON export, e.g. `"concrete"`.
+     */
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VNodeKind::Concrete => "concrete",
+            VNodeKind::Context => "context",
+            VNodeKind::Pass => "pass",
+            VNodeKind::Portal => "portal",
+            VNodeKind::Suspense => "suspense",
+            VNodeKind::Transition => "transition",
+        }
+    }
+}
+
+/** What the inspector knows about a vnode.
+ */
+#[derive(Clone, Debug)]
+pub struct VNodeInfo {
+    pub kind: VNodeKind,
+    /// For pass vnodes, the index they wrap positions with.
+    pub index: Option<usize>,
+    /// Type names of the context values available.
+    pub context_types: Vec<&'static str>,
+    /// For concrete vnodes, the node they render into.
+    pub node: Option<String>,
+    /// For concrete vnodes, the position index and description of each node added.
+    pub child_nodes: Vec<(Vec<usize>, String)>,
+    /// The task running when the vnode was first entered.
+    pub task: Option<u64>,
+    pub children: Vec<VNodeInfo>,
+}
+
+/** Snapshot the recorded vnode tree. Returns the roots.
+ *
+ * Vnodes that have been dropped are forgotten. Returns nothing if the inspector is not enabled.
+ */
+pub fn inspect<B: BackendTrait>() -> Vec<VNodeInfo>
+where
+    B::Node: Debug,
+{
+    REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        let Some(registry) = registry
+            .as_mut()
+            .and_then(|r| r.downcast_mut::<Registry<B>>())
+        else {
+            return Vec::new();
+        };
+        let mut order: Vec<(&*const VNode<B>, &Entry<B>)> = registry.entries.iter().collect();
+        order.sort_unstable_by_key(|(_, entry)| entry.seen);
+        let mut children: HashMap<*const VNode<B>, Vec<*const VNode<B>>> = HashMap::new();
+        let mut roots = Vec::new();
+        for (&key, entry) in order {
+            match entry
+                .parent
+                .filter(|parent| registry.entries.contains_key(parent))
+            {
+                Some(parent) => children.entry(parent).or_default().push(key),
+                None => roots.push(key),
+            }
+        }
+        roots
+            .into_iter()
+            .map(|root| build(&registry.entries, &children, root))
+            .collect()
+    })
+}
+
+fn build<B: BackendTrait>(
+    entries: &HashMap<*const VNode<B>, Entry<B>>,
+    children: &HashMap<*const VNode<B>, Vec<*const VNode<B>>>,
+    key: *const VNode<B>,
+) -> VNodeInfo
+where
+    B::Node: Debug,
+{
+    let entry = &entries[&key];
+    let vnode = entry
+        .vnode
+        .upgrade()
+        .expect("vnodes are forgotten when dropped");
+    let mut info = vnode.describe();
+    info.task = entry.task;
+    info.children = children
+        .get(&key)
+        .into_iter()
+        .flatten()
+        .map(|&child| build(entries, children, child))
+        .collect();
+    info
+}
+
+fn write_json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+impl VNodeInfo {
+    /** This vnode and its descendants as a JSON object.
+     */
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out);
+        out
+    }
+    fn write_json(&self, out: &mut String) {
+        out.push_str("{\"kind\":");
+        write_json_str(out, self.kind.as_str());
+        out.push_str(",\"index\":");
+        match self.index {
+            Some(index) => {
+                let _ = write!(out, "{index}");
+            }
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"context_types\":[");
+        for (i, name) in self.context_types.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            write_json_str(out, name);
+        }
+        out.push_str("],\"node\":");
+        match &self.node {
+            Some(node) => write_json_str(out, node),
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"child_nodes\":[");
+        for (i, (position, node)) in self.child_nodes.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(out, "{{\"position\":{position:?},\"node\":");
+            write_json_str(out, node);
+            out.push('}');
+        }
+        out.push_str("],\"task\":");
+        match self.task {
+            Some(task) => {
+                let _ = write!(out, "{task}");
+            }
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"children\":[");
+        for (i, child) in self.children.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            child.write_json(out);
+        }
+        out.push_str("]}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use observables::cell::ReactiveCell;
+
+    use crate::{
+        switch::show_when,
+        testing::{element, fragment, mount, run_until_stalled, text, TestBackend},
+        vnode::node_context::WithContext,
+    };
+
+    use super::{enable_inspector, inspect, VNodeInfo, VNodeKind};
+
+    fn count(infos: &[VNodeInfo]) -> usize {
+        infos.iter().map(|info| 1 + count(&info.children)).sum()
+    }
+
+    #[test]
+    fn inspector_sees_the_tree() {
+        enable_inspector::<TestBackend>();
+        let _root = mount(element(
+            "div",
+            WithContext::<TestBackend, _>::new(fragment((text("a"), text("b"))), Rc::new(7u8)),
+        ));
+        let tree = inspect::<TestBackend>();
+        assert_eq!(tree.len(), 1);
+        let div = &tree[0].children[0];
+        assert_eq!(div.kind, VNodeKind::Concrete);
+        assert_eq!(div.child_nodes.len(), 2);
+        let context = &div.children[0];
+        assert_eq!(context.kind, VNodeKind::Context);
+        assert_eq!(context.context_types, ["u8"]);
+        let indices: Vec<_> = context.children.iter().map(|c| c.index).collect();
+        assert_eq!(indices, [Some(0), Some(1)]);
+        assert_ne!(context.children[0].task, context.children[1].task);
+        assert!(tree[0].to_json().contains(r#""node":"\"a\"""#));
+    }
+
+    #[test]
+    fn dropped_vnodes_are_forgotten() {
+        enable_inspector::<TestBackend>();
+        let shown = Rc::new(ReactiveCell::new(false));
+        let shown_1 = shown.clone();
+        let _root = mount(async move {
+            let children = || element("div", text("a"));
+            fragment((show_when::<TestBackend, _>(
+                &shown_1.as_observable(),
+                &children,
+            ),))
+            .await
+        });
+        let hidden = count(&inspect::<TestBackend>());
+        shown.set(true);
+        run_until_stalled();
+        assert!(count(&inspect::<TestBackend>()) > hidden);
+        shown.set(false);
+        run_until_stalled();
+        assert_eq!(count(&inspect::<TestBackend>()), hidden);
+    }
+}
@@ -1,10 +1,21 @@
 pub mod backend;
 pub use mount::mount;
 pub mod context;
+pub mod driver;
+pub mod error_boundary;
 pub mod executor;
 pub mod fragment;
+#[cfg(feature = "inspect")]
+pub mod inspect;
+pub mod lifecycle;
 pub mod list;
 pub mod mount;
+pub mod node_ref;
 pub mod position;
+pub mod switch;
+#[cfg(test)]
+mod test_util;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod vnode;
 pub use fragment::__private_macro_only;
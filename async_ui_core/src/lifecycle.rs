@@ -0,0 +1,190 @@
+/*! Running code when nodes enter and leave the tree.
+ *
+ * Each backend's `ElementFuture` takes `on_mount` and `on_unmount` callbacks (stored in a [Lifecycle]),
+ * and [mounted] waits until a node is connected to the root
+ * (e.g. attached to the document, so that it can be measured or focused).
+ */
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use crate::backend::BackendTrait;
+
+type Callback<N> = Box<dyn FnOnce(&N)>;
+
+/** The `on_mount` and `on_unmount` callbacks of an element.
+ */
+pub struct Lifecycle<N> {
+    on_mount: Vec<Callback<N>>,
+    on_unmount: Vec<Callback<N>>,
+}
+
+impl<N> Default for Lifecycle<N> {
+    fn default() -> Self {
+        Self {
+            on_mount: Vec::new(),
+            on_unmount: Vec::new(),
+        }
+    }
+}
+
+impl<N> Lifecycle<N> {
+    pub fn add_on_mount<F: FnOnce(&N) + 'static>(&mut self, callback: F) {
+        self.on_mount.push(Box::new(callback));
+    }
+    pub fn add_on_unmount<F: FnOnce(&N) + 'static>(&mut self, callback: F) {
+        self.on_unmount.push(Box::new(callback));
+    }
+    /** Call right after the node has been added to its parent.
+     */
+    pub fn mount(&mut self, node: &N) {
+        self.on_mount.drain(..).for_each(|callback| callback(node));
+    }
+    /** Call right before the node is removed from its parent.
+     */
+    pub fn unmount(&mut self, node: &N) {
+        self.on_unmount
+            .drain(..)
+            .for_each(|callback| callback(node));
+    }
+}
+
+struct Waiting {
+    is_connected: Box<dyn Fn() -> bool>,
+    waker: Waker,
+    cancelled: Rc<Cell<bool>>,
+}
+
+thread_local! {
+    static WAITING: RefCell<Vec<Waiting>> = const { RefCell::new(Vec::new()) };
+}
+
+/** Wake the [mounted] futures whose nodes are now connected.
+ *
+ * Backends call this every time their executor has finished a run,
+ * so that [mounted] resolves after the DOM or widget tree has been updated.
+ */
+pub fn notify_connected() {
+    let ready: Vec<Waker> = WAITING.with(|waiting| {
+        let mut ready = Vec::new();
+        waiting.borrow_mut().retain(|w| {
+            if w.cancelled.get() {
+                false
+            } else if (w.is_connected)() {
+                ready.push(w.waker.to_owned());
+                false
+            } else {
+                true
+            }
+        });
+        ready
+    });
+    ready.into_iter().for_each(Waker::wake);
+}
+
+/** Future returned by [mounted].
+ */
+pub struct Mounted<B: BackendTrait> {
+    node: B::Node,
+    registration: Option<Rc<Cell<bool>>>,
+}
+
+/** Wait until `node` is connected to the root, as reported by [BackendTrait::is_connected].
+ *
+ * Nodes rendered behind an inactive portal, or held back by a suspense, are not connected.
+ */
+pub fn mounted<B: BackendTrait>(node: B::Node) -> Mounted<B> {
+    Mounted {
+        node,
+        registration: None,
+    }
+}
+
+// No field is structurally pinned.
+impl<B: BackendTrait> Unpin for Mounted<B> {}
+
+impl<B: BackendTrait> Future for Mounted<B> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if let Some(previous) = this.registration.take() {
+            previous.set(true);
+        }
+        if B::is_connected(&this.node) {
+            return Poll::Ready(());
+        }
+        let cancelled = Rc::new(Cell::new(false));
+        let node = this.node.clone();
+        WAITING.with(|waiting| {
+            waiting.borrow_mut().push(Waiting {
+                is_connected: Box::new(move || B::is_connected(&node)),
+                waker: cx.waker().to_owned(),
+                cancelled: cancelled.clone(),
+            })
+        });
+        this.registration = Some(cancelled);
+        Poll::Pending
+    }
+}
+
+impl<B: BackendTrait> Drop for Mounted<B> {
+    fn drop(&mut self) {
+        if let Some(registration) = self.registration.take() {
+            registration.set(true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        test_util::Gate,
+        testing::{fragment, mount, run_until_stalled, ElementFuture, TestBackend, TestNode},
+        vnode::node_portal::create_portal_pair,
+    };
+
+    use super::mounted;
+
+    #[test]
+    fn lifecycle_hooks_and_mounted() {
+        let (exit_gate, done_gate) = (Gate::default(), Gate::default());
+        let (exit_gate_1, done_gate_1) = (exit_gate.clone(), done_gate.clone());
+        let log: Rc<RefCell<Vec<&str>>> = Default::default();
+        let (log_1, log_2, log_3) = (log.clone(), log.clone(), log.clone());
+        let node = TestNode::new_element("x");
+        let node_1 = node.clone();
+        let _root = mount(async move {
+            let (entry, exit) = create_portal_pair::<TestBackend>();
+            fragment((
+                entry.mount(
+                    ElementFuture::new(done_gate_1.wait(), node)
+                        .on_mount(move |_| log_1.borrow_mut().push("mount"))
+                        .on_unmount(move |_| log_2.borrow_mut().push("unmount")),
+                ),
+                async move {
+                    mounted::<TestBackend>(node_1).await;
+                    log_3.borrow_mut().push("connected");
+                },
+                async move {
+                    exit_gate_1.wait().await;
+                    exit.await;
+                },
+            ))
+            .await;
+        });
+        assert_eq!(*log.borrow(), ["mount"]);
+        exit_gate.open();
+        run_until_stalled();
+        assert_eq!(*log.borrow(), ["mount", "connected"]);
+        done_gate.open();
+        run_until_stalled();
+        assert_eq!(*log.borrow(), ["mount", "connected", "unmount"]);
+    }
+}
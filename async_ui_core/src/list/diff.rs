@@ -0,0 +1,129 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+};
+
+/** How to turn a list with the old keys into a list with the new keys.
+ *
+ * Produced by [diff_keys].
+ */
+#[derive(Debug, PartialEq, Eq)]
+pub struct KeyedDiff {
+    /** For each item in the new list, the index of the item with the same key in the old list.
+     * `None` for items that have to be created.
+     */
+    pub sources: Vec<Option<usize>>,
+    /** For each item in the new list, whether it is already in the right place relative to the others.
+     * Reused items that are not in place have to be moved.
+     */
+    pub in_place: Vec<bool>,
+    /** Indices (ascending) of the items of the old list whose keys are gone.
+     */
+    pub removed: Vec<usize>,
+}
+
+/** Diff two key sequences, moving as few items as possible.
+ *
+ * The reused items that keep their relative order form a longest increasing subsequence
+ * of old indices; only the rest of the reused items need to be moved.
+ * Duplicate keys are matched up in order.
+ */
+pub fn diff_keys<K: Hash + Eq>(old: &[K], new: &[K]) -> KeyedDiff {
+    let mut old_indices: HashMap<&K, VecDeque<usize>> = HashMap::new();
+    for (index, key) in old.iter().enumerate() {
+        old_indices.entry(key).or_default().push_back(index);
+    }
+    let sources: Vec<Option<usize>> = new
+        .iter()
+        .map(|key| old_indices.get_mut(key).and_then(VecDeque::pop_front))
+        .collect();
+    let mut removed: Vec<usize> = old_indices.into_values().flatten().collect();
+    removed.sort_unstable();
+    let in_place = in_place(&sources);
+    KeyedDiff {
+        sources,
+        in_place,
+        removed,
+    }
+}
+
+/** For a reordering where the item now at index `i` was at `sources[i]`
+ * (like [Change::Reorder][super::Change::Reorder]),
+ * whether each item is already in the right place relative to the others.
+ * Only the other items have to be moved.
+ */
+pub fn reorder_in_place(sources: &[usize]) -> Vec<bool> {
+    let sources: Vec<Option<usize>> = sources.iter().copied().map(Some).collect();
+    in_place(&sources)
+}
+
+fn in_place(sources: &[Option<usize>]) -> Vec<bool> {
+    let reused: Vec<(usize, usize)> = sources
+        .iter()
+        .enumerate()
+        .filter_map(|(new_index, source)| source.map(|old_index| (new_index, old_index)))
+        .collect();
+    let mut in_place = vec![false; sources.len()];
+    for position in longest_increasing_subsequence(&reused) {
+        in_place[reused[position].0] = true;
+    }
+    in_place
+}
+
+/** Positions (ascending) in `seq` of a longest strictly increasing subsequence of the `.1`s.
+ */
+fn longest_increasing_subsequence(seq: &[(usize, usize)]) -> Vec<usize> {
+    // tails[l] is the position of the smallest last value of an increasing subsequence of length l + 1.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut predecessors: Vec<Option<usize>> = Vec::with_capacity(seq.len());
+    for (position, (_, value)) in seq.iter().enumerate() {
+        let length = tails.partition_point(|&tail| seq[tail].1 < *value);
+        predecessors.push(length.checked_sub(1).map(|prev| tails[prev]));
+        match tails.get_mut(length) {
+            Some(tail) => *tail = position,
+            None => tails.push(position),
+        }
+    }
+    let mut result = Vec::with_capacity(tails.len());
+    let mut current = tails.last().copied();
+    while let Some(position) = current {
+        result.push(position);
+        current = predecessors[position];
+    }
+    result.reverse();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_keys, reorder_in_place, KeyedDiff};
+
+    #[test]
+    fn reorder_moves_fewest() {
+        let diff = diff_keys(&['a', 'b', 'c', 'd', 'e'], &['b', 'c', 'e', 'a', 'x']);
+        assert_eq!(
+            diff,
+            KeyedDiff {
+                sources: vec![Some(1), Some(2), Some(4), Some(0), None],
+                in_place: vec![true, true, true, false, false],
+                removed: vec![3],
+            }
+        );
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let diff = diff_keys(&[1, 2, 1], &[1, 1, 1]);
+        assert_eq!(diff.sources, [Some(0), Some(2), None]);
+        assert_eq!(diff.in_place, [true, true, false]);
+        assert_eq!(diff.removed, [1]);
+    }
+
+    #[test]
+    fn reorder() {
+        assert_eq!(
+            reorder_in_place(&[4, 0, 1, 3, 2]),
+            [false, true, true, false, true]
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use std::{future::IntoFuture, pin::pin, rc::Rc};
+
+use observables::{ObservableAs, ObservableAsExt};
+use scoped_async_spawn::SpawnGuard;
+
+use crate::{
+    backend::BackendTrait,
+    error_boundary::ForwardPanics,
+    executor::spawn_local,
+    vnode::{
+        node_concrete::{ConcreteNodeVNode, RefNode},
+        VNode, VNodeTrait, WithVNode,
+    },
+};
+
+use super::{keyed::Item, reorder_in_place, Change, ListModel, ListModelPrivateAPIs};
+
+/// Counts as a listener of the model while alive, so the model keeps the log of its changes.
+struct Listening<'c, T: Clone>(&'c dyn ObservableAs<ListModel<T>>);
+impl<'c, T: Clone> Drop for Listening<'c, T> {
+    fn drop(&mut self) {
+        self.0.visit(|model| {
+            let model = ListModelPrivateAPIs(model);
+            model
+                .total_listeners()
+                .set(model.total_listeners().get() - 1);
+        });
+    }
+}
+
+/** The body of the backends' `list`: render the items of `data` into `container`,
+ * following the changes made to the model one by one.
+ *
+ * Must run inside the vnode of `container`, which should hold nothing else.
+ * Each item's nodes go right before its own anchor node, made by `new_anchor`.
+ * Items keep running until they are removed from the model (or the model is reset).
+ *
+ * The first items may be `adopted` rather than created: each is an anchor already in `container`
+ * and the vnode to render into, e.g. one over server-rendered nodes when hydrating.
+ * Adopted items beyond the end of the model are removed, along with their nodes.
+ */
+pub async fn items<'c, B, T, F>(
+    container: B::Node,
+    new_anchor: &dyn Fn() -> B::Node,
+    adopted: Vec<(B::Node, Rc<VNode<B>>)>,
+    data: &'c dyn ObservableAs<ListModel<T>>,
+    render: &'c dyn Fn(T) -> F,
+) where
+    B: BackendTrait,
+    T: Clone + 'c,
+    F: IntoFuture + 'c,
+{
+    let parent_vnode = B::get_vnode_key().with(Clone::clone);
+    let parent_context = parent_vnode.get_context_map();
+    let mut guard = pin!(SpawnGuard::new());
+    let mut create_item =
+        |value: &T, before: Option<&B::Node>, adopted: Option<(B::Node, Rc<VNode<B>>)>| {
+            let (anchor, vnode) = adopted.unwrap_or_else(|| {
+                let mut anchor = new_anchor();
+                B::add_child_node(&mut container.clone(), &mut anchor, before);
+                let vnode: Rc<VNode<B>> = Rc::new(
+                    ConcreteNodeVNode::new(
+                        RefNode::<B>::Sibling {
+                            parent: container.clone(),
+                            sibling: anchor.clone(),
+                        },
+                        parent_context.clone(),
+                    )
+                    .into(),
+                );
+                (anchor, vnode)
+            });
+            let fut = WithVNode::new(render(value.to_owned()).into_future(), vnode.clone());
+            let fut = guard
+                .as_mut()
+                .convert_future(ForwardPanics::new(fut, vnode.clone()));
+            Item {
+                anchor,
+                vnode,
+                _task: spawn_local(fut),
+            }
+        };
+    let remove_item = |item: Item<B>| {
+        let mut anchor = item.anchor.clone();
+        // Dropping the task removes the item's nodes.
+        std::mem::drop(item);
+        B::del_child_node(&mut container.clone(), &mut anchor);
+    };
+    let mut items: Vec<Item<B>> = Vec::new();
+    let mut adopted = adopted.into_iter();
+    let mut last_version = data.visit(|model| {
+        for value in model.underlying_vector().iter() {
+            items.push(create_item(value, None, adopted.next()));
+        }
+        let model = ListModelPrivateAPIs(model);
+        model
+            .total_listeners()
+            .set(model.total_listeners().get() + 1);
+        model.get_version()
+    });
+    let _listening = Listening(data);
+    for (mut anchor, vnode) in adopted {
+        if let VNode::ConcreteNode(concrete) = &*vnode {
+            concrete.finish_hydration();
+        }
+        B::del_child_node(&mut container.clone(), &mut anchor);
+    }
+    data.skip_initial()
+        .for_each(|model| {
+            let model_priv = ListModelPrivateAPIs(model);
+            for change in model_priv.changes_since_version(last_version) {
+                match change {
+                    Change::Splice {
+                        remove_range,
+                        replace_with,
+                    } => {
+                        let mut removed = items.split_off(remove_range.start);
+                        let right = removed.split_off(remove_range.len());
+                        for item in removed {
+                            remove_item(item);
+                        }
+                        let before = right.first().and_then(Item::first_node);
+                        for value in replace_with.iter() {
+                            items.push(create_item(value, before.as_ref(), None));
+                        }
+                        items.extend(right);
+                    }
+                    Change::Remove { index } => {
+                        remove_item(items.remove(*index));
+                    }
+                    Change::Insert { index, value } => {
+                        let before = items.get(*index).and_then(Item::first_node);
+                        let item = create_item(value, before.as_ref(), None);
+                        items.insert(*index, item);
+                    }
+                    Change::Move { from, to } => {
+                        let item = items.remove(*from);
+                        if from != to {
+                            let before = items.get(*to).and_then(Item::first_node);
+                            item.move_before(before.as_ref());
+                        }
+                        items.insert(*to, item);
+                    }
+                    Change::Reorder { sources } => {
+                        let mut old_items: Vec<Option<Item<B>>> =
+                            items.drain(..).map(Some).collect();
+                        // Starting from the end, put each item right before the next one.
+                        let mut before: Option<B::Node> = None;
+                        for (source, in_place) in
+                            sources.iter().zip(reorder_in_place(sources)).rev()
+                        {
+                            if let Some(item) = old_items[*source].take() {
+                                if !in_place {
+                                    item.move_before(before.as_ref());
+                                }
+                                before = item.first_node();
+                                items.push(item);
+                            }
+                        }
+                        items.reverse();
+                    }
+                    Change::Reset => {
+                        for item in items.drain(..) {
+                            remove_item(item);
+                        }
+                        for value in model.underlying_vector().iter() {
+                            items.push(create_item(value, None, None));
+                        }
+                        // Everything is up to date now.
+                        break;
+                    }
+                }
+            }
+            last_version = model_priv.get_version();
+            model_priv
+                .pending_listeners()
+                .set(model_priv.pending_listeners().get() - 1);
+        })
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use observables::cell::ReactiveCell;
+
+    use crate::{
+        list::ListModel,
+        testing::{mount, run_until_stalled, text, ElementFuture, TestBackend, TestNode},
+    };
+
+    use super::items;
+
+    #[test]
+    fn items_follow_the_changes() {
+        let model = Rc::new(ReactiveCell::new(ListModel::from_iter(
+            ["a", "b", "c", "d"].into_iter(),
+        )));
+        let renders = Rc::new(Cell::new(0));
+        let ul = TestNode::new_element("ul");
+        let (model_1, renders_1, ul_1) = (model.clone(), renders.clone(), ul.clone());
+        mount(async move {
+            let render = |item: &'static str| {
+                renders_1.set(renders_1.get() + 1);
+                text(item)
+            };
+            let data = model_1.as_observable();
+            let items = items::<TestBackend, _, _>(
+                ul_1.clone(),
+                &|| TestNode::new_element("anchor"),
+                Vec::new(),
+                &data,
+                &render,
+            );
+            ElementFuture::new(items, ul_1).await
+        });
+        let items = || -> Vec<String> { ul.children().iter().filter_map(TestNode::text).collect() };
+        assert_eq!(items(), ["a", "b", "c", "d"]);
+        let [a, b, c, d] = ["a", "b", "c", "d"].map(|t| ul.find_by_text(t).unwrap());
+
+        model.borrow_mut().transaction(|model| {
+            model.remove(2);
+            model.move_item(2, 0);
+            model.insert(1, "e");
+        });
+        run_until_stalled();
+        assert_eq!(items(), ["d", "e", "a", "b"]);
+        assert!(ul.find_by_text("d").unwrap().ptr_eq(&d));
+        assert!(c.parent().is_none());
+        assert_eq!(renders.get(), 5);
+
+        model.borrow_mut().swap(0, 3);
+        run_until_stalled();
+        assert_eq!(items(), ["b", "e", "a", "d"]);
+        assert!(ul.find_by_text("a").unwrap().ptr_eq(&a));
+        assert!(ul.find_by_text("b").unwrap().ptr_eq(&b));
+        assert_eq!(renders.get(), 5);
+
+        model.borrow_mut().splice(1..3, ["f"].into_iter());
+        run_until_stalled();
+        assert_eq!(items(), ["b", "f", "d"]);
+        assert!(a.parent().is_none());
+        assert_eq!(renders.get(), 6);
+        // One anchor per item, and nothing else.
+        assert_eq!(ul.children().len(), 6);
+    }
+}
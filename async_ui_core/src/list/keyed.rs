@@ -0,0 +1,195 @@
+use std::{future::IntoFuture, hash::Hash, pin::pin, rc::Rc};
+
+use async_task::Task;
+use observables::{ObservableAs, ObservableAsExt};
+use scoped_async_spawn::SpawnGuard;
+
+use crate::{
+    backend::BackendTrait,
+    error_boundary::ForwardPanics,
+    executor::spawn_local,
+    vnode::{
+        node_concrete::{ConcreteNodeVNode, RefNode},
+        VNode, VNodeTrait, WithVNode,
+    },
+};
+
+use super::{diff_keys, KeyedDiff, ListModel};
+
+/** A rendered list item: its task, and the vnode it renders into, right before its anchor.
+ */
+pub(super) struct Item<B: BackendTrait> {
+    pub(super) anchor: B::Node,
+    pub(super) vnode: Rc<VNode<B>>,
+    pub(super) _task: Task<()>,
+}
+
+impl<B: BackendTrait> Item<B> {
+    /// The first node of the item, which is its anchor if it has rendered nothing.
+    pub(super) fn first_node(&self) -> Option<B::Node> {
+        match &*self.vnode {
+            VNode::ConcreteNode(concrete) => concrete.with_first_node(|first| first.cloned()),
+            _ => None,
+        }
+    }
+    /// Move the nodes of the item, then its anchor, to before `before`.
+    pub(super) fn move_before(&self, before: Option<&B::Node>) {
+        if let VNode::ConcreteNode(concrete) = &*self.vnode {
+            concrete.move_before(before);
+        }
+    }
+}
+
+/** The body of the backends' `keyed_list`: render the items of `data` into `container`,
+ * reconciling by key whenever the model changes.
+ *
+ * Must run inside the vnode of `container`, which should hold nothing else.
+ * Each item's nodes go right before its own anchor node, made by `new_anchor`.
+ * Items whose keys are still there keep running and are moved, with as few moves as possible;
+ * items whose keys are gone are dropped and their nodes removed.
+ */
+pub async fn keyed_items<'c, B, T, K, F>(
+    container: B::Node,
+    new_anchor: &dyn Fn() -> B::Node,
+    data: &'c dyn ObservableAs<ListModel<T>>,
+    render: &'c dyn Fn(T) -> F,
+    key: &'c dyn Fn(&T) -> K,
+) where
+    B: BackendTrait,
+    T: Clone + 'c,
+    K: Hash + Eq + 'c,
+    F: IntoFuture + 'c,
+{
+    let parent_vnode = B::get_vnode_key().with(Clone::clone);
+    let parent_context = parent_vnode.get_context_map();
+    let mut guard = pin!(SpawnGuard::new());
+    let mut create_item = |value: &T, before: Option<&B::Node>| {
+        let mut anchor = new_anchor();
+        B::add_child_node(&mut container.clone(), &mut anchor, before);
+        let vnode: Rc<VNode<B>> = Rc::new(
+            ConcreteNodeVNode::new(
+                RefNode::<B>::Sibling {
+                    parent: container.clone(),
+                    sibling: anchor.clone(),
+                },
+                parent_context.clone(),
+            )
+            .into(),
+        );
+        let fut = WithVNode::new(render(value.to_owned()).into_future(), vnode.clone());
+        let fut = guard
+            .as_mut()
+            .convert_future(ForwardPanics::new(fut, vnode.clone()));
+        Item {
+            anchor,
+            vnode,
+            _task: spawn_local(fut),
+        }
+    };
+    let mut keys: Vec<K> = Vec::new();
+    let mut items: Vec<Item<B>> = Vec::new();
+    data.for_each(|model| {
+        let values = model.underlying_vector();
+        let new_keys: Vec<K> = values.iter().map(key).collect();
+        let KeyedDiff {
+            sources,
+            in_place,
+            removed,
+        } = diff_keys(&keys, &new_keys);
+        let mut old_items: Vec<Option<Item<B>>> = items.drain(..).map(Some).collect();
+        for index in removed {
+            if let Some(item) = old_items[index].take() {
+                let mut anchor = item.anchor.clone();
+                // Dropping the task removes the item's nodes.
+                std::mem::drop(item);
+                B::del_child_node(&mut container.clone(), &mut anchor);
+            }
+        }
+        // Starting from the end, put each item right before the next one.
+        let mut before: Option<B::Node> = None;
+        for (index, value) in values.iter().enumerate().rev() {
+            let item = match sources[index].and_then(|source| old_items[source].take()) {
+                Some(item) => {
+                    if !in_place[index] {
+                        item.move_before(before.as_ref());
+                    }
+                    item
+                }
+                None => create_item(value, before.as_ref()),
+            };
+            before = item.first_node();
+            items.push(item);
+        }
+        items.reverse();
+        keys = new_keys;
+    })
+    .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use observables::cell::ReactiveCell;
+
+    use crate::{
+        list::ListModel,
+        testing::{mount, run_until_stalled, text, ElementFuture, TestBackend, TestNode},
+    };
+
+    use super::keyed_items;
+
+    #[test]
+    fn keyed_items_keep_or_drop_their_nodes() {
+        let model = Rc::new(ReactiveCell::new(ListModel::from_iter(
+            ["a", "b", "c", "d"].into_iter(),
+        )));
+        let renders = Rc::new(Cell::new(0));
+        let ul = TestNode::new_element("ul");
+        let (model_1, renders_1, ul_1) = (model.clone(), renders.clone(), ul.clone());
+        mount(async move {
+            let render = |item: &'static str| {
+                renders_1.set(renders_1.get() + 1);
+                text(item)
+            };
+            let data = model_1.as_observable();
+            let items = keyed_items::<TestBackend, _, _, _>(
+                ul_1.clone(),
+                &|| TestNode::new_element("anchor"),
+                &data,
+                &render,
+                &|item: &&str| *item,
+            );
+            ElementFuture::new(items, ul_1).await
+        });
+        let items = || -> Vec<String> { ul.children().iter().filter_map(TestNode::text).collect() };
+        assert_eq!(items(), ["a", "b", "c", "d"]);
+        let [a, b, c, d] = ["a", "b", "c", "d"].map(|t| ul.find_by_text(t).unwrap());
+
+        model.borrow_mut().transaction(|model| {
+            model.remove(2);
+            model.move_item(2, 0);
+        });
+        run_until_stalled();
+        assert_eq!(items(), ["d", "a", "b"]);
+        assert!(ul.find_by_text("d").unwrap().ptr_eq(&d));
+        assert!(ul.find_by_text("b").unwrap().ptr_eq(&b));
+        assert!(c.parent().is_none());
+        assert_eq!(renders.get(), 4);
+
+        model.borrow_mut().splice(0..3, ["b", "e", "d"].into_iter());
+        run_until_stalled();
+        assert_eq!(items(), ["b", "e", "d"]);
+        assert!(ul.find_by_text("b").unwrap().ptr_eq(&b));
+        assert!(ul.find_by_text("d").unwrap().ptr_eq(&d));
+        assert!(a.parent().is_none());
+        assert_eq!(renders.get(), 5);
+
+        model.borrow_mut().swap(0, 2);
+        run_until_stalled();
+        assert_eq!(items(), ["d", "e", "b"]);
+        assert_eq!(renders.get(), 5);
+        // One anchor per item, and nothing else.
+        assert_eq!(ul.children().len(), 6);
+    }
+}
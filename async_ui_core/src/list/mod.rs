@@ -1,10 +1,19 @@
 use std::{
     cell::Cell,
+    cmp::Ordering,
     collections::VecDeque,
     ops::{Bound, Range, RangeBounds},
 };
 
 use im_rc::Vector;
+use smallvec::SmallVec;
+
+mod diff;
+mod items;
+mod keyed;
+pub use diff::{diff_keys, reorder_in_place, KeyedDiff};
+pub use items::items;
+pub use keyed::keyed_items;
 
 #[derive(Clone)]
 pub enum Change<T> {
@@ -19,11 +28,30 @@ pub enum Change<T> {
         index: usize,
         value: T,
     },
+    /// The item at `from` is taken out and put back in at `to`.
+    Move {
+        from: usize,
+        to: usize,
+    },
+    /// The item now at index `i` was at index `sources[i]`.
+    Reorder {
+        sources: Vec<usize>,
+    },
+    /// The listener is too far behind to catch up change by change.
+    /// Rebuild everything from [ListModel::underlying_vector] and skip the remaining changes.
+    Reset,
 }
+const DEFAULT_MAX_LOG_LEN: usize = 256;
+/// The changes that make up one version (more than one if made in a transaction).
+type LogEntry<T> = SmallVec<[Change<T>; 1]>;
 pub struct ListModel<T: Clone> {
     head: Vector<T>,
-    log: VecDeque<Change<T>>,
+    log: VecDeque<LogEntry<T>>,
     log_start_version: u64,
+    batch: Option<Vec<Change<T>>>,
+    max_log_len: usize,
+    /// Handed to listeners that are too far behind.
+    reset: Change<T>,
     total_listeners: Cell<usize>,
     pending_listeners: Cell<usize>,
 }
@@ -64,6 +92,61 @@ fn apply_change<T: Clone>(vector: &mut Vector<T>, change: Change<T>) {
         Change::Insert { index, value } => {
             vector.insert(index, value);
         }
+        Change::Move { from, to } => {
+            let value = vector.remove(from);
+            vector.insert(to, value);
+        }
+        Change::Reorder { sources } => {
+            *vector = sources
+                .iter()
+                .map(|source| vector[*source].clone())
+                .collect();
+        }
+        Change::Reset => {}
+    }
+}
+/** The range removed and the number of items inserted, if the change is a splice or can be seen as one.
+ */
+fn as_splice<T: Clone>(change: &Change<T>) -> Option<(Range<usize>, usize)> {
+    match change {
+        Change::Splice {
+            remove_range,
+            replace_with,
+        } => Some((remove_range.clone(), replace_with.len())),
+        Change::Remove { index } => Some((*index..index + 1, 0)),
+        Change::Insert { index, .. } => Some((*index..*index, 1)),
+        _ => None,
+    }
+}
+/** Add `change` (already applied to `head`) to `batch`,
+ * merging it into the last change if they are both splices that overlap or touch.
+ */
+fn push_compacted<T: Clone>(batch: &mut Vec<Change<T>>, change: Change<T>, head: &Vector<T>) {
+    let merged = match (batch.last().and_then(as_splice), as_splice(&change)) {
+        (Some((last_range, last_inserted)), Some((range, inserted)))
+            if range.start <= last_range.start + last_inserted && range.end >= last_range.start =>
+        {
+            let last_end = last_range.start + last_inserted;
+            let start = last_range.start.min(range.start);
+            let removed_end = last_range.end + range.end.saturating_sub(last_end);
+            let replaced_len = last_end.max(range.end) - start - range.len() + inserted;
+            Change::Splice {
+                remove_range: start..removed_end,
+                replace_with: head.skip(start).take(replaced_len),
+            }
+        }
+        _ => {
+            batch.push(change);
+            return;
+        }
+    };
+    batch.pop();
+    match &merged {
+        Change::Splice {
+            remove_range,
+            replace_with,
+        } if remove_range.is_empty() && replace_with.is_empty() => {}
+        _ => batch.push(merged),
     }
 }
 impl<T: Clone> ListModel<T> {
@@ -75,26 +158,80 @@ impl<T: Clone> ListModel<T> {
             head: iter.collect(),
             log: VecDeque::new(),
             log_start_version: 0,
+            batch: None,
+            max_log_len: DEFAULT_MAX_LOG_LEN,
+            reset: Change::Reset,
             total_listeners: Cell::new(0),
             pending_listeners: Cell::new(0),
         }
     }
     fn change(&mut self, change: Change<T>) {
-        let total_listeners = self.total_listeners.get();
-        if total_listeners == 0 {
+        if self.total_listeners.get() == 0 {
             apply_change(&mut self.head, change);
             self.log_start_version += self.log.len() as u64;
             self.log.clear();
         } else {
             apply_change(&mut self.head, change.clone());
-            if self.pending_listeners.get() == 0 {
-                self.log_start_version += self.log.len() as u64;
-                self.log.clear();
+            match self.batch.as_mut() {
+                Some(batch) => push_compacted(batch, change, &self.head),
+                None => self.push_log(SmallVec::from_buf([change])),
             }
-            self.log.push_back(change);
-            self.pending_listeners.set(total_listeners);
         }
     }
+    fn push_log(&mut self, entry: LogEntry<T>) {
+        if self.pending_listeners.get() == 0 {
+            self.log_start_version += self.log.len() as u64;
+            self.log.clear();
+        }
+        self.log.push_back(entry);
+        self.pending_listeners.set(self.total_listeners.get());
+        self.trim_log();
+    }
+    fn trim_log(&mut self) {
+        while self.log.len() > self.max_log_len {
+            self.log.pop_front();
+            self.log_start_version += 1;
+        }
+    }
+    /** Set how many versions of changes to keep for listeners that haven't caught up.
+     * Listeners further behind than that get [Change::Reset].
+     *
+     * Panics if `max_log_len` is 0; the latest change is always needed.
+     */
+    pub fn set_max_log_len(&mut self, max_log_len: usize) {
+        assert!(max_log_len > 0, "max_log_len must be at least 1");
+        self.max_log_len = max_log_len;
+        self.trim_log();
+    }
+    /** Make many changes as one.
+     *
+     * Changes made to the model inside `f` are recorded together
+     * (with adjacent inserts and removes combined into splices)
+     * and become a single new version, so listeners handle them all in one go.
+     *
+     * ```rust
+     * # use async_ui_core::list::ListModel;
+     * let mut model = ListModel::new();
+     * model.transaction(|tx| {
+     *     for i in 0..1000 {
+     *         tx.push(i);
+     *     }
+     * });
+     * assert_eq!(model.len(), 1000);
+     * ```
+     */
+    pub fn transaction<R, F: FnOnce(&mut Self) -> R>(&mut self, f: F) -> R {
+        if self.batch.is_some() {
+            return f(self);
+        }
+        self.batch = Some(Vec::new());
+        let result = f(self);
+        let batch = self.batch.take().unwrap_or_default();
+        if !batch.is_empty() {
+            self.push_log(SmallVec::from_vec(batch));
+        }
+        result
+    }
     pub fn insert(&mut self, index: usize, value: T) {
         self.change(Change::Insert { index, value })
     }
@@ -127,6 +264,63 @@ impl<T: Clone> ListModel<T> {
             index: self.head.len() - 1,
         });
     }
+    /** Move the item at `from` so that it ends up at index `to`.
+     */
+    pub fn move_item(&mut self, from: usize, to: usize) {
+        if from != to {
+            self.change(Change::Move { from, to });
+        }
+    }
+    pub fn swap(&mut self, a: usize, b: usize) {
+        let (a, b) = (a.min(b), a.max(b));
+        if a != b {
+            self.change(Change::Move { from: b, to: a });
+            self.change(Change::Move { from: a + 1, to: b });
+        }
+    }
+    /** Remove the items for which `keep` returns false.
+     * Each run of consecutive removed items is one splice.
+     */
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut keep: F) {
+        let keep: Vec<bool> = self.head.iter().map(&mut keep).collect();
+        let mut end = keep.len();
+        while end > 0 {
+            if keep[end - 1] {
+                end -= 1;
+                continue;
+            }
+            let start = keep[..end]
+                .iter()
+                .rposition(|k| *k)
+                .map_or(0, |last_kept| last_kept + 1);
+            self.change(Change::Splice {
+                remove_range: start..end,
+                replace_with: Vector::new(),
+            });
+            end = start;
+        }
+    }
+    /** Stable sort. Rendered items are moved, not re-created.
+     */
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, mut compare: F) {
+        let mut sources: Vec<usize> = (0..self.head.len()).collect();
+        sources.sort_by(|a, b| compare(&self.head[*a], &self.head[*b]));
+        if sources
+            .iter()
+            .enumerate()
+            .any(|(index, source)| index != *source)
+        {
+            self.change(Change::Reorder { sources });
+        }
+    }
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.head.len() {
+            self.change(Change::Splice {
+                remove_range: len..self.head.len(),
+                replace_with: Vector::new(),
+            });
+        }
+    }
     pub fn len(&self) -> usize {
         self.head.len()
     }
@@ -141,12 +335,16 @@ impl<'l, T: Clone> ListModelPrivateAPIs<'l, T> {
     pub fn get_version(&self) -> u64 {
         self.0.log_start_version + self.0.log.len() as u64
     }
-    pub fn changes_since_version(
-        &self,
-        version: u64,
-    ) -> std::collections::vec_deque::Iter<'_, Change<T>> {
-        let min = (version - self.0.log_start_version) as usize;
-        self.0.log.range(min..)
+    /** The changes made after `version`.
+     * Just [Change::Reset] if they are no longer all in the log (or if `version` is invalid).
+     */
+    pub fn changes_since_version(&self, version: u64) -> impl Iterator<Item = &'_ Change<T>> {
+        let log = &self.0.log;
+        let (reset, min) = match version.checked_sub(self.0.log_start_version) {
+            Some(offset) if offset <= log.len() as u64 => (None, offset as usize),
+            _ => (Some(&self.0.reset), log.len()),
+        };
+        reset.into_iter().chain(log.range(min..).flatten())
     }
     pub fn total_listeners(&self) -> &'_ Cell<usize> {
         &self.0.total_listeners
@@ -155,3 +353,112 @@ impl<'l, T: Clone> ListModelPrivateAPIs<'l, T> {
         &self.0.pending_listeners
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use im_rc::Vector;
+
+    use super::{apply_change, Change, ListModel, ListModelPrivateAPIs};
+
+    fn items(model: &ListModel<i32>) -> Vec<i32> {
+        model.underlying_vector().iter().copied().collect()
+    }
+
+    #[test]
+    fn reordering_operations() {
+        let mut model = ListModel::from_iter([3, 1, 4, 1, 5, 9, 2, 6].into_iter());
+        model.move_item(0, 2);
+        assert_eq!(items(&model), [1, 4, 3, 1, 5, 9, 2, 6]);
+        model.swap(5, 1);
+        assert_eq!(items(&model), [1, 9, 3, 1, 5, 4, 2, 6]);
+        model.sort_by(|a, b| a.cmp(b));
+        assert_eq!(items(&model), [1, 1, 2, 3, 4, 5, 6, 9]);
+        model.retain(|x| x % 3 != 0 && *x != 4);
+        assert_eq!(items(&model), [1, 1, 2, 5]);
+        model.truncate(2);
+        assert_eq!(items(&model), [1, 1]);
+    }
+
+    #[test]
+    fn retain_splices_runs() {
+        let mut model = ListModel::from_iter([1, 2, 3, 4, 5, 6].into_iter());
+        let model_priv = ListModelPrivateAPIs(&model);
+        model_priv.total_listeners().set(1);
+        let version = model_priv.get_version();
+        model.retain(|x| *x == 3);
+        let ranges: Vec<_> = ListModelPrivateAPIs(&model)
+            .changes_since_version(version)
+            .map(|change| match change {
+                Change::Splice { remove_range, .. } => remove_range.clone(),
+                _ => panic!("expected a splice"),
+            })
+            .collect();
+        assert_eq!(ranges, [3..6, 0..2]);
+        assert_eq!(items(&model), [3]);
+    }
+
+    #[test]
+    fn transaction_compacts_into_splices() {
+        let mut model = ListModel::from_iter([0, 1, 2, 3].into_iter());
+        ListModelPrivateAPIs(&model).total_listeners().set(1);
+        let version = ListModelPrivateAPIs(&model).get_version();
+        model.transaction(|tx| {
+            tx.push(4);
+            tx.push(5);
+            tx.insert(4, 10);
+            tx.remove(1);
+            tx.remove(1);
+            tx.insert(2, 30);
+            tx.remove(2);
+        });
+        assert_eq!(items(&model), [0, 3, 10, 4, 5]);
+        let model_priv = ListModelPrivateAPIs(&model);
+        assert_eq!(model_priv.get_version(), version + 1);
+        let changes: Vec<_> = model_priv
+            .changes_since_version(version)
+            .map(|change| match change {
+                Change::Splice {
+                    remove_range,
+                    replace_with,
+                } => (
+                    remove_range.clone(),
+                    replace_with.iter().copied().collect::<Vec<_>>(),
+                ),
+                _ => panic!("expected a splice"),
+            })
+            .collect();
+        assert_eq!(changes, [(4..4, vec![10, 4, 5]), (1..3, vec![])]);
+        let mut replayed: Vector<i32> = [0, 1, 2, 3].into_iter().collect();
+        for change in model_priv.changes_since_version(version) {
+            apply_change(&mut replayed, change.clone());
+        }
+        assert_eq!(&replayed, model.underlying_vector());
+    }
+
+    #[test]
+    fn lagging_listener_gets_reset() {
+        let mut model = ListModel::from_iter([0].into_iter());
+        ListModelPrivateAPIs(&model).total_listeners().set(1);
+        model.set_max_log_len(2);
+        let old_version = ListModelPrivateAPIs(&model).get_version();
+        model.push(1);
+        let recent_version = ListModelPrivateAPIs(&model).get_version();
+        model.push(2);
+        model.push(3);
+        let model_priv = ListModelPrivateAPIs(&model);
+        let is_reset = |version| {
+            let changes: Vec<_> = model_priv.changes_since_version(version).collect();
+            matches!(changes[..], [Change::Reset])
+        };
+        assert!(is_reset(old_version));
+        assert!(is_reset(model_priv.get_version() + 1));
+        assert!(!is_reset(recent_version));
+        assert_eq!(model_priv.changes_since_version(recent_version).count(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "max_log_len must be at least 1")]
+    fn zero_max_log_len_is_rejected() {
+        ListModel::<i32>::new().set_max_log_len(0);
+    }
+}
@@ -9,5 +9,5 @@ use crate::{
 
 pub fn mount<B: BackendTrait, F: Future + 'static>(fut: F) {
     spawn_local(GiveUnforgettableScope::new_static(fut)).detach();
-    B::drive_executor(get_driving_future());
+    B::drive_executor(get_driving_future::<B>());
 }
@@ -0,0 +1,136 @@
+/*! Getting hold of the nodes that components create.
+ *
+ * See [NodeRef].
+ */
+use std::{
+    cell::RefCell,
+    future::poll_fn,
+    rc::Rc,
+    task::{Poll, Waker},
+};
+
+use crate::{backend::BackendTrait, lifecycle::Lifecycle};
+
+struct Inner<B: BackendTrait> {
+    node: Option<B::Node>,
+    wakers: Vec<Waker>,
+}
+
+/** A slot that a component fills with its node when it mounts, and empties when it unmounts.
+ *
+ * Pass it (usually through the `node_ref` prop of a built-in component) to get the underlying node,
+ * e.g. to focus it or measure it.
+ * Clones refer to the same slot.
+ */
+pub struct NodeRef<B: BackendTrait> {
+    inner: Rc<RefCell<Inner<B>>>,
+}
+
+impl<B: BackendTrait> Clone for NodeRef<B> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<B: BackendTrait> Default for NodeRef<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: BackendTrait> NodeRef<B> {
+    pub fn new() -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(Inner {
+                node: None,
+                wakers: Vec::new(),
+            })),
+        }
+    }
+    /** Fill the slot, waking everyone waiting in [get][Self::get].
+     */
+    pub fn set(&self, node: B::Node) {
+        let wakers = {
+            let mut bm = self.inner.borrow_mut();
+            bm.node = Some(node);
+            std::mem::take(&mut bm.wakers)
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+    /** Empty the slot.
+     */
+    pub fn clear(&self) {
+        self.inner.borrow_mut().node = None;
+    }
+    /** Have the element this lifecycle belongs to fill the slot on mount and empty it on unmount.
+     */
+    pub fn attach(&self, lifecycle: &mut Lifecycle<B::Node>) {
+        let on_mount = self.clone();
+        lifecycle.add_on_mount(move |node| on_mount.set(node.clone()));
+        let on_unmount = self.clone();
+        lifecycle.add_on_unmount(move |_| on_unmount.clear());
+    }
+    /** The node, if the component is mounted.
+     */
+    pub fn current(&self) -> Option<B::Node> {
+        self.inner.borrow().node.clone()
+    }
+    /** Wait until the slot is filled, then return the node.
+     */
+    pub async fn get(&self) -> B::Node {
+        poll_fn(|cx| {
+            let mut bm = self.inner.borrow_mut();
+            match &bm.node {
+                Some(node) => Poll::Ready(node.clone()),
+                None => {
+                    if !bm.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                        bm.wakers.push(cx.waker().to_owned());
+                    }
+                    Poll::Pending
+                }
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, future::pending, rc::Rc};
+
+    use crate::{
+        test_util::Gate,
+        testing::{fragment, mount, run_until_stalled, ElementFuture, TestBackend, TestNode},
+    };
+
+    use super::NodeRef;
+
+    #[test]
+    fn node_ref_is_filled_while_mounted() {
+        let gate = Gate::default();
+        let gate_1 = gate.clone();
+        let node_ref = NodeRef::<TestBackend>::new();
+        let (node_ref_1, node_ref_2) = (node_ref.clone(), node_ref.clone());
+        let got: Rc<RefCell<Option<TestNode>>> = Default::default();
+        let got_1 = got.clone();
+        let _root = mount(fragment((
+            async move {
+                *got_1.borrow_mut() = Some(node_ref_1.get().await);
+            },
+            async move {
+                gate_1.wait().await;
+                ElementFuture::new(pending::<()>(), TestNode::new_element("input"))
+                    .node_ref(Some(&node_ref_2))
+                    .await;
+            },
+        )));
+        assert!(node_ref.current().is_none());
+        gate.open();
+        run_until_stalled();
+        let input = node_ref.current().unwrap();
+        assert_eq!(input.tag().as_deref(), Some("input"));
+        assert!(got.borrow().as_ref().unwrap().ptr_eq(&input));
+    }
+}
@@ -34,4 +34,8 @@ impl PositionIndex {
     pub fn wrap(&mut self, index: PositionSegment) {
         self.0.push(index);
     }
+    /** The segments, innermost first. */
+    pub fn segments(&self) -> &[PositionSegment] {
+        &self.0
+    }
 }
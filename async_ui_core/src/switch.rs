@@ -0,0 +1,209 @@
+/*! Rendering different children depending on state.
+ *
+ * See [switch] and [show_when].
+ */
+use std::{
+    future::{poll_fn, Future, IntoFuture},
+    pin::{pin, Pin},
+    rc::Rc,
+    task::Poll,
+};
+
+use observables::{ObservableAs, ObservableAsExt};
+
+use crate::{
+    backend::BackendTrait,
+    vnode::node_portal::{PortalEntry, PortalExit},
+};
+
+struct Branch<'c, B: BackendTrait, K> {
+    key: K,
+    entry: Rc<PortalEntry<B>>,
+    /// `None` once the children have completed.
+    future: Option<Pin<Box<dyn Future<Output = ()> + 'c>>>,
+}
+
+/** Render `render(value)`, and render it anew whenever the value of `discriminant` changes.
+ *
+ * The old branch is dropped (unmounting its nodes) before the new one is mounted.
+ * Changes that leave the value equal to before are ignored. Never completes.
+ */
+pub async fn switch<'c, B, K, C>(discriminant: &'c dyn ObservableAs<K>, render: &'c dyn Fn(&K) -> C)
+where
+    B: BackendTrait,
+    K: PartialEq + Clone + 'c,
+    C: IntoFuture + 'c,
+{
+    switch_inner::<B, K, C>(discriminant, render, false).await
+}
+
+/** Like [switch], but keep branches alive when they are switched away from.
+ *
+ * Their nodes are detached (held like those of a portal with no exit),
+ * and put back without rendering again when the value comes back.
+ * Their futures keep running in the meantime.
+ */
+pub async fn switch_keep_alive<'c, B, K, C>(
+    discriminant: &'c dyn ObservableAs<K>,
+    render: &'c dyn Fn(&K) -> C,
+) where
+    B: BackendTrait,
+    K: PartialEq + Clone + 'c,
+    C: IntoFuture + 'c,
+{
+    switch_inner::<B, K, C>(discriminant, render, true).await
+}
+
+async fn switch_inner<'c, B, K, C>(
+    discriminant: &'c dyn ObservableAs<K>,
+    render: &'c dyn Fn(&K) -> C,
+    keep_alive: bool,
+) where
+    B: BackendTrait,
+    K: PartialEq + Clone + 'c,
+    C: IntoFuture + 'c,
+{
+    let mut branches: Vec<Branch<'c, B, K>> = Vec::new();
+    // The branch being shown, and the exit its nodes go to.
+    let mut active: Option<(usize, PortalExit<B>)> = None;
+    let mut change = pin!(discriminant.until_change());
+    poll_fn(|cx| {
+        loop {
+            let key = discriminant.visit(K::clone);
+            if !matches!(&active, Some((index, _)) if branches[*index].key == key) {
+                if !keep_alive {
+                    // Unmount the old branch while its nodes are still in place.
+                    branches.clear();
+                }
+                // Dropping the exit detaches whatever nodes are left.
+                active = None;
+                let index = match branches.iter().position(|branch| branch.key == key) {
+                    Some(index) => index,
+                    None => {
+                        let entry = Rc::new(PortalEntry::new());
+                        let entry_1 = entry.clone();
+                        let children = render(&key).into_future();
+                        branches.push(Branch {
+                            key,
+                            entry,
+                            future: Some(Box::pin(async move {
+                                entry_1.mount(children).await;
+                            })),
+                        });
+                        branches.len() - 1
+                    }
+                };
+                active = Some((index, branches[index].entry.exit()));
+            }
+            if change.as_mut().poll(cx).is_ready() {
+                change.set(discriminant.until_change());
+                continue;
+            }
+            break;
+        }
+        if let Some((_, exit)) = active.as_mut() {
+            let _ = Pin::new(exit).poll(cx);
+        }
+        for branch in branches.iter_mut() {
+            if let Some(future) = branch.future.as_mut() {
+                if future.as_mut().poll(cx).is_ready() {
+                    branch.future = None;
+                }
+            }
+        }
+        Poll::<()>::Pending
+    })
+    .await
+}
+
+/** Render `children()` while `condition` is true. Never completes.
+ *
+ * The children are dropped when the condition turns false, and rendered anew when it turns true again.
+ */
+pub async fn show_when<'c, B, C>(condition: &'c dyn ObservableAs<bool>, children: &'c dyn Fn() -> C)
+where
+    B: BackendTrait,
+    C: IntoFuture + 'c,
+{
+    let render = |shown: &bool| {
+        let shown = *shown;
+        async move {
+            if shown {
+                children().await;
+            }
+        }
+    };
+    switch::<B, bool, _>(condition, &render).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use observables::cell::ReactiveCell;
+
+    use crate::{
+        test_util::texts,
+        testing::{fragment, mount, run_until_stalled, text, TestBackend},
+    };
+
+    use super::{show_when, switch, switch_keep_alive};
+
+    #[test]
+    fn switch_swaps_branches() {
+        let value = Rc::new(ReactiveCell::new(1u8));
+        let value_1 = value.clone();
+        let root = mount(async move {
+            let render = |v: &u8| text(if *v == 1 { "one" } else { "other" });
+            switch::<TestBackend, _, _>(&value_1.as_observable(), &render).await
+        });
+        assert_eq!(texts(&root), ["one"]);
+        value.set(2);
+        run_until_stalled();
+        assert_eq!(texts(&root), ["other"]);
+    }
+
+    #[test]
+    fn switch_keep_alive_reuses_branches() {
+        let value = Rc::new(ReactiveCell::new(false));
+        let rendered = Rc::new(Cell::new(0));
+        let (value_1, rendered_1) = (value.clone(), rendered.clone());
+        let root = mount(async move {
+            let render = |v: &bool| {
+                rendered_1.set(rendered_1.get() + 1);
+                text(if *v { "on" } else { "off" })
+            };
+            switch_keep_alive::<TestBackend, _, _>(&value_1.as_observable(), &render).await
+        });
+        let off = root.children()[0].clone();
+        value.set(true);
+        run_until_stalled();
+        assert_eq!(texts(&root), ["on"]);
+        value.set(false);
+        run_until_stalled();
+        assert!(root.children()[0].ptr_eq(&off));
+        assert_eq!(rendered.get(), 2);
+    }
+
+    #[test]
+    fn show_when_toggles_children() {
+        let shown = Rc::new(ReactiveCell::new(false));
+        let shown_1 = shown.clone();
+        let root = mount(async move {
+            let children = || text("hi");
+            fragment((
+                text("a"),
+                show_when::<TestBackend, _>(&shown_1.as_observable(), &children),
+                text("b"),
+            ))
+            .await
+        });
+        assert_eq!(texts(&root), ["a", "b"]);
+        shown.set(true);
+        run_until_stalled();
+        assert_eq!(texts(&root), ["a", "hi", "b"]);
+        shown.set(false);
+        run_until_stalled();
+        assert_eq!(texts(&root), ["a", "b"]);
+    }
+}
@@ -0,0 +1,38 @@
+/*! Helpers shared by the tests of this crate.
+ */
+use std::{
+    cell::RefCell,
+    future::poll_fn,
+    rc::Rc,
+    task::{Poll, Waker},
+};
+
+use crate::testing::TestNode;
+
+/** A future that waits until [open][Gate::open] is called. */
+#[derive(Clone, Default)]
+pub(crate) struct Gate(Rc<RefCell<(bool, Vec<Waker>)>>);
+impl Gate {
+    pub(crate) fn open(&self) {
+        let mut bm = self.0.borrow_mut();
+        bm.0 = true;
+        bm.1.drain(..).for_each(Waker::wake);
+    }
+    pub(crate) async fn wait(&self) {
+        poll_fn(|cx| {
+            let mut bm = self.0.borrow_mut();
+            if bm.0 {
+                Poll::Ready(())
+            } else {
+                bm.1.push(cx.waker().to_owned());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+/** The text content of each child of `node`. */
+pub(crate) fn texts(node: &TestNode) -> Vec<String> {
+    node.children().iter().map(TestNode::text_content).collect()
+}
@@ -0,0 +1,129 @@
+use std::{
+    future::{pending, Future, IntoFuture},
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use pin_project_lite::pin_project;
+
+use crate::{
+    backend::BackendTrait,
+    lifecycle::Lifecycle,
+    node_ref::NodeRef,
+    vnode::{
+        enter_vnode,
+        node_concrete::{ConcreteNodeVNode, RefNode},
+        VNode, VNodeTrait,
+    },
+};
+
+use super::{node::TestNode, TestBackend};
+
+pin_project! {
+    /** Render a [TestNode], and render everything in the future inside it.
+     *
+     * This is the test backend's equivalent of the `ElementFuture`s in the web and GTK backends.
+     */
+    pub struct ElementFuture<F: Future> {
+        #[pin]
+        future: F,
+        inner: ElementFutureInner
+    }
+}
+struct ElementFutureInner {
+    node: TestNode,
+    vnodes: Option<MyAndParentVNodes>,
+    lifecycle: Lifecycle<TestNode>,
+}
+struct MyAndParentVNodes {
+    my: Rc<VNode<TestBackend>>,
+    parent: Rc<VNode<TestBackend>>,
+}
+
+impl Drop for ElementFutureInner {
+    fn drop(&mut self) {
+        if let Some(MyAndParentVNodes { parent, .. }) = &self.vnodes {
+            self.lifecycle.unmount(&self.node);
+            parent.del_child_node(Default::default());
+        }
+    }
+}
+impl<F: Future> ElementFuture<F> {
+    pub fn new(future: F, node: TestNode) -> Self {
+        Self {
+            future,
+            inner: ElementFutureInner {
+                node,
+                vnodes: None,
+                lifecycle: Lifecycle::default(),
+            },
+        }
+    }
+    /** Call `callback` with the node right after it is added to its parent.
+     */
+    pub fn on_mount<C: FnOnce(&TestNode) + 'static>(mut self, callback: C) -> Self {
+        self.inner.lifecycle.add_on_mount(callback);
+        self
+    }
+    /** Call `callback` with the node right before it is removed from its parent.
+     */
+    pub fn on_unmount<C: FnOnce(&TestNode) + 'static>(mut self, callback: C) -> Self {
+        self.inner.lifecycle.add_on_unmount(callback);
+        self
+    }
+    /** Fill `node_ref` with the node while it is mounted. Does nothing if `None`.
+     */
+    pub fn node_ref(mut self, node_ref: Option<&NodeRef<TestBackend>>) -> Self {
+        if let Some(node_ref) = node_ref {
+            node_ref.attach(&mut self.inner.lifecycle);
+        }
+        self
+    }
+}
+impl<F: Future> Future for ElementFuture<F> {
+    type Output = F::Output;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let vnk = TestBackend::get_vnode_key();
+        let vnodes = this.inner.vnodes.get_or_insert_with(|| {
+            let parent_vnode = vnk.with(Clone::clone);
+            parent_vnode.add_child_node(this.inner.node.to_owned(), Default::default());
+            this.inner.lifecycle.mount(&this.inner.node);
+            let parent_context = parent_vnode.get_context_map().clone();
+            let my = Rc::new(
+                ConcreteNodeVNode::new(
+                    RefNode::Parent {
+                        parent: this.inner.node.clone(),
+                    },
+                    parent_context,
+                )
+                .into(),
+            );
+            MyAndParentVNodes {
+                my,
+                parent: parent_vnode,
+            }
+        });
+        enter_vnode(&vnodes.my, || this.future.poll(cx))
+    }
+}
+
+/** An element node with the given tag, with `children` rendered inside it.
+ *
+ * ```rust
+ * # use async_ui_core::testing::{element, fragment, text};
+ * # async fn f() {
+ * element("div", fragment((text("hello"),))).await;
+ * # }
+ * ```
+ */
+pub async fn element<I: IntoFuture>(tag: &str, children: I) -> I::Output {
+    ElementFuture::new(children.into_future(), TestNode::new_element(tag)).await
+}
+
+/** A text node. Never completes.
+ */
+pub async fn text(text: &str) {
+    ElementFuture::new(pending::<()>(), TestNode::new_text(text)).await
+}
@@ -0,0 +1,135 @@
+/*! A headless, in-memory backend for testing.
+ *
+ * Rendering goes into a tree of [TestNode]s instead of the DOM or GTK widgets,
+ * and the executor is only driven when the test asks it to,
+ * so components can be checked with plain `cargo test`.
+ * Only built with the `testing` feature; add it to your dev-dependencies.
+ *
+ * ```rust
+ * # use async_ui_core::testing::{element, fragment, mount, text};
+ * let root = mount(element("div", fragment((text("hello"), text("world")))));
+ * assert_eq!(root.text_content(), "helloworld");
+ * ```
+ */
+mod element;
+mod node;
+
+use std::{
+    future::{Future, IntoFuture},
+    rc::Rc,
+};
+
+use scoped_tls::{scoped_thread_local, ScopedKey};
+
+use crate::{
+    backend::BackendTrait,
+    driver::ManualDriver,
+    mount as core_mount,
+    vnode::{
+        node_concrete::{ConcreteNodeVNode, RefNode},
+        VNode, WithVNode,
+    },
+};
+
+pub use element::{element, text, ElementFuture};
+pub use node::{NodeKind, TestNode};
+pub use observables::testing::TestClock;
+
+pub type Fragment<'c> = crate::fragment::Fragment<'c, TestBackend>;
+
+pub fn fragment<'c, T: Into<Fragment<'c>>>(tuple: T) -> Fragment<'c> {
+    tuple.into()
+}
+
+pub struct TestBackend;
+impl BackendTrait for TestBackend {
+    type Node = TestNode;
+
+    fn add_child_node(
+        parent: &mut Self::Node,
+        child: &mut Self::Node,
+        insert_before_sibling: Option<&Self::Node>,
+    ) {
+        parent.insert_before(child, insert_before_sibling);
+    }
+
+    fn del_child_node(parent: &mut Self::Node, child: &mut Self::Node) {
+        parent.remove_child(child);
+    }
+
+    /// Connected if the topmost ancestor is a `root` element, like the ones created by [mount].
+    fn is_connected(node: &Self::Node) -> bool {
+        let mut top = node.clone();
+        while let Some(parent) = top.parent() {
+            top = parent;
+        }
+        top.tag().as_deref() == Some("root")
+    }
+
+    fn drive_executor<F: Future<Output = ()> + 'static>(fut: F) {
+        DRIVER.with(|driver| driver.set_future(fut));
+    }
+
+    fn initialize() {}
+
+    fn get_vnode_key() -> &'static ScopedKey<Rc<VNode<Self>>> {
+        &VNODE
+    }
+}
+
+scoped_thread_local!(
+    static VNODE: Rc<VNode<TestBackend>>
+);
+
+thread_local! {
+    static DRIVER: ManualDriver = ManualDriver::new();
+}
+
+/** Poll the executor once.
+ *
+ * Returns whether the executor asked to be polled again.
+ */
+pub fn step() -> bool {
+    DRIVER.with(ManualDriver::step)
+}
+
+/** Poll the executor until it has nothing left to do.
+ *
+ * Panics if the executor is still busy after a very large number of polls,
+ * which usually means some component never yields.
+ */
+pub fn run_until_stalled() {
+    const MAX_POLLS: usize = 10_000;
+    if !DRIVER.with(ManualDriver::is_scheduled) {
+        return;
+    }
+    for _ in 0..MAX_POLLS {
+        if !step() {
+            return;
+        }
+    }
+    panic!("executor did not stall after {MAX_POLLS} polls");
+}
+
+/** Mount the given future inside a new `root` element and run it until it stalls.
+ *
+ * Returns the root node, which can be inspected with the query methods on [TestNode].
+ */
+pub fn mount<F: IntoFuture + 'static>(root: F) -> TestNode {
+    let node = TestNode::new_element("root");
+    let fut = WithVNode::new(
+        root.into_future(),
+        Rc::new(
+            ConcreteNodeVNode::new(
+                RefNode::<TestBackend>::Parent {
+                    parent: node.clone(),
+                },
+                Default::default(),
+            )
+            .into(),
+        ),
+    );
+    core_mount::<TestBackend, _>(fut);
+    run_until_stalled();
+    node
+}
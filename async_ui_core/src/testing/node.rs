@@ -0,0 +1,154 @@
+use std::{
+    cell::RefCell,
+    fmt::Debug,
+    rc::{Rc, Weak},
+};
+
+/** A node in the in-memory tree used by the test backend.
+ *
+ * Cloning a `TestNode` gives another handle to the same node,
+ * just like cloning a `web_sys::Node`.
+ */
+#[derive(Clone)]
+pub struct TestNode(Rc<RefCell<NodeData>>);
+
+struct NodeData {
+    kind: NodeKind,
+    children: Vec<TestNode>,
+    parent: Weak<RefCell<NodeData>>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum NodeKind {
+    Element(String),
+    Text(String),
+}
+
+impl TestNode {
+    fn new(kind: NodeKind) -> Self {
+        Self(Rc::new(RefCell::new(NodeData {
+            kind,
+            children: Vec::new(),
+            parent: Weak::new(),
+        })))
+    }
+    pub fn new_element(tag: &str) -> Self {
+        Self::new(NodeKind::Element(tag.to_owned()))
+    }
+    pub fn new_text(text: &str) -> Self {
+        Self::new(NodeKind::Text(text.to_owned()))
+    }
+    /// Whether the two handles point to the same node.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+    pub fn kind(&self) -> NodeKind {
+        self.0.borrow().kind.clone()
+    }
+    pub fn tag(&self) -> Option<String> {
+        match &self.0.borrow().kind {
+            NodeKind::Element(tag) => Some(tag.clone()),
+            NodeKind::Text(_) => None,
+        }
+    }
+    pub fn text(&self) -> Option<String> {
+        match &self.0.borrow().kind {
+            NodeKind::Text(text) => Some(text.clone()),
+            NodeKind::Element(_) => None,
+        }
+    }
+    pub fn set_text(&self, text: &str) {
+        match &mut self.0.borrow_mut().kind {
+            NodeKind::Text(t) => *t = text.to_owned(),
+            NodeKind::Element(_) => panic!("set_text called on an element node"),
+        }
+    }
+    pub fn parent(&self) -> Option<TestNode> {
+        self.0.borrow().parent.upgrade().map(TestNode)
+    }
+    /// The direct children of this node, in order.
+    pub fn children(&self) -> Vec<TestNode> {
+        self.0.borrow().children.clone()
+    }
+    /// The concatenated text of all text nodes under this node, in document order.
+    pub fn text_content(&self) -> String {
+        let mut out = String::new();
+        self.for_each_descendant(&mut |node| {
+            if let NodeKind::Text(t) = &node.0.borrow().kind {
+                out.push_str(t);
+            }
+        });
+        out
+    }
+    /// The first text node (in document order) under this node whose text equals `text`.
+    pub fn find_by_text(&self, text: &str) -> Option<TestNode> {
+        self.find(&|node| node.text().as_deref() == Some(text))
+    }
+    /// The first element (in document order) under this node with the given tag.
+    pub fn find_by_tag(&self, tag: &str) -> Option<TestNode> {
+        self.find(&|node| node.tag().as_deref() == Some(tag))
+    }
+    /// All nodes under this node (not including itself) matching the predicate, in document order.
+    pub fn find_all(&self, predicate: &dyn Fn(&TestNode) -> bool) -> Vec<TestNode> {
+        let mut out = Vec::new();
+        self.children().iter().for_each(|ch| {
+            ch.for_each_descendant(&mut |node| {
+                if predicate(node) {
+                    out.push(node.clone());
+                }
+            })
+        });
+        out
+    }
+    fn find(&self, predicate: &dyn Fn(&TestNode) -> bool) -> Option<TestNode> {
+        self.find_all(predicate).into_iter().next()
+    }
+    fn for_each_descendant(&self, visitor: &mut dyn FnMut(&TestNode)) {
+        visitor(self);
+        for ch in self.children().iter() {
+            ch.for_each_descendant(visitor);
+        }
+    }
+    pub(crate) fn insert_before(&self, child: &TestNode, before: Option<&TestNode>) {
+        if let Some(old_parent) = child.parent() {
+            old_parent.remove_child(child);
+        }
+        let mut bm = self.0.borrow_mut();
+        let index = match before {
+            Some(before) => bm
+                .children
+                .iter()
+                .position(|ch| ch.ptr_eq(before))
+                .expect("insert failed: sibling is not a child of this node"),
+            None => bm.children.len(),
+        };
+        bm.children.insert(index, child.clone());
+        child.0.borrow_mut().parent = Rc::downgrade(&self.0);
+    }
+    pub(crate) fn remove_child(&self, child: &TestNode) {
+        let mut bm = self.0.borrow_mut();
+        let index = bm
+            .children
+            .iter()
+            .position(|ch| ch.ptr_eq(child))
+            .expect("remove failed: node is not a child of this node");
+        bm.children.remove(index);
+        child.0.borrow_mut().parent = Weak::new();
+    }
+}
+
+impl Debug for TestNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bm = self.0.borrow();
+        match &bm.kind {
+            NodeKind::Text(t) => write!(f, "{t:?}"),
+            NodeKind::Element(tag) => {
+                let mut d = f.debug_tuple(tag);
+                bm.children.iter().for_each(|ch| {
+                    d.field(ch);
+                });
+                d.finish()
+            }
+        }
+    }
+}
@@ -1,34 +1,182 @@
+#[cfg(feature = "inspect")]
+use std::fmt::Debug;
 use std::future::Future;
 use std::rc::Rc;
 
-use enum_dispatch::enum_dispatch;
 use pin_project_lite::pin_project;
 pub mod node_concrete;
 pub mod node_context;
 pub mod node_pass;
 pub mod node_portal;
+pub mod node_suspense;
+pub mod node_transition;
 use crate::context::ContextMap;
 
+#[cfg(feature = "inspect")]
+use crate::inspect::{VNodeInfo, VNodeKind};
 use crate::{backend::BackendTrait, position::PositionIndex};
 
 use self::{
     node_concrete::ConcreteNodeVNode, node_context::ContextVNode, node_pass::PassVNode,
-    node_portal::PortalVNode,
+    node_portal::PortalVNode, node_suspense::SuspenseVNode, node_transition::TransitionVNode,
 };
 
-#[enum_dispatch]
 pub trait VNodeTrait<B: BackendTrait> {
     fn add_child_node(&self, node: B::Node, position: PositionIndex);
     fn del_child_node(&self, position: PositionIndex) -> B::Node;
     fn get_context_map<'s>(&'s self) -> &'s ContextMap;
+    /** Take an existing node (e.g. server-rendered) to reuse instead of creating a new one,
+     * for a node that will be added at `position`.
+     *
+     * Shows the unclaimed nodes of the nearest concrete node to `decide`, in order, until one is taken or left.
+     * Only the nodes that can go at `position` are shown: those after every node claimed for an earlier position
+     * and before every node claimed for a later one. So siblings may claim in any order.
+     */
+    fn claim_child_node(
+        &self,
+        position: PositionIndex,
+        decide: &dyn Fn(&B::Node) -> Claim,
+    ) -> Option<B::Node>;
+}
+
+/** What [claim_child_node][VNodeTrait::claim_child_node] should do with an existing node.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Claim {
+    /// Take the node.
+    Take,
+    /// Remove the node from its parent and look at the next one.
+    Discard,
+    /// Keep the node, which may be claimed by another sibling, and look at the next one.
+    Skip,
+    /// Keep the node (and those after it) for later, and take nothing.
+    Leave,
 }
 
-#[enum_dispatch(VNodeTrait<B>)]
 pub enum VNode<B: BackendTrait> {
     ConcreteNode(ConcreteNodeVNode<B>),
     Context(ContextVNode<B>),
     Pass(PassVNode<B>),
     Portal(PortalVNode<B>),
+    Suspense(SuspenseVNode<B>),
+    Transition(TransitionVNode<B>),
+}
+
+/* Written out rather than derived (e.g. with enum_dispatch),
+ * because VNode implements Drop when the inspector is enabled, so nothing may move out of it.
+ */
+macro_rules! dispatch {
+    ($vnode:expr, $vn:ident => $call:expr) => {
+        match $vnode {
+            VNode::ConcreteNode($vn) => $call,
+            VNode::Context($vn) => $call,
+            VNode::Pass($vn) => $call,
+            VNode::Portal($vn) => $call,
+            VNode::Suspense($vn) => $call,
+            VNode::Transition($vn) => $call,
+        }
+    };
+}
+impl<B: BackendTrait> VNodeTrait<B> for VNode<B> {
+    fn add_child_node(&self, node: B::Node, position: PositionIndex) {
+        dispatch!(self, vn => vn.add_child_node(node, position))
+    }
+    fn del_child_node(&self, position: PositionIndex) -> B::Node {
+        dispatch!(self, vn => vn.del_child_node(position))
+    }
+    fn get_context_map(&self) -> &ContextMap {
+        dispatch!(self, vn => vn.get_context_map())
+    }
+    fn claim_child_node(
+        &self,
+        position: PositionIndex,
+        decide: &dyn Fn(&B::Node) -> Claim,
+    ) -> Option<B::Node> {
+        dispatch!(self, vn => vn.claim_child_node(position, decide))
+    }
+}
+macro_rules! impl_from {
+    ($($variant:ident($inner:ident)),*) => {
+        $(
+            impl<B: BackendTrait> From<$inner<B>> for VNode<B> {
+                fn from(vn: $inner<B>) -> Self {
+                    VNode::$variant(vn)
+                }
+            }
+        )*
+    };
+}
+impl_from!(
+    ConcreteNode(ConcreteNodeVNode),
+    Context(ContextVNode),
+    Pass(PassVNode),
+    Portal(PortalVNode),
+    Suspense(SuspenseVNode),
+    Transition(TransitionVNode)
+);
+
+impl<B: BackendTrait> VNode<B> {
+    #[cfg(feature = "inspect")]
+    pub(crate) fn parent(&self) -> Option<&Rc<VNode<B>>> {
+        match self {
+            VNode::Context(vn) => Some(vn.parent()),
+            VNode::Pass(vn) => Some(vn.parent()),
+            VNode::Suspense(vn) => Some(vn.parent()),
+            VNode::Transition(vn) => Some(vn.parent()),
+            VNode::ConcreteNode(_) | VNode::Portal(_) => None,
+        }
+    }
+    #[cfg(feature = "inspect")]
+    pub(crate) fn describe(&self) -> VNodeInfo
+    where
+        B::Node: Debug,
+    {
+        let mut context_types: Vec<_> = self
+            .get_context_map()
+            .type_names
+            .values()
+            .copied()
+            .collect();
+        context_types.sort_unstable();
+        let (kind, index, node, child_nodes) = match self {
+            VNode::ConcreteNode(vn) => {
+                let (node, child_nodes) = vn.describe_nodes();
+                (VNodeKind::Concrete, None, Some(node), child_nodes)
+            }
+            VNode::Context(_) => (VNodeKind::Context, None, None, Vec::new()),
+            VNode::Pass(vn) => (VNodeKind::Pass, Some(vn.index()), None, Vec::new()),
+            VNode::Portal(_) => (VNodeKind::Portal, None, None, Vec::new()),
+            VNode::Suspense(_) => (VNodeKind::Suspense, None, None, Vec::new()),
+            VNode::Transition(_) => (VNodeKind::Transition, None, None, Vec::new()),
+        };
+        VNodeInfo {
+            kind,
+            index,
+            context_types,
+            node,
+            child_nodes,
+            task: None,
+            children: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "inspect")]
+impl<B: BackendTrait> Drop for VNode<B> {
+    fn drop(&mut self) {
+        crate::inspect::forget::<B>(self);
+    }
+}
+
+/** Run `f` with `vnode` as the current vnode.
+ *
+ * Backends should use this rather than setting the vnode key directly,
+ * so that the inspector (with the `inspect` feature) sees the vnode.
+ */
+pub fn enter_vnode<B: BackendTrait, R>(vnode: &Rc<VNode<B>>, f: impl FnOnce() -> R) -> R {
+    #[cfg(feature = "inspect")]
+    crate::inspect::track(vnode);
+    B::get_vnode_key().set(vnode, f)
 }
 
 pin_project! {
@@ -52,6 +200,6 @@ impl<B: BackendTrait, F: Future> Future for WithVNode<B, F> {
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Self::Output> {
         let this = self.project();
-        B::get_vnode_key().set(this.vnode, || this.future.poll(cx))
+        enter_vnode(this.vnode, || this.future.poll(cx))
     }
 }
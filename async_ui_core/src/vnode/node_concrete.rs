@@ -1,7 +1,10 @@
+#[cfg(feature = "inspect")]
+use std::fmt::Debug;
 use std::{
     cell::RefCell,
-    collections::BTreeMap,
+    collections::{BTreeMap, VecDeque},
     future::Future,
+    ops::{Bound, Range},
     pin::Pin,
     rc::Rc,
     task::{Context, Poll},
@@ -11,7 +14,7 @@ use pin_project_lite::pin_project;
 
 use crate::{backend::BackendTrait, context::ContextMap, position::PositionIndex};
 
-use super::{VNode, VNodeTrait};
+use super::{enter_vnode, Claim, VNode, VNodeTrait};
 
 pub struct ConcreteNodeVNode<B: BackendTrait> {
     inside: RefCell<Inside<B>>,
@@ -20,18 +23,127 @@ pub struct ConcreteNodeVNode<B: BackendTrait> {
 struct Inside<B: BackendTrait> {
     node: RefNode<B>,
     children: BTreeMap<PositionIndex, B::Node>,
+    /// The existing child nodes, in order. `None` once claimed or discarded.
+    claimable: Vec<Option<B::Node>>,
+    /// Where in `claimable` each claimed node was, by the position it was claimed for.
+    claimed: BTreeMap<PositionIndex, usize>,
+}
+
+impl<B: BackendTrait> Inside<B> {
+    /** The part of `claimable` that a node at `position` can be claimed from:
+     * after every node claimed for an earlier position, and before every node claimed for a later one.
+     */
+    fn claimable_range(&self, position: &PositionIndex) -> Range<usize> {
+        let start = self
+            .claimed
+            .range(..position.clone())
+            .map(|(_, index)| index + 1)
+            .max()
+            .unwrap_or(0);
+        let end = self
+            .claimed
+            .range((Bound::Excluded(position.clone()), Bound::Unbounded))
+            .map(|(_, index)| *index)
+            .min()
+            .unwrap_or(self.claimable.len());
+        start..end.max(start)
+    }
 }
 
 impl<B: BackendTrait> ConcreteNodeVNode<B> {
     pub fn new(node: RefNode<B>, context: ContextMap) -> Self {
+        Self::new_hydrating(node, context, VecDeque::new())
+    }
+    /** Like [new][Self::new], but with existing child nodes (in order) that descendants can claim.
+     *
+     * Call [finish_hydration][Self::finish_hydration] to remove the ones that were never claimed.
+     */
+    pub fn new_hydrating(
+        node: RefNode<B>,
+        context: ContextMap,
+        claimable: VecDeque<B::Node>,
+    ) -> Self {
         Self {
             inside: RefCell::new(Inside {
                 node,
                 children: BTreeMap::new(),
+                claimable: claimable.into_iter().map(Some).collect(),
+                claimed: BTreeMap::new(),
             }),
             context,
         }
     }
+    /** Look at the first node of this vnode in its parent:
+     * the first added child, or else the sibling node for [RefNode::Sibling].
+     */
+    pub fn with_first_node<R>(&self, visitor: impl FnOnce(Option<&B::Node>) -> R) -> R {
+        let inside = self.inside.borrow();
+        visitor(match (inside.children.values().next(), &inside.node) {
+            (Some(first), _) => Some(first),
+            (None, RefNode::Sibling { sibling, .. }) => Some(sibling),
+            (None, RefNode::Parent { .. }) => None,
+        })
+    }
+    /** Move every node added to this vnode, in order, to before `before`
+     * (or to the end of the parent, if `before` is `None`).
+     * For [RefNode::Sibling], the sibling node is moved too, after the others.
+     *
+     * This is how list items get reordered without being rendered again.
+     */
+    pub fn move_before(&self, before: Option<&B::Node>) {
+        let mut inside = self.inside.borrow_mut();
+        let Inside { node, children, .. } = &mut *inside;
+        let (parent, sibling) = match node {
+            RefNode::Parent { parent } => (parent, None),
+            RefNode::Sibling { parent, sibling } => (parent, Some(sibling)),
+        };
+        for child in children.values_mut().chain(sibling) {
+            B::add_child_node(parent, child, before);
+        }
+    }
+    /** Describe the node rendered into and the nodes added, for the [inspector][crate::inspect].
+     */
+    #[cfg(feature = "inspect")]
+    pub(crate) fn describe_nodes(&self) -> (String, Vec<(Vec<usize>, String)>)
+    where
+        B::Node: Debug,
+    {
+        let inside = self.inside.borrow();
+        let node = match &inside.node {
+            RefNode::Parent { parent } => format!("{parent:?}"),
+            RefNode::Sibling { parent, sibling } => format!("{parent:?} before {sibling:?}"),
+        };
+        let children = inside
+            .children
+            .iter()
+            .map(|(position, child)| (position.segments().to_vec(), format!("{child:?}")))
+            .collect();
+        (node, children)
+    }
+    /** Remove the existing child nodes that were not claimed, and return them.
+     */
+    pub fn finish_hydration(&self) -> Vec<B::Node> {
+        let mut inside = self.inside.borrow_mut();
+        let Inside {
+            node,
+            claimable,
+            claimed,
+            ..
+        } = &mut *inside;
+        let parent = match node {
+            RefNode::Parent { parent } => parent,
+            RefNode::Sibling { parent, .. } => parent,
+        };
+        claimed.clear();
+        claimable
+            .drain(..)
+            .flatten()
+            .map(|mut unclaimed| {
+                B::del_child_node(parent, &mut unclaimed);
+                unclaimed
+            })
+            .collect()
+    }
 }
 pub enum RefNode<B: BackendTrait> {
     Parent { parent: B::Node },
@@ -41,14 +153,22 @@ pub enum RefNode<B: BackendTrait> {
 impl<B: BackendTrait> VNodeTrait<B> for ConcreteNodeVNode<B> {
     fn add_child_node(&self, mut node: <B as BackendTrait>::Node, position: PositionIndex) {
         let mut inside = self.inside.borrow_mut();
+        // Unclaimed nodes that may still be claimed for a later position go after this node.
+        let unclaimed_after = match inside.claimed.get(&position) {
+            Some(index) => index + 1,
+            None => inside.claimable_range(&position).start,
+        };
         let Inside {
             node: this_node,
             children: children_map,
+            claimable,
+            ..
         } = &mut *inside;
         let next_node = children_map
             .range(position.clone()..)
             .next()
-            .map(|(_k, v)| v);
+            .map(|(_k, v)| v)
+            .or_else(|| claimable[unclaimed_after..].iter().flatten().next());
         match this_node {
             RefNode::Parent { parent } => {
                 B::add_child_node(parent, &mut node, next_node);
@@ -73,6 +193,44 @@ impl<B: BackendTrait> VNodeTrait<B> for ConcreteNodeVNode<B> {
     fn get_context_map<'s>(&'s self) -> &'s ContextMap {
         &self.context
     }
+
+    fn claim_child_node(
+        &self,
+        position: PositionIndex,
+        decide: &dyn Fn(&B::Node) -> Claim,
+    ) -> Option<B::Node> {
+        let mut inside = self.inside.borrow_mut();
+        let range = inside.claimable_range(&position);
+        let Inside {
+            node,
+            claimable,
+            claimed,
+            ..
+        } = &mut *inside;
+        let parent = match node {
+            RefNode::Parent { parent } => parent,
+            RefNode::Sibling { parent, .. } => parent,
+        };
+        for index in range {
+            let Some(candidate) = &claimable[index] else {
+                continue;
+            };
+            match decide(candidate) {
+                Claim::Take => {
+                    claimed.insert(position, index);
+                    return claimable[index].take();
+                }
+                Claim::Leave => return None,
+                Claim::Skip => {}
+                Claim::Discard => {
+                    if let Some(mut discarded) = claimable[index].take() {
+                        B::del_child_node(parent, &mut discarded);
+                    }
+                }
+            }
+        }
+        None
+    }
 }
 
 enum WithConcreteNodeState<B: BackendTrait> {
@@ -122,8 +280,172 @@ where
             WithConcreteNodeState::Started { vnode } => vnode,
             _ => unreachable!(),
         };
-        let res = vnk.set(&vnode, || this.future.poll(cx));
+        let res = enter_vnode(&vnode, || this.future.poll(cx));
         *this.state = WithConcreteNodeState::Started { vnode };
         res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::{pending, IntoFuture},
+        rc::Rc,
+    };
+
+    use crate::{
+        backend::BackendTrait,
+        mount,
+        position::PositionIndex,
+        test_util::{texts, Gate},
+        testing::{fragment, run_until_stalled, ElementFuture, TestBackend, TestNode},
+        vnode::{Claim, VNode, VNodeTrait, WithVNode},
+    };
+
+    use super::{ConcreteNodeVNode, RefNode};
+
+    /** Claim an existing element with the given tag (passing over any other), or create one. Never completes.
+     */
+    async fn claiming(tag: &'static str) {
+        let node = TestBackend::get_vnode_key()
+            .with(|vnode| {
+                vnode.claim_child_node(Default::default(), &|node| match node.tag().as_deref()
+                    == Some(tag)
+                {
+                    true => Claim::Take,
+                    false => Claim::Skip,
+                })
+            })
+            .unwrap_or_else(|| TestNode::new_element(tag));
+        ElementFuture::new(pending::<()>(), node).await
+    }
+
+    #[test]
+    fn claimed_nodes_stay_in_place() {
+        let root = TestNode::new_element("root");
+        let existing = ["a", "b", "c"].map(TestNode::new_text);
+        existing.iter().for_each(|n| root.insert_before(n, None));
+        let vnode: VNode<TestBackend> = ConcreteNodeVNode::new_hydrating(
+            RefNode::Parent {
+                parent: root.clone(),
+            },
+            Default::default(),
+            existing.iter().cloned().collect(),
+        )
+        .into();
+        let is_a = |n: &TestNode| match n.text().as_deref() {
+            Some("a") => Claim::Take,
+            _ => Claim::Leave,
+        };
+        let a = vnode.claim_child_node(Default::default(), &is_a).unwrap();
+        assert!(a.ptr_eq(&existing[0]));
+        vnode.add_child_node(a, Default::default());
+        assert!(vnode.claim_child_node(Default::default(), &is_a).is_none());
+        assert_eq!(texts(&root), ["a", "b", "c"]);
+        if let VNode::ConcreteNode(concrete) = &vnode {
+            concrete.finish_hydration();
+        }
+        assert_eq!(texts(&root), ["a"]);
+    }
+
+    #[test]
+    fn mismatched_node_is_discarded() {
+        let root = TestNode::new_element("root");
+        let existing = ["a", "x", "b", "c"].map(TestNode::new_text);
+        existing.iter().for_each(|n| root.insert_before(n, None));
+        let vnode: VNode<TestBackend> = ConcreteNodeVNode::new_hydrating(
+            RefNode::Parent {
+                parent: root.clone(),
+            },
+            Default::default(),
+            existing.iter().cloned().collect(),
+        )
+        .into();
+        let expect = |text: &'static str| {
+            move |n: &TestNode| match n.text().as_deref() == Some(text) {
+                true => Claim::Take,
+                false => Claim::Discard,
+            }
+        };
+        let at = |index| {
+            let mut position = PositionIndex::default();
+            position.wrap(index);
+            position
+        };
+        let a = vnode.claim_child_node(at(0), &expect("a")).unwrap();
+        vnode.add_child_node(a, at(0));
+        let b = vnode.claim_child_node(at(1), &expect("b")).unwrap();
+        assert!(b.ptr_eq(&existing[2]));
+        vnode.add_child_node(b, at(1));
+        assert_eq!(texts(&root), ["a", "b", "c"]);
+        let c = vnode.claim_child_node(at(2), &expect("c")).unwrap();
+        assert!(c.ptr_eq(&existing[3]));
+        vnode.add_child_node(c, at(2));
+        assert!(vnode.claim_child_node(at(3), &expect("d")).is_none());
+        assert_eq!(texts(&root), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn moved_item_keeps_its_nodes() {
+        let root = TestNode::new_element("root");
+        let anchors = ["1", "2"].map(TestNode::new_text);
+        anchors.iter().for_each(|n| root.insert_before(n, None));
+        let item: VNode<TestBackend> = ConcreteNodeVNode::new(
+            RefNode::Sibling {
+                parent: root.clone(),
+                sibling: anchors[1].clone(),
+            },
+            Default::default(),
+        )
+        .into();
+        let content = TestNode::new_text("b");
+        item.add_child_node(content.clone(), Default::default());
+        assert_eq!(texts(&root), ["1", "b", "2"]);
+        if let VNode::ConcreteNode(concrete) = &item {
+            assert!(concrete.with_first_node(|first| first.unwrap().ptr_eq(&content)));
+            concrete.move_before(Some(&anchors[0]));
+        }
+        assert_eq!(texts(&root), ["b", "2", "1"]);
+        assert!(root.children()[0].ptr_eq(&content));
+    }
+
+    #[test]
+    fn siblings_claim_by_position() {
+        let root = TestNode::new_element("root");
+        let existing = ["div", "p", "div"].map(TestNode::new_element);
+        existing.iter().for_each(|n| root.insert_before(n, None));
+        let vnode: Rc<VNode<TestBackend>> = Rc::new(
+            ConcreteNodeVNode::new_hydrating(
+                RefNode::Parent {
+                    parent: root.clone(),
+                },
+                Default::default(),
+                existing.iter().cloned().collect(),
+            )
+            .into(),
+        );
+        let gate = Gate::default();
+        let gate_1 = gate.clone();
+        let first = async move {
+            gate_1.wait().await;
+            claiming("div").await
+        };
+        let fut = fragment((first, claiming("p"), claiming("div")));
+        mount::<TestBackend, _>(WithVNode::new(fut.into_future(), vnode.clone()));
+        run_until_stalled();
+        // The later siblings took their own nodes, leaving the first one's alone.
+        assert_eq!(root.children().len(), 3);
+        assert!(root.children()[0].ptr_eq(&existing[0]));
+        assert!(root.children()[1].ptr_eq(&existing[1]));
+        assert!(root.children()[2].ptr_eq(&existing[2]));
+        gate.open();
+        run_until_stalled();
+        let VNode::ConcreteNode(concrete) = &*vnode else {
+            unreachable!()
+        };
+        assert!(concrete.finish_hydration().is_empty());
+        let children = root.children();
+        assert_eq!(children.len(), 3);
+        assert!(children.iter().zip(&existing).all(|(a, b)| a.ptr_eq(b)));
+    }
+}
@@ -8,9 +8,14 @@ use std::{
 
 use pin_project_lite::pin_project;
 
-use crate::{backend::BackendTrait, context::ContextMap, position::PositionIndex, vnode::VNode};
+use crate::{
+    backend::BackendTrait,
+    context::{ContextMap, ReactiveContext},
+    position::PositionIndex,
+    vnode::VNode,
+};
 
-use super::VNodeTrait;
+use super::{enter_vnode, Claim, VNodeTrait};
 
 pub struct ContextVNode<B: BackendTrait> {
     parent: Rc<VNode<B>>,
@@ -21,6 +26,10 @@ impl<B: BackendTrait> ContextVNode<B> {
     pub fn new(parent: Rc<VNode<B>>, context: ContextMap) -> Self {
         Self { parent, context }
     }
+    #[cfg(feature = "inspect")]
+    pub(crate) fn parent(&self) -> &Rc<VNode<B>> {
+        &self.parent
+    }
 }
 
 impl<B: BackendTrait> VNodeTrait<B> for ContextVNode<B> {
@@ -35,27 +44,53 @@ impl<B: BackendTrait> VNodeTrait<B> for ContextVNode<B> {
     fn get_context_map<'s>(&'s self) -> &'s ContextMap {
         &self.context
     }
+
+    fn claim_child_node(
+        &self,
+        position: PositionIndex,
+        decide: &dyn Fn(&B::Node) -> Claim,
+    ) -> Option<B::Node> {
+        self.parent.claim_child_node(position, decide)
+    }
 }
 
 pub fn get_context<B: BackendTrait, T: 'static>() -> Rc<T> {
+    try_get_context::<B, T>().expect("Context not set.")
+}
+/** Get the context value of type `T`, or `None` if no ancestor provides one.
+ */
+pub fn try_get_context<B: BackendTrait, T: 'static>() -> Option<Rc<T>> {
     B::get_vnode_key().with(|vn| {
-        let cmap = vn.get_context_map();
-        let type_id = TypeId::of::<T>();
-        let entry = cmap
+        let entry = vn
+            .get_context_map()
             .inner
-            .get(&type_id)
-            .expect("Context not set.")
+            .get(&TypeId::of::<T>())?
             .to_owned();
-        let val = entry.downcast::<T>().unwrap();
-        val
+        entry.downcast::<T>().ok()
     })
 }
+/** Get the context value of type `T`, or `T::default()` if no ancestor provides one.
+ */
+pub fn use_context_or_default<B: BackendTrait, T: Default + 'static>() -> Rc<T> {
+    try_get_context::<B, T>().unwrap_or_default()
+}
+/** Get the [ReactiveContext] of type `T` provided by an ancestor, if any.
+ */
+pub fn try_get_reactive_context<B: BackendTrait, T: 'static>() -> Option<Rc<ReactiveContext<T>>> {
+    try_get_context::<B, ReactiveContext<T>>()
+}
 enum WithContextState<B>
 where
     B: BackendTrait,
 {
-    NotStarted { value: Rc<dyn Any> },
-    Started { vnode: Rc<VNode<B>> },
+    NotStarted {
+        value: Rc<dyn Any>,
+        #[cfg(feature = "inspect")]
+        type_name: &'static str,
+    },
+    Started {
+        vnode: Rc<VNode<B>>,
+    },
     Null,
 }
 
@@ -81,13 +116,18 @@ where
         let this = self.project();
         let vk = B::get_vnode_key();
         let vnode = match std::mem::replace(this.state, WithContextState::Null) {
-            WithContextState::NotStarted { value } => {
+            WithContextState::NotStarted {
+                value,
+                #[cfg(feature = "inspect")]
+                type_name,
+            } => {
                 let parent = vk.with(Clone::clone);
+                let type_id = value.as_ref().type_id();
+                let parent_context = parent.get_context_map();
                 let context = ContextMap {
-                    inner: parent
-                        .get_context_map()
-                        .inner
-                        .update(value.as_ref().type_id(), value),
+                    inner: parent_context.inner.update(type_id, value),
+                    #[cfg(feature = "inspect")]
+                    type_names: parent_context.type_names.update(type_id, type_name),
                 };
                 let vnode = Rc::new(ContextVNode::new(parent, context).into());
                 vnode
@@ -95,7 +135,7 @@ where
             WithContextState::Started { vnode } => vnode,
             _ => unreachable!(),
         };
-        let res = vk.set(&vnode, || this.future.poll(cx));
+        let res = enter_vnode(&vnode, || this.future.poll(cx));
         *this.state = WithContextState::Started { vnode };
         res
     }
@@ -108,7 +148,11 @@ where
     pub fn new<T: 'static, I: IntoFuture<IntoFuture = F>>(into_future: I, value: Rc<T>) -> Self {
         Self {
             future: into_future.into_future(),
-            state: WithContextState::NotStarted { value },
+            state: WithContextState::NotStarted {
+                value,
+                #[cfg(feature = "inspect")]
+                type_name: std::any::type_name::<T>(),
+            },
         }
     }
 }
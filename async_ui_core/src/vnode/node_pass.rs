@@ -2,7 +2,7 @@ use std::rc::Rc;
 
 use crate::{backend::BackendTrait, context::ContextMap, position::PositionIndex, vnode::VNode};
 
-use super::VNodeTrait;
+use super::{Claim, VNodeTrait};
 
 pub struct PassVNode<B: BackendTrait> {
     parent: Rc<VNode<B>>,
@@ -19,6 +19,14 @@ impl<B: BackendTrait> PassVNode<B> {
             context,
         }
     }
+    #[cfg(feature = "inspect")]
+    pub(crate) fn parent(&self) -> &Rc<VNode<B>> {
+        &self.parent
+    }
+    #[cfg(feature = "inspect")]
+    pub(crate) fn index(&self) -> usize {
+        self.index
+    }
 }
 
 impl<B: BackendTrait> VNodeTrait<B> for PassVNode<B> {
@@ -35,4 +43,13 @@ impl<B: BackendTrait> VNodeTrait<B> for PassVNode<B> {
     fn get_context_map<'s>(&'s self) -> &'s ContextMap {
         &self.context
     }
+
+    fn claim_child_node(
+        &self,
+        mut position: PositionIndex,
+        decide: &dyn Fn(&B::Node) -> Claim,
+    ) -> Option<B::Node> {
+        position.wrap(self.index);
+        self.parent.claim_child_node(position, decide)
+    }
 }
@@ -1,6 +1,15 @@
+/*! Rendering children somewhere else in the tree.
+ *
+ * A portal has entries, where children are mounted, and exits, where their nodes show up.
+ * The nodes go to the exit picked with [PortalEntry::retarget], or else to the most recently mounted exit.
+ * When that changes, the nodes are moved over without being rendered again.
+ * With no exit mounted, the nodes are held until one is.
+ *
+ * Portals can also be looked up by name, through [PortalTargets] in the context.
+ */
 use std::{
-    cell::RefCell,
-    collections::BTreeMap,
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
     future::{Future, IntoFuture},
     marker::PhantomData,
     pin::Pin,
@@ -12,20 +21,112 @@ use pin_project_lite::pin_project;
 
 use crate::{backend::BackendTrait, context::ContextMap, position::PositionIndex};
 
-use super::{VNode, VNodeTrait};
+use super::{enter_vnode, node_context::try_get_context, Claim, VNode, VNodeTrait};
+
+thread_local! {
+    static MOVING: Cell<bool> = const { Cell::new(false) };
+}
+
+/** Whether the nodes being added or removed right now are only being moved by a portal,
+ * from one exit to another (or out of sight, while it has no exit).
+ *
+ * Lets [TransitionVNode][super::node_transition::TransitionVNode] tell moves from mounting and unmounting.
+ */
+pub(crate) fn is_moving() -> bool {
+    MOVING.with(Cell::get)
+}
+
+/** Sets [is_moving] until dropped, even if moving a node panics. */
+struct MovingGuard {
+    outer: bool,
+}
+impl MovingGuard {
+    fn new() -> Self {
+        Self {
+            outer: MOVING.with(|moving| moving.replace(true)),
+        }
+    }
+}
+impl Drop for MovingGuard {
+    fn drop(&mut self) {
+        MOVING.with(|moving| moving.set(self.outer));
+    }
+}
+
+struct Exit<B: BackendTrait> {
+    id: u64,
+    vnode: Rc<VNode<B>>,
+}
 
 struct Shared<B: BackendTrait> {
+    /// Mounted exits, in the order they were mounted.
+    exits: Vec<Exit<B>>,
+    /// The exit picked with [PortalEntry::retarget].
+    preferred: Option<u64>,
+    /// Where the nodes are now.
     target: Option<Rc<VNode<B>>>,
+    /// `Some` for nodes held while there is no target.
     nodes: BTreeMap<PositionIndex, Option<B::Node>>,
+    next_id: u64,
+}
+
+impl<B: BackendTrait> Shared<B> {
+    fn new() -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            exits: Vec::new(),
+            preferred: None,
+            target: None,
+            nodes: BTreeMap::new(),
+            next_id: 0,
+        }))
+    }
+    fn new_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+    /** Move the nodes if the exit they should be in has changed.
+     */
+    fn update_target(&mut self) {
+        let chosen = self
+            .preferred
+            .and_then(|id| self.exits.iter().find(|exit| exit.id == id))
+            .or(self.exits.last())
+            .map(|exit| exit.vnode.clone());
+        let unchanged = match (&chosen, &self.target) {
+            (Some(chosen), Some(target)) => Rc::ptr_eq(chosen, target),
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged {
+            return;
+        }
+        let old = self.target.take();
+        // Nodes taken out of an exit are being moved. Nodes that were held have not been shown yet.
+        let _moving = old.is_some().then(MovingGuard::new);
+        if let Some(old) = old {
+            self.nodes
+                .iter_mut()
+                .for_each(|(k, v)| *v = Some(old.del_child_node(k.clone())));
+        }
+        if let Some(new) = chosen.as_ref() {
+            self.nodes.iter_mut().for_each(|(k, v)| {
+                new.add_child_node(v.take().expect("node already placed"), k.clone())
+            });
+        }
+        self.target = chosen;
+    }
 }
 
 pub struct PortalVNode<B: BackendTrait> {
     shared: Rc<RefCell<Shared<B>>>,
+    /// Keeps the nodes of different entries apart.
+    entry_id: usize,
     context: ContextMap,
 }
 
 impl<B: BackendTrait> VNodeTrait<B> for PortalVNode<B> {
-    fn add_child_node(&self, node: B::Node, position: PositionIndex) {
+    fn add_child_node(&self, node: B::Node, mut position: PositionIndex) {
+        position.wrap(self.entry_id);
         let mut bm = self.shared.borrow_mut();
         let ins = if let Some(target) = bm.target.as_ref() {
             target.add_child_node(node, position.clone());
@@ -36,32 +137,78 @@ impl<B: BackendTrait> VNodeTrait<B> for PortalVNode<B> {
         bm.nodes.insert(position, ins);
     }
 
-    fn del_child_node(&self, position: PositionIndex) -> B::Node {
+    fn del_child_node(&self, mut position: PositionIndex) -> B::Node {
+        position.wrap(self.entry_id);
         let mut bm = self.shared.borrow_mut();
-        let _removed = bm.nodes.remove(&position);
-        bm.target.as_ref().unwrap().del_child_node(position)
+        match bm.nodes.remove(&position) {
+            Some(Some(held)) => held,
+            Some(None) => bm
+                .target
+                .as_ref()
+                .expect("placed node without a target")
+                .del_child_node(position),
+            None => panic!("node to delete not found"),
+        }
     }
 
     fn get_context_map<'s>(&'s self) -> &'s ContextMap {
         &self.context
     }
+
+    fn claim_child_node(
+        &self,
+        mut position: PositionIndex,
+        decide: &dyn Fn(&B::Node) -> Claim,
+    ) -> Option<B::Node> {
+        position.wrap(self.entry_id);
+        let target = self.shared.borrow().target.clone();
+        target.and_then(|target| target.claim_child_node(position, decide))
+    }
 }
 
 pub struct PortalEntry<B: BackendTrait> {
     shared: Rc<RefCell<Shared<B>>>,
 }
 
+impl<B: BackendTrait> Default for PortalEntry<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<B: BackendTrait> PortalEntry<B> {
-    pub fn mount<'m, I: IntoFuture>(
-        &'m mut self,
-        into_future: I,
-    ) -> WithPortal<'m, B, I::IntoFuture> {
+    /** A new portal, with no exit yet.
+     */
+    pub fn new() -> Self {
+        Self {
+            shared: Shared::new(),
+        }
+    }
+    pub fn mount<'m, I: IntoFuture>(&'m self, into_future: I) -> WithPortal<'m, B, I::IntoFuture> {
         WithPortal {
             future: into_future.into_future(),
             state: WithPortalState::Shared(self.shared.clone()),
             _phantom: PhantomData,
         }
     }
+    /** Make another exit for this portal.
+     */
+    pub fn exit(&self) -> PortalExit<B> {
+        let id = self.shared.borrow_mut().new_id();
+        PortalExit {
+            shared: self.shared.clone(),
+            id,
+        }
+    }
+    /** Send the nodes to the given exit, moving them if they are somewhere else.
+     *
+     * If that exit is not mounted (or `None` is given), the most recently mounted exit is used.
+     */
+    pub fn retarget(&self, exit: Option<PortalExitKey>) {
+        let mut bm = self.shared.borrow_mut();
+        bm.preferred = exit.map(|key| key.0);
+        bm.update_target();
+    }
 }
 enum WithPortalState<B: BackendTrait> {
     Shared(Rc<RefCell<Shared<B>>>),
@@ -82,36 +229,53 @@ impl<'m, B: BackendTrait, F: Future> Future for WithPortal<'m, B, F> {
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.project();
         let vnode = match std::mem::replace(this.state, WithPortalState::Null) {
-            WithPortalState::Shared(shared) => Rc::new(
-                (PortalVNode {
-                    context: B::get_vnode_key().with(|vn| vn.get_context_map().to_owned()),
-                    shared,
-                })
-                .into(),
-            ),
+            WithPortalState::Shared(shared) => {
+                let entry_id = shared.borrow_mut().new_id() as usize;
+                Rc::new(
+                    (PortalVNode {
+                        context: B::get_vnode_key().with(|vn| vn.get_context_map().to_owned()),
+                        entry_id,
+                        shared,
+                    })
+                    .into(),
+                )
+            }
             WithPortalState::VNode(vnode) => vnode,
             WithPortalState::Null => unreachable!(),
         };
-        let res = B::get_vnode_key().set(&vnode, || this.future.poll(cx));
+        let res = enter_vnode(&vnode, || this.future.poll(cx));
         *this.state = WithPortalState::VNode(vnode);
         res
     }
 }
+
+/** Identifies a [PortalExit], for [PortalEntry::retarget].
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PortalExitKey(u64);
+
+/** Where the nodes of a portal show up. Await it where they should go. Never completes.
+ */
 pub struct PortalExit<B: BackendTrait> {
     shared: Rc<RefCell<Shared<B>>>,
+    id: u64,
+}
+
+impl<B: BackendTrait> PortalExit<B> {
+    pub fn key(&self) -> PortalExitKey {
+        PortalExitKey(self.id)
+    }
 }
 
 impl<B: BackendTrait> Future for PortalExit<B> {
     type Output = ();
 
     fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let vnode = B::get_vnode_key().with(Clone::clone);
         let mut bm = self.shared.borrow_mut();
-        if bm.target.is_none() {
-            bm.nodes.iter_mut().for_each(|(k, v)| {
-                vnode.add_child_node(v.take().expect("portal already active"), k.clone());
-            });
-            bm.target = Some(vnode);
+        if !bm.exits.iter().any(|exit| exit.id == self.id) {
+            let vnode = B::get_vnode_key().with(Clone::clone);
+            bm.exits.push(Exit { id: self.id, vnode });
+            bm.update_target();
         }
         Poll::Pending
     }
@@ -120,23 +284,149 @@ impl<B: BackendTrait> Future for PortalExit<B> {
 impl<B: BackendTrait> Drop for PortalExit<B> {
     fn drop(&mut self) {
         let mut bm = self.shared.borrow_mut();
-        if let Some(vn) = bm.target.take() {
-            bm.nodes
-                .iter_mut()
-                .for_each(|(k, v)| *v = Some(vn.del_child_node(k.clone())));
+        let before = bm.exits.len();
+        bm.exits.retain(|exit| exit.id != self.id);
+        if bm.exits.len() != before {
+            bm.update_target();
         }
     }
 }
 
 pub fn create_portal_pair<B: BackendTrait>() -> (PortalEntry<B>, PortalExit<B>) {
-    let shared = Rc::new(RefCell::new(Shared {
-        nodes: BTreeMap::new(),
-        target: None,
-    }));
-    (
+    let entry = PortalEntry::new();
+    let exit = entry.exit();
+    (entry, exit)
+}
+
+/** Portals by name, like `"modal-root"`.
+ *
+ * Provide one as context near the root of the app,
+ * then use [portal_target] to mount exits and [portal_entry] to render into them from anywhere below.
+ */
+pub struct PortalTargets<B: BackendTrait> {
+    portals: RefCell<HashMap<String, PortalEntry<B>>>,
+}
+
+impl<B: BackendTrait> Default for PortalTargets<B> {
+    fn default() -> Self {
+        Self {
+            portals: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl<B: BackendTrait> PortalTargets<B> {
+    pub fn entry(&self, name: &str) -> PortalEntry<B> {
+        let mut portals = self.portals.borrow_mut();
+        let entry = portals.entry(name.to_owned()).or_default();
         PortalEntry {
-            shared: shared.clone(),
-        },
-        PortalExit { shared },
-    )
+            shared: entry.shared.clone(),
+        }
+    }
+    pub fn exit(&self, name: &str) -> PortalExit<B> {
+        self.entry(name).exit()
+    }
+}
+
+fn portal_targets<B: BackendTrait>() -> Rc<PortalTargets<B>> {
+    try_get_context::<B, PortalTargets<B>>().expect("PortalTargets not provided in context.")
+}
+
+/** An exit for the portal named `name`, from the [PortalTargets] in the context.
+ */
+pub fn portal_target<B: BackendTrait>(name: &str) -> PortalExit<B> {
+    portal_targets::<B>().exit(name)
+}
+
+/** An entry for the portal named `name`, from the [PortalTargets] in the context.
+ */
+pub fn portal_entry<B: BackendTrait>(name: &str) -> PortalEntry<B> {
+    portal_targets::<B>().entry(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::{poll_fn, Future},
+        pin::pin,
+        rc::Rc,
+        task::Poll,
+    };
+
+    use crate::{
+        test_util::{texts, Gate},
+        testing::{element, fragment, mount, run_until_stalled, text, TestBackend},
+        vnode::node_context::WithContext,
+    };
+
+    use super::{create_portal_pair, portal_entry, portal_target, PortalTargets};
+
+    #[test]
+    fn portal_renders_at_exit() {
+        let root = mount(async {
+            let (entry, exit) = create_portal_pair::<TestBackend>();
+            fragment((
+                element("source", entry.mount(fragment((text("x"), text("y"))))),
+                element("target", exit),
+            ))
+            .await;
+        });
+        let source = root.find_by_tag("source").unwrap();
+        let target = root.find_by_tag("target").unwrap();
+        assert!(source.children().is_empty());
+        assert_eq!(texts(&target), ["x", "y"]);
+    }
+
+    #[test]
+    fn portal_moves_between_exits() {
+        let (entry, exit_a) = create_portal_pair::<TestBackend>();
+        let entry = Rc::new(entry);
+        let (entry_1, exit_b, key_a) = (entry.clone(), entry.exit(), exit_a.key());
+        let close_b = Gate::default();
+        let close_b_2 = close_b.clone();
+        let root = mount(fragment((
+            element("source", async move { entry_1.mount(text("x")).await }),
+            element("a", exit_a),
+            element("b", async move {
+                let mut exit_b = pin!(exit_b);
+                let mut closed = pin!(close_b_2.wait());
+                poll_fn(|cx| match closed.as_mut().poll(cx) {
+                    Poll::Ready(()) => Poll::Ready(()),
+                    Poll::Pending => exit_b.as_mut().poll(cx),
+                })
+                .await
+            }),
+        )));
+        let (a, b) = (
+            root.find_by_tag("a").unwrap(),
+            root.find_by_tag("b").unwrap(),
+        );
+        let x = b.find_by_text("x").unwrap();
+        entry.retarget(Some(key_a));
+        assert!(b.children().is_empty());
+        assert!(a.children()[0].ptr_eq(&x));
+        entry.retarget(None);
+        assert!(b.children()[0].ptr_eq(&x));
+        close_b.open();
+        run_until_stalled();
+        assert!(a.children()[0].ptr_eq(&x));
+    }
+
+    #[test]
+    fn named_portal_targets() {
+        let root = mount(WithContext::<TestBackend, _>::new(
+            fragment((
+                element("modal-root", async {
+                    portal_target::<TestBackend>("modal").await
+                }),
+                element("page", async {
+                    let modal = portal_entry::<TestBackend>("modal");
+                    modal.mount(text("m")).await
+                }),
+            )),
+            Rc::new(PortalTargets::<TestBackend>::default()),
+        ));
+        assert_eq!(texts(&root.find_by_tag("modal-root").unwrap()), ["m"]);
+        assert!(root.find_by_tag("page").unwrap().children().is_empty());
+    }
 }
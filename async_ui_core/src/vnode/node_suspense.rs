@@ -0,0 +1,236 @@
+use std::{
+    any::TypeId,
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    future::{poll_fn, ready, Future, IntoFuture},
+    pin::pin,
+    rc::Rc,
+    task::{Poll, Waker},
+};
+
+use crate::{backend::BackendTrait, context::ContextMap, position::PositionIndex, vnode::VNode};
+
+use super::{node_pass::PassVNode, Claim, VNodeTrait, WithVNode};
+
+/** Set once the children of a [suspense] have something to show.
+ * Put in the context of the children, for [mark_ready].
+ */
+#[derive(Default)]
+struct Readiness {
+    ready: Cell<bool>,
+    waker: Cell<Option<Waker>>,
+}
+impl Readiness {
+    fn set_ready(&self) {
+        self.ready.set(true);
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+struct Shared<B: BackendTrait> {
+    revealed: bool,
+    held: BTreeMap<PositionIndex, B::Node>,
+}
+
+/** Holds the nodes of the children of a [suspense] until the fallback is swapped out.
+ */
+pub struct SuspenseVNode<B: BackendTrait> {
+    parent: Rc<VNode<B>>,
+    context: ContextMap,
+    readiness: Rc<Readiness>,
+    shared: RefCell<Shared<B>>,
+}
+
+impl<B: BackendTrait> SuspenseVNode<B> {
+    #[cfg(feature = "inspect")]
+    pub(crate) fn parent(&self) -> &Rc<VNode<B>> {
+        &self.parent
+    }
+    fn reveal(&self) {
+        let held = {
+            let mut bm = self.shared.borrow_mut();
+            bm.revealed = true;
+            std::mem::take(&mut bm.held)
+        };
+        held.into_iter()
+            .for_each(|(position, node)| self.parent.add_child_node(node, position));
+    }
+}
+
+impl<B: BackendTrait> VNodeTrait<B> for SuspenseVNode<B> {
+    fn add_child_node(&self, node: B::Node, position: PositionIndex) {
+        let mut bm = self.shared.borrow_mut();
+        if bm.revealed {
+            drop(bm);
+            self.parent.add_child_node(node, position);
+        } else {
+            bm.held.insert(position, node);
+            self.readiness.set_ready();
+        }
+    }
+
+    fn del_child_node(&self, position: PositionIndex) -> B::Node {
+        let mut bm = self.shared.borrow_mut();
+        if bm.revealed {
+            drop(bm);
+            self.parent.del_child_node(position)
+        } else {
+            bm.held.remove(&position).expect("node to delete not found")
+        }
+    }
+
+    fn get_context_map(&self) -> &ContextMap {
+        &self.context
+    }
+
+    fn claim_child_node(
+        &self,
+        position: PositionIndex,
+        decide: &dyn Fn(&B::Node) -> Claim,
+    ) -> Option<B::Node> {
+        self.parent.claim_child_node(position, decide)
+    }
+}
+
+/** Render `fallback` until `children` have something to show, then swap them.
+ *
+ * The children are ready once they add their first node, or call [mark_ready].
+ * Until then, their nodes are held back.
+ * When ready, the fallback is unmounted and the children's nodes are inserted in the same poll,
+ * so the two never show together.
+ * If the children are ready as soon as they are first polled, the fallback is never shown.
+ *
+ * Returns when the children complete.
+ */
+pub async fn suspense<B: BackendTrait, F: IntoFuture, C: IntoFuture>(
+    fallback: F,
+    children: C,
+) -> C::Output {
+    suspense_with_min_display::<B, _, _, _>(fallback, children, ready(())).await
+}
+
+/** Like [suspense], but once the fallback has been shown,
+ * keep it until `min_display` completes too, to avoid flicker.
+ *
+ * `min_display` is usually a timer, started when the suspense is first polled.
+ */
+pub async fn suspense_with_min_display<B, F, C, D>(
+    fallback: F,
+    children: C,
+    min_display: D,
+) -> C::Output
+where
+    B: BackendTrait,
+    F: IntoFuture,
+    C: IntoFuture,
+    D: IntoFuture<Output = ()>,
+{
+    let parent = B::get_vnode_key().with(Clone::clone);
+    let readiness = Rc::new(Readiness::default());
+    let mut context = parent.get_context_map().to_owned();
+    context.insert(readiness.clone());
+    let suspense_vnode: Rc<VNode<B>> = Rc::new(
+        SuspenseVNode {
+            parent: Rc::new(PassVNode::new(parent.clone(), 1).into()),
+            context,
+            readiness: readiness.clone(),
+            shared: RefCell::new(Shared {
+                revealed: false,
+                held: BTreeMap::new(),
+            }),
+        }
+        .into(),
+    );
+    let mut children = pin!(WithVNode::new(
+        children.into_future(),
+        suspense_vnode.clone()
+    ));
+    let mut fallback = Some(Box::pin(WithVNode::new(
+        fallback.into_future(),
+        Rc::new(PassVNode::new(parent, 0).into()),
+    )));
+    let mut fallback_shown = false;
+    let mut fallback_done = false;
+    let mut min_display = pin!(min_display.into_future());
+    let mut min_display_done = false;
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = children.as_mut().poll(cx) {
+            return Poll::Ready(output);
+        }
+        if let Some(mut fb) = fallback.take() {
+            readiness.waker.set(Some(cx.waker().to_owned()));
+            if !min_display_done {
+                min_display_done = min_display.as_mut().poll(cx).is_ready();
+            }
+            if readiness.ready.get() && (!fallback_shown || min_display_done) {
+                drop(fb);
+                if let VNode::Suspense(suspense) = &*suspense_vnode {
+                    suspense.reveal();
+                }
+            } else {
+                fallback_shown = true;
+                if !fallback_done {
+                    fallback_done = fb.as_mut().poll(cx).is_ready();
+                }
+                fallback = Some(fb);
+            }
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+/** Tell the nearest enclosing [suspense] that its children are ready to be shown,
+ * even if they have no nodes yet.
+ *
+ * Does nothing outside of a suspense.
+ */
+pub fn mark_ready<B: BackendTrait>() {
+    B::get_vnode_key().with(|vn| {
+        if let Some(readiness) = vn.get_context_map().inner.get(&TypeId::of::<Readiness>()) {
+            if let Ok(readiness) = readiness.clone().downcast::<Readiness>() {
+                readiness.set_ready();
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_util::{texts, Gate},
+        testing::{fragment, mount, run_until_stalled, text, TestBackend},
+    };
+
+    use super::suspense_with_min_display;
+
+    #[test]
+    fn suspense_swaps_after_min_display() {
+        let (data, min_display) = (Gate::default(), Gate::default());
+        let (data_1, min_display_1) = (data.clone(), min_display.clone());
+        let root = mount(fragment((
+            text("a"),
+            async move {
+                suspense_with_min_display::<TestBackend, _, _, _>(
+                    text("loading"),
+                    async move {
+                        data_1.wait().await;
+                        fragment((text("b"), text("c"))).await;
+                    },
+                    min_display_1.wait(),
+                )
+                .await
+            },
+            text("d"),
+        )));
+        assert_eq!(texts(&root), ["a", "loading", "d"]);
+        data.open();
+        run_until_stalled();
+        assert_eq!(texts(&root), ["a", "loading", "d"]);
+        min_display.open();
+        run_until_stalled();
+        assert_eq!(texts(&root), ["a", "b", "c", "d"]);
+    }
+}
@@ -0,0 +1,365 @@
+/*! Animating nodes in and out.
+ *
+ * Wrap children with [WithTransition] to run [Transition::enter] on each node right after it is inserted,
+ * and, once the wrapper is dropped, [Transition::exit] on each node before it is removed.
+ * The node stays in place until its exit future completes.
+ *
+ * Only mounting and unmounting are animated. Nodes that a portal moves from one exit to another
+ * neither enter again nor exit, even if the exit they leave is being unmounted: they are detached right away.
+ * Nodes removed while the wrapper is still alive go right away too, and so do nodes whose parent has been removed
+ * (no longer [connected][crate::backend::BackendTrait::is_connected] by the time the exit would start),
+ * as there is nothing left to see. Nodes held by a portal with no exit (as in [switch][crate::switch::switch])
+ * are already detached when their exit runs.
+ */
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    future::{Future, IntoFuture},
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use pin_project_lite::pin_project;
+
+use crate::{
+    backend::BackendTrait,
+    context::ContextMap,
+    executor::{spawn_local, try_spawn_local},
+    position::PositionIndex,
+};
+
+use super::{enter_vnode, node_portal::is_moving, Claim, VNode, VNodeTrait};
+
+pub type TransitionFuture = Pin<Box<dyn Future<Output = ()>>>;
+pub type TransitionFn<B> = Box<dyn Fn(&<B as BackendTrait>::Node) -> TransitionFuture>;
+
+/** What to do with nodes entering and leaving. Share one between many [WithTransition]s with an [Rc].
+ */
+pub struct Transition<B: BackendTrait> {
+    /// Called right after a node is inserted (but not when a portal moves it). The future runs in the background.
+    pub enter: TransitionFn<B>,
+    /// Called when a node is to be removed. The node is removed once the future completes.
+    /// Not called if the node's parent is being removed as well.
+    pub exit: TransitionFn<B>,
+}
+
+impl<B: BackendTrait> Default for Transition<B> {
+    fn default() -> Self {
+        Self {
+            enter: Box::new(|_| Box::pin(async {})),
+            exit: Box::new(|_| Box::pin(async {})),
+        }
+    }
+}
+
+thread_local! {
+    static NEXT_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+pub struct TransitionVNode<B: BackendTrait> {
+    parent: Rc<VNode<B>>,
+    transition: Rc<Transition<B>>,
+    /// Keeps the nodes still leaving apart from the ones of whatever replaces them.
+    id: usize,
+    /// Set once the [WithTransition] is dropped.
+    leaving: Cell<bool>,
+    nodes: RefCell<BTreeMap<PositionIndex, B::Node>>,
+}
+
+impl<B: BackendTrait> TransitionVNode<B> {
+    #[cfg(feature = "inspect")]
+    pub(crate) fn parent(&self) -> &Rc<VNode<B>> {
+        &self.parent
+    }
+}
+
+impl<B: BackendTrait> VNodeTrait<B> for TransitionVNode<B> {
+    fn add_child_node(&self, node: B::Node, mut position: PositionIndex) {
+        position.wrap(self.id);
+        self.nodes
+            .borrow_mut()
+            .insert(position.clone(), node.clone());
+        self.parent.add_child_node(node.clone(), position);
+        if !is_moving() {
+            spawn_local((self.transition.enter)(&node)).detach();
+        }
+    }
+
+    fn del_child_node(&self, mut position: PositionIndex) -> B::Node {
+        position.wrap(self.id);
+        let node = self.nodes.borrow_mut().remove(&position);
+        // A portal moving the node re-adds it elsewhere right away, so it must be detached by then.
+        if !self.leaving.get() || is_moving() {
+            return self.parent.del_child_node(position);
+        }
+        let node = node.expect("node to delete not found");
+        let parent = self.parent.clone();
+        let transition = self.transition.clone();
+        let (leaving, leaving_position) = (node.clone(), position.clone());
+        let exit = try_spawn_local(async move {
+            // By now, an ancestor being unmounted along with this node has been removed.
+            if B::is_connected(&leaving) {
+                (transition.exit)(&leaving).await;
+            }
+            parent.del_child_node(leaving_position);
+        });
+        match exit {
+            Some(task) => task.detach(),
+            // The executor is gone (thread exit); there is nothing to animate.
+            None => return self.parent.del_child_node(position),
+        }
+        node
+    }
+
+    fn get_context_map<'s>(&'s self) -> &'s ContextMap {
+        self.parent.get_context_map()
+    }
+
+    fn claim_child_node(
+        &self,
+        mut position: PositionIndex,
+        decide: &dyn Fn(&B::Node) -> Claim,
+    ) -> Option<B::Node> {
+        position.wrap(self.id);
+        self.parent.claim_child_node(position, decide)
+    }
+}
+
+enum WithTransitionState<B: BackendTrait> {
+    NotStarted { transition: Rc<Transition<B>> },
+    Started { vnode: Rc<VNode<B>> },
+    Null,
+}
+
+pin_project! {
+    /** Render the future with its nodes going through a [Transition].
+     */
+    pub struct WithTransition<B: BackendTrait, F: Future> {
+        #[pin]
+        future: F,
+        state: WithTransitionState<B>,
+    }
+    impl<B: BackendTrait, F: Future> PinnedDrop for WithTransition<B, F> {
+        fn drop(this: Pin<&mut Self>) {
+            // Runs before the future is dropped, so its nodes are removed as leaving.
+            if let WithTransitionState::Started { vnode } = &this.state {
+                if let VNode::Transition(vn) = &**vnode {
+                    vn.leaving.set(true);
+                }
+            }
+        }
+    }
+}
+
+impl<B: BackendTrait, F: Future> WithTransition<B, F> {
+    pub fn new<I: IntoFuture<IntoFuture = F>>(
+        into_future: I,
+        transition: Rc<Transition<B>>,
+    ) -> Self {
+        Self {
+            future: into_future.into_future(),
+            state: WithTransitionState::NotStarted { transition },
+        }
+    }
+}
+
+impl<B: BackendTrait, F: Future> Future for WithTransition<B, F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let vnode = match std::mem::replace(this.state, WithTransitionState::Null) {
+            WithTransitionState::NotStarted { transition } => Rc::new(
+                TransitionVNode {
+                    parent: B::get_vnode_key().with(Clone::clone),
+                    transition,
+                    id: NEXT_ID.with(|id| id.replace(id.get() + 1)),
+                    leaving: Cell::new(false),
+                    nodes: RefCell::new(BTreeMap::new()),
+                }
+                .into(),
+            ),
+            WithTransitionState::Started { vnode } => vnode,
+            WithTransitionState::Null => unreachable!(),
+        };
+        let res = enter_vnode(&vnode, || this.future.poll(cx));
+        *this.state = WithTransitionState::Started { vnode };
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        future::{pending, poll_fn, Future},
+        pin::pin,
+        rc::Rc,
+        task::Poll,
+    };
+
+    use observables::cell::ReactiveCell;
+
+    use crate::{
+        list::{keyed_items, ListModel},
+        switch::show_when,
+        test_util::{texts, Gate},
+        testing::{
+            element, fragment, mount, run_until_stalled, text, ElementFuture, TestBackend, TestNode,
+        },
+        vnode::node_portal::create_portal_pair,
+    };
+
+    use super::{Transition, WithTransition};
+
+    #[test]
+    fn transition_delays_removal() {
+        let (gate, exited) = (Gate::default(), Gate::default());
+        let entered = Rc::new(RefCell::new(Vec::new()));
+        let (gate_1, exited_1, entered_1) = (gate.clone(), exited.clone(), entered.clone());
+        let transition = Rc::new(Transition::<TestBackend> {
+            enter: Box::new(move |node| {
+                entered_1.borrow_mut().push(node.text_content());
+                Box::pin(async {})
+            }),
+            exit: Box::new(move |_| {
+                let exited = exited_1.clone();
+                Box::pin(async move { exited.wait().await })
+            }),
+        });
+        let root = mount(fragment((
+            text("a"),
+            async move {
+                let b = ElementFuture::new(gate_1.wait(), TestNode::new_text("b"));
+                WithTransition::new(b, transition).await
+            },
+            text("c"),
+        )));
+        assert_eq!(*entered.borrow(), ["b"]);
+        gate.open();
+        run_until_stalled();
+        assert_eq!(texts(&root), ["a", "b", "c"]);
+        exited.open();
+        run_until_stalled();
+        assert_eq!(texts(&root), ["a", "c"]);
+    }
+
+    #[test]
+    fn transition_delays_removal_from_a_list() {
+        let exited = Gate::default();
+        let exited_1 = exited.clone();
+        let transition = Rc::new(Transition::<TestBackend> {
+            exit: Box::new(move |_| {
+                let exited = exited_1.clone();
+                Box::pin(async move { exited.wait().await })
+            }),
+            ..Default::default()
+        });
+        let model = Rc::new(ReactiveCell::new(ListModel::from_iter(
+            ["a", "b", "c"].into_iter(),
+        )));
+        let ul = TestNode::new_element("ul");
+        let (model_1, ul_1) = (model.clone(), ul.clone());
+        mount(async move {
+            let render = |item: &'static str| WithTransition::new(text(item), transition.clone());
+            let data = model_1.as_observable();
+            let items = keyed_items::<TestBackend, _, _, _>(
+                ul_1.clone(),
+                &|| TestNode::new_element("anchor"),
+                &data,
+                &render,
+                &|item: &&str| *item,
+            );
+            ElementFuture::new(items, ul_1).await
+        });
+        let items = || -> Vec<String> { ul.children().iter().filter_map(TestNode::text).collect() };
+        model.borrow_mut().remove(1);
+        run_until_stalled();
+        assert_eq!(items(), ["a", "b", "c"]);
+        exited.open();
+        run_until_stalled();
+        assert_eq!(items(), ["a", "c"]);
+    }
+
+    #[test]
+    fn transition_skips_exit_when_parent_goes() {
+        let exits = Rc::new(Cell::new(0));
+        let exits_1 = exits.clone();
+        let transition = Rc::new(Transition::<TestBackend> {
+            exit: Box::new(move |_| {
+                exits_1.set(exits_1.get() + 1);
+                Box::pin(pending())
+            }),
+            ..Default::default()
+        });
+        let shown = Rc::new(ReactiveCell::new(true));
+        let shown_1 = shown.clone();
+        let root = mount(async move {
+            let children = || element("div", WithTransition::new(text("x"), transition.clone()));
+            fragment((show_when::<TestBackend, _>(
+                &shown_1.as_observable(),
+                &children,
+            ),))
+            .await
+        });
+        assert_eq!(texts(&root), ["x"]);
+        shown.set(false);
+        run_until_stalled();
+        assert_eq!(texts(&root), [""; 0]);
+        assert_eq!(exits.get(), 0);
+    }
+
+    #[test]
+    fn portal_moves_skip_transitions() {
+        let (entered, exits) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(0)));
+        let (entered_1, exits_1) = (entered.clone(), exits.clone());
+        let transition = Rc::new(Transition::<TestBackend> {
+            enter: Box::new(move |_| {
+                entered_1.set(entered_1.get() + 1);
+                Box::pin(async {})
+            }),
+            exit: Box::new(move |_| {
+                exits_1.set(exits_1.get() + 1);
+                Box::pin(pending())
+            }),
+        });
+        let (entry, exit_a) = create_portal_pair::<TestBackend>();
+        let entry = Rc::new(entry);
+        let (entry_1, exit_b, key_a) = (entry.clone(), entry.exit(), exit_a.key());
+        let (transition_a, transition_b) = (transition.clone(), transition);
+        let close_b = Gate::default();
+        let close_b_1 = close_b.clone();
+        let root = mount(fragment((
+            element("source", async move { entry_1.mount(text("x")).await }),
+            element("a", WithTransition::new(exit_a, transition_a)),
+            element("b", async move {
+                let mut exit_b = pin!(WithTransition::new(exit_b, transition_b));
+                let mut closed = pin!(close_b_1.wait());
+                poll_fn(|cx| match closed.as_mut().poll(cx) {
+                    Poll::Ready(()) => Poll::Ready(()),
+                    Poll::Pending => exit_b.as_mut().poll(cx),
+                })
+                .await
+            }),
+        )));
+        let (a, b) = (
+            root.find_by_tag("a").unwrap(),
+            root.find_by_tag("b").unwrap(),
+        );
+        let x = b.find_by_text("x").unwrap();
+        assert_eq!(entered.get(), 1);
+        entry.retarget(Some(key_a));
+        entry.retarget(None);
+        run_until_stalled();
+        assert!(b.children()[0].ptr_eq(&x));
+        assert_eq!(entered.get(), 1);
+        // Closing exit b moves the node to exit a at once, instead of leaving it in b to exit.
+        close_b.open();
+        run_until_stalled();
+        assert!(a.children()[0].ptr_eq(&x));
+        assert_eq!(a.children().len(), 1);
+        assert!(x.parent().unwrap().ptr_eq(&a));
+        assert_eq!((entered.get(), exits.get()), (1, 0));
+    }
+}
@@ -1,6 +1,7 @@
 use std::{future::Future, rc::Rc};
 
 use async_ui_core::{backend::BackendTrait, vnode::VNode};
+use gtk::traits::WidgetExt;
 use scoped_tls::scoped_thread_local;
 
 use crate::{executor::set_executor_future, widget::WrappedWidget};
@@ -21,6 +22,10 @@ impl BackendTrait for Backend {
         parent.del_child_node(child)
     }
 
+    fn is_connected(node: &Self::Node) -> bool {
+        node.widget.root().is_some()
+    }
+
     fn drive_executor<F: Future<Output = ()> + 'static>(fut: F) {
         set_executor_future(fut)
     }
@@ -4,7 +4,7 @@ use gtk::traits::ButtonExt;
 
 use crate::{
     widget::{single::ButtonOp, WidgetOp, WrappedWidget},
-    Fragment,
+    Fragment, NodeRef,
 };
 
 use super::{
@@ -17,17 +17,25 @@ pub struct PressEvent {}
 pub struct ButtonProps<'c> {
     pub children: Fragment<'c>,
     pub on_press: &'c mut (dyn FnMut(PressEvent) + 'c),
+    pub node_ref: Option<&'c NodeRef>,
 }
 impl<'c> Default for ButtonProps<'c> {
     fn default() -> Self {
         Self {
             children: Default::default(),
             on_press: dummy_handler(),
+            node_ref: None,
         }
     }
 }
 
-pub async fn button<'c>(ButtonProps { children, on_press }: ButtonProps<'c>) {
+pub async fn button<'c>(
+    ButtonProps {
+        children,
+        on_press,
+        node_ref,
+    }: ButtonProps<'c>,
+) {
     let button = gtk::Button::new();
     let manager = EventsManager::new();
     if !is_dummy_handler(on_press) {
@@ -55,5 +63,6 @@ pub async fn button<'c>(ButtonProps { children, on_press }: ButtonProps<'c>) {
             op: WidgetOp::SingleChild(&ButtonOp),
         },
     )
+    .node_ref(node_ref)
     .await;
 }
@@ -0,0 +1,96 @@
+use std::{cell::Cell, rc::Rc};
+
+use futures_lite::FutureExt;
+use glib::Cast;
+use gtk::traits::CheckButtonExt;
+use observables::{ObservableAs, ObservableAsExt};
+
+use crate::{
+    widget::{WidgetOp, WrappedWidget},
+    NodeRef,
+};
+
+use super::{
+    dummy::{dummy_handler, is_dummy_handler},
+    events::{EventsManager, QueuedEvent},
+    ElementFuture,
+};
+
+pub struct CheckboxChangeEvent {
+    node: gtk::CheckButton,
+}
+impl CheckboxChangeEvent {
+    pub fn get_value(&self) -> bool {
+        self.node.is_active()
+    }
+}
+
+pub struct CheckboxProps<'c> {
+    pub value: &'c dyn ObservableAs<bool>,
+    pub on_change: &'c mut (dyn FnMut(CheckboxChangeEvent) + 'c),
+    pub node_ref: Option<&'c NodeRef>,
+}
+impl<'c> Default for CheckboxProps<'c> {
+    fn default() -> Self {
+        Self {
+            value: &[false],
+            on_change: dummy_handler(),
+            node_ref: None,
+        }
+    }
+}
+
+/** Checkbox - GTK CheckButton
+ *
+ */
+pub async fn checkbox<'c>(
+    CheckboxProps {
+        value,
+        on_change,
+        node_ref,
+    }: CheckboxProps<'c>,
+) {
+    let check = gtk::CheckButton::new();
+    let manager = EventsManager::new();
+    // `toggled` also fires for `set_active`; only user input should reach `on_change`, as on the web.
+    let setting = Rc::new(Cell::new(false));
+    if !is_dummy_handler(on_change) {
+        let mgr = manager.clone();
+        let setting = setting.clone();
+        check.connect_toggled(move |_c| {
+            if !setting.get() {
+                mgr.add_event(QueuedEvent::Toggle)
+            }
+        });
+    }
+    let check_1 = check.clone();
+    ElementFuture::new(
+        (async {
+            manager.grab_waker().await;
+            loop {
+                let mut events = manager.get_queue().await;
+                for event in events.drain(..) {
+                    if let QueuedEvent::Toggle = event {
+                        on_change(CheckboxChangeEvent {
+                            node: check_1.clone(),
+                        });
+                    }
+                }
+            }
+        })
+        .or(value.for_each(|v| {
+            if check.is_active() != *v {
+                setting.set(true);
+                check.set_active(*v);
+                setting.set(false);
+            }
+        })),
+        WrappedWidget {
+            widget: check.clone().upcast(),
+            inner_widget: check.clone().upcast(),
+            op: WidgetOp::NoChild,
+        },
+    )
+    .node_ref(node_ref)
+    .await;
+}
@@ -18,6 +18,7 @@ pub(super) enum QueuedEvent {
     // KeyDown,
     Focus,
     Blur,
+    Toggle,
 }
 
 pub(super) struct EventsManager {
@@ -0,0 +1,56 @@
+use std::{future::IntoFuture, hash::Hash, marker::PhantomData};
+
+use async_ui_core::list::{keyed_items, ListModel};
+use glib::Cast;
+use gtk::Widget;
+use observables::ObservableAs;
+
+use crate::{
+    backend::Backend,
+    widget::{gtk_box::GtkBoxOp, WidgetOp, WrappedWidget},
+};
+
+use super::{
+    list::{no_child, DummyObservableAs, DUMMY_USED},
+    ElementFuture,
+};
+
+pub struct KeyedListProps<'c, T: Clone, K, F: IntoFuture> {
+    pub data: &'c dyn ObservableAs<ListModel<T>>,
+    pub render: &'c dyn Fn(T) -> F,
+    /// Items with the same key are considered the same item. Keys should be unique.
+    pub key: &'c dyn Fn(&T) -> K,
+}
+impl<'c, T: Clone + 'c, K, F: IntoFuture> Default for KeyedListProps<'c, T, K, F> {
+    fn default() -> Self {
+        Self {
+            data: &DummyObservableAs(PhantomData),
+            render: &|_: T| panic!("{}", DUMMY_USED),
+            key: &|_: &T| panic!("{}", DUMMY_USED),
+        }
+    }
+}
+
+/** List where items are identified by key.
+ *
+ * Items whose keys survive a change of the model keep running and are moved, not re-created.
+ * See the web backend's `keyed_list` for details.
+ */
+pub async fn keyed_list<'c, T: Clone + 'c, K: Hash + Eq + 'c, F: IntoFuture + 'c>(
+    KeyedListProps { data, render, key }: KeyedListProps<'c, T, K, F>,
+) {
+    let container_node: Widget = gtk::Box::new(gtk::Orientation::Vertical, 0).upcast();
+    let wrapped_container_node = WrappedWidget {
+        widget: container_node.clone(),
+        inner_widget: container_node.clone().upcast(),
+        op: WidgetOp::MultiChild(&GtkBoxOp),
+    };
+    let new_anchor = || no_child(gtk::Separator::new(gtk::Orientation::Horizontal).upcast());
+    let inside =
+        keyed_items::<Backend, _, _, _>(wrapped_container_node, &new_anchor, data, render, key);
+    let scroll_window = gtk::ScrolledWindow::new();
+    scroll_window.set_child(Some(&container_node));
+    scroll_window.set_propagate_natural_height(true);
+    scroll_window.set_propagate_natural_width(true);
+    ElementFuture::new(inside, no_child(scroll_window.upcast())).await;
+}
@@ -0,0 +1,76 @@
+use futures_lite::FutureExt;
+use glib::Cast;
+use gtk::traits::ButtonExt;
+use observables::{ObservableAs, ObservableAsExt};
+
+use crate::{
+    widget::{single::ButtonOp, WidgetOp, WrappedWidget},
+    Fragment, NodeRef,
+};
+
+use super::{
+    button::PressEvent,
+    dummy::{dummy_handler, is_dummy_handler},
+    events::{EventsManager, QueuedEvent},
+    ElementFuture,
+};
+
+pub struct LinkProps<'c> {
+    pub children: Fragment<'c>,
+    pub href: &'c dyn ObservableAs<str>,
+    pub on_press: &'c mut (dyn FnMut(PressEvent) + 'c),
+    pub node_ref: Option<&'c NodeRef>,
+}
+impl<'c> Default for LinkProps<'c> {
+    fn default() -> Self {
+        Self {
+            children: Default::default(),
+            href: &[""],
+            on_press: dummy_handler(),
+            node_ref: None,
+        }
+    }
+}
+
+/** Link - GTK LinkButton
+ *
+ * Opens `href` when clicked, after calling `on_press`.
+ */
+pub async fn link<'c>(
+    LinkProps {
+        children,
+        href,
+        on_press,
+        node_ref,
+    }: LinkProps<'c>,
+) {
+    let link = gtk::LinkButton::new("");
+    let manager = EventsManager::new();
+    if !is_dummy_handler(on_press) {
+        let mgr = manager.clone();
+        link.connect_clicked(move |_b| mgr.add_event(QueuedEvent::Click));
+    }
+    let link_1 = link.clone();
+    ElementFuture::new(
+        (children)
+            .or(async {
+                manager.grab_waker().await;
+                loop {
+                    let mut events = manager.get_queue().await;
+                    for event in events.drain(..) {
+                        if let QueuedEvent::Click = event {
+                            on_press(PressEvent {});
+                        }
+                    }
+                }
+            })
+            .or(href.for_each(|href| link_1.set_uri(href))),
+        WrappedWidget {
+            widget: link.clone().upcast(),
+            inner_widget: link.upcast(),
+            op: WidgetOp::SingleChild(&ButtonOp),
+        },
+    )
+    .node_ref(node_ref)
+    .await;
+}
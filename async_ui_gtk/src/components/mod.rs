@@ -2,19 +2,28 @@ mod dummy;
 mod events;
 
 mod button;
+mod checkbox;
+mod keyed_list;
+mod link;
 mod list;
 mod text;
 mod text_input;
 mod view;
-pub use button::{button, ButtonProps};
+pub use button::{button, ButtonProps, PressEvent};
+pub use checkbox::{checkbox, CheckboxChangeEvent, CheckboxProps};
+pub use keyed_list::{keyed_list, KeyedListProps};
+pub use link::{link, LinkProps};
 pub use list::{list, ListModel, ListProps};
 pub use text::text;
-pub use text_input::{text_input, TextInputProps};
+pub use text_input::{text_input, TextInputEvent, TextInputProps};
 pub use view::{view, ViewProps};
 
 use async_ui_core::{
     backend::BackendTrait,
+    lifecycle::Lifecycle,
+    node_ref::NodeRef,
     vnode::{
+        enter_vnode,
         node_concrete::{ConcreteNodeVNode, RefNode},
         VNode, VNodeTrait,
     },
@@ -39,6 +48,7 @@ pin_project! {
 struct ElementFutureInner {
     node: WrappedWidget,
     vnodes: Option<MyAndParentVNodes>,
+    lifecycle: Lifecycle<WrappedWidget>,
 }
 struct MyAndParentVNodes {
     my: Rc<VNode<Backend>>,
@@ -48,6 +58,7 @@ struct MyAndParentVNodes {
 impl Drop for ElementFutureInner {
     fn drop(&mut self) {
         if let Some(MyAndParentVNodes { parent, .. }) = &self.vnodes {
+            self.lifecycle.unmount(&self.node);
             parent.del_child_node(Default::default());
         }
     }
@@ -56,9 +67,33 @@ impl<F: Future> ElementFuture<F> {
     fn new(future: F, node: WrappedWidget) -> Self {
         Self {
             future,
-            inner: ElementFutureInner { node, vnodes: None },
+            inner: ElementFutureInner {
+                node,
+                vnodes: None,
+                lifecycle: Lifecycle::default(),
+            },
         }
     }
+    /** Call `callback` with the node right after it is added to its parent.
+     */
+    pub fn on_mount<C: FnOnce(&WrappedWidget) + 'static>(mut self, callback: C) -> Self {
+        self.inner.lifecycle.add_on_mount(callback);
+        self
+    }
+    /** Call `callback` with the node right before it is removed from its parent.
+     */
+    pub fn on_unmount<C: FnOnce(&WrappedWidget) + 'static>(mut self, callback: C) -> Self {
+        self.inner.lifecycle.add_on_unmount(callback);
+        self
+    }
+    /** Fill `node_ref` with the node while it is mounted. Does nothing if `None`.
+     */
+    pub fn node_ref(mut self, node_ref: Option<&NodeRef<Backend>>) -> Self {
+        if let Some(node_ref) = node_ref {
+            node_ref.attach(&mut self.inner.lifecycle);
+        }
+        self
+    }
 }
 impl<F: Future> Future for ElementFuture<F> {
     type Output = F::Output;
@@ -68,6 +103,7 @@ impl<F: Future> Future for ElementFuture<F> {
         let vnodes = this.inner.vnodes.get_or_insert_with(|| {
             let parent_vnode = vnk.with(Clone::clone);
             parent_vnode.add_child_node(this.inner.node.to_owned(), Default::default());
+            this.inner.lifecycle.mount(&this.inner.node);
             let parent_context = parent_vnode.get_context_map().clone();
             let my = Rc::new(
                 ConcreteNodeVNode::new(
@@ -83,6 +119,6 @@ impl<F: Future> Future for ElementFuture<F> {
                 parent: parent_vnode,
             }
         });
-        vnk.set(&vnodes.my, || this.future.poll(cx))
+        enter_vnode(&vnodes.my, || this.future.poll(cx))
     }
 }
@@ -6,7 +6,10 @@ use gtk::{
 };
 use observables::{ObservableAs, ObservableAsExt};
 
-use crate::widget::{WidgetOp, WrappedWidget};
+use crate::{
+    widget::{WidgetOp, WrappedWidget},
+    NodeRef,
+};
 
 use super::{
     dummy::{dummy_handler, is_dummy_handler},
@@ -67,6 +70,7 @@ pub struct TextInputProps<'c> {
     pub on_focus: &'c mut (dyn FnMut(TextInputEvent) + 'c),
     pub multiline: bool,
     pub placeholder: &'c (dyn ObservableAs<str> + 'c),
+    pub node_ref: Option<&'c NodeRef>,
 }
 impl<'c> Default for TextInputProps<'c> {
     fn default() -> Self {
@@ -78,6 +82,7 @@ impl<'c> Default for TextInputProps<'c> {
             on_focus: dummy_handler(),
             multiline: false,
             placeholder: &[""],
+            node_ref: None,
         }
     }
 }
@@ -91,6 +96,7 @@ pub async fn text_input<'c>(
         on_focus,
         multiline,
         placeholder,
+        node_ref,
     }: TextInputProps<'c>,
 ) {
     let manager = EventsManager::new();
@@ -169,5 +175,6 @@ pub async fn text_input<'c>(
             op: WidgetOp::NoChild,
         },
     )
+    .node_ref(node_ref)
     .await;
 }
@@ -1,6 +1,6 @@
 use crate::{
     widget::{gtk_box::GtkBoxOp, WidgetOp::MultiChild, WrappedWidget},
-    Fragment,
+    Fragment, NodeRef,
 };
 
 use super::ElementFuture;
@@ -11,6 +11,7 @@ pub struct ViewProps<'c> {
     pub children: Fragment<'c>,
     pub width: i32,
     pub height: i32,
+    pub node_ref: Option<&'c NodeRef>,
 }
 impl<'c> Default for ViewProps<'c> {
     fn default() -> Self {
@@ -18,6 +19,7 @@ impl<'c> Default for ViewProps<'c> {
             children: Default::default(),
             width: -1,
             height: -1,
+            node_ref: None,
         }
     }
 }
@@ -26,6 +28,7 @@ pub async fn view<'c>(
         children,
         width,
         height,
+        node_ref,
     }: ViewProps<'c>,
 ) {
     let b = gtk::Box::new(gtk::Orientation::Vertical, 0);
@@ -38,5 +41,6 @@ pub async fn view<'c>(
             op: MultiChild(&GtkBoxOp),
         },
     )
+    .node_ref(node_ref)
     .await;
 }
@@ -2,5 +2,10 @@ use std::future::Future;
 
 use glib::MainContext;
 pub(crate) fn set_executor_future<F: Future<Output = ()> + 'static>(future: F) {
-    MainContext::default().spawn_local(future);
+    let mut future = Box::pin(future);
+    MainContext::default().spawn_local(std::future::poll_fn(move |cx| {
+        let res = future.as_mut().poll(cx);
+        async_ui_core::lifecycle::notify_connected();
+        res
+    }));
 }
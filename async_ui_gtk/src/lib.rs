@@ -6,13 +6,19 @@ mod backend;
 pub mod components;
 mod executor;
 mod mount;
+pub mod timer;
 mod widget;
 pub use gtk;
 pub use mount::{mount, mount_at};
+pub use widget::WrappedWidget;
 
+pub use async_ui_core::context::ReactiveContext;
+pub use async_ui_core::error_boundary::{Failure, Retry};
+pub use async_ui_core::executor::{spawn_local, spawn_local_with_priority, Priority};
 pub use futures_lite;
 
 pub type Fragment<'c> = FragmentBase<'c, Backend>;
+pub type NodeRef = async_ui_core::node_ref::NodeRef<Backend>;
 
 pub mod __private_macro_only {
     pub use super::Fragment;
@@ -27,6 +33,20 @@ pub mod __private_macro_only {
     }
 }
 
+/** Render `children`, and `fallback` in their place if they return an error or panic.
+ *
+ * See [async_ui_core::error_boundary::error_boundary].
+ */
+pub async fn error_boundary<'c, T, E, C, F>(
+    children: &'c dyn Fn() -> C,
+    fallback: &'c dyn Fn(Failure<E>, Retry) -> F,
+) -> T
+where
+    C: IntoFuture<Output = Result<T, E>>,
+    F: IntoFuture<Output = ()>,
+{
+    async_ui_core::error_boundary::error_boundary::<Backend, _, _, _, _>(children, fallback).await
+}
 pub fn fragment<'c, T: Into<Fragment<'c>>>(tuple: T) -> Fragment<'c> {
     tuple.into()
 }
@@ -38,3 +58,110 @@ pub async fn with_context<I: IntoFuture, T: 'static>(future: I, value: Rc<T>) ->
 pub fn get_context<T: 'static>() -> Rc<T> {
     async_ui_core::vnode::node_context::get_context::<Backend, T>()
 }
+pub fn try_get_context<T: 'static>() -> Option<Rc<T>> {
+    async_ui_core::vnode::node_context::try_get_context::<Backend, T>()
+}
+pub fn use_context_or_default<T: Default + 'static>() -> Rc<T> {
+    async_ui_core::vnode::node_context::use_context_or_default::<Backend, T>()
+}
+pub fn try_get_reactive_context<T: 'static>() -> Option<Rc<ReactiveContext<T>>> {
+    async_ui_core::vnode::node_context::try_get_reactive_context::<Backend, T>()
+}
+
+pub type PortalTargets = async_ui_core::vnode::node_portal::PortalTargets<Backend>;
+/** Create a portal. Mount children with the entry; await the exit where they should show up.
+ */
+pub fn create_portal_pair() -> (
+    async_ui_core::vnode::node_portal::PortalEntry<Backend>,
+    async_ui_core::vnode::node_portal::PortalExit<Backend>,
+) {
+    async_ui_core::vnode::node_portal::create_portal_pair::<Backend>()
+}
+/** An exit for the named portal. Needs [PortalTargets] in the context.
+ */
+pub fn portal_target(name: &str) -> async_ui_core::vnode::node_portal::PortalExit<Backend> {
+    async_ui_core::vnode::node_portal::portal_target::<Backend>(name)
+}
+/** An entry for the named portal. Needs [PortalTargets] in the context.
+ */
+pub fn portal_entry(name: &str) -> async_ui_core::vnode::node_portal::PortalEntry<Backend> {
+    async_ui_core::vnode::node_portal::portal_entry::<Backend>(name)
+}
+/** Render `children()` while `condition` is true. See [async_ui_core::switch::show_when].
+ */
+pub async fn show_when<'c, C: IntoFuture + 'c>(
+    condition: &'c dyn observables::ObservableAs<bool>,
+    children: &'c dyn Fn() -> C,
+) {
+    async_ui_core::switch::show_when::<Backend, C>(condition, children).await
+}
+/** Render `render(value)` anew whenever the value changes. See [async_ui_core::switch::switch].
+ */
+pub async fn switch<'c, K: PartialEq + Clone + 'c, C: IntoFuture + 'c>(
+    discriminant: &'c dyn observables::ObservableAs<K>,
+    render: &'c dyn Fn(&K) -> C,
+) {
+    async_ui_core::switch::switch::<Backend, K, C>(discriminant, render).await
+}
+/** Like [switch], but keep inactive branches alive and detached.
+ */
+pub async fn switch_keep_alive<'c, K: PartialEq + Clone + 'c, C: IntoFuture + 'c>(
+    discriminant: &'c dyn observables::ObservableAs<K>,
+    render: &'c dyn Fn(&K) -> C,
+) {
+    async_ui_core::switch::switch_keep_alive::<Backend, K, C>(discriminant, render).await
+}
+pub type Transition = async_ui_core::vnode::node_transition::Transition<Backend>;
+/** Render `children`, animating their nodes in and out. See [async_ui_core::vnode::node_transition].
+ */
+pub async fn transition<F: IntoFuture>(children: F, transition: Rc<Transition>) -> F::Output {
+    use async_ui_core::vnode::node_transition::WithTransition;
+    WithTransition::<Backend, _>::new(children, transition).await
+}
+/** Render `fallback` until `children` have rendered something. See [async_ui_core::vnode::node_suspense::suspense].
+ */
+pub async fn suspense<F: IntoFuture, C: IntoFuture>(fallback: F, children: C) -> C::Output {
+    async_ui_core::vnode::node_suspense::suspense::<Backend, _, _>(fallback, children).await
+}
+/** Like [suspense], but show the fallback at least until `min_display` completes once it has been shown.
+ */
+pub async fn suspense_with_min_display<F: IntoFuture, C: IntoFuture, D: IntoFuture<Output = ()>>(
+    fallback: F,
+    children: C,
+    min_display: D,
+) -> C::Output {
+    async_ui_core::vnode::node_suspense::suspense_with_min_display::<Backend, _, _, _>(
+        fallback,
+        children,
+        min_display,
+    )
+    .await
+}
+/** Tell the enclosing [suspense] that its children are ready even without nodes.
+ */
+pub fn mark_ready() {
+    async_ui_core::vnode::node_suspense::mark_ready::<Backend>()
+}
+/** Wait until `widget` is in a window, e.g. to measure or focus it.
+ */
+pub fn mounted(widget: &gtk::Widget) -> async_ui_core::lifecycle::Mounted<Backend> {
+    use glib::Cast;
+    async_ui_core::lifecycle::mounted::<Backend>(widget::WrappedWidget {
+        widget: widget.clone(),
+        inner_widget: widget.clone().upcast(),
+        op: widget::WidgetOp::NoChild,
+    })
+}
+
+/** Start recording the vnode tree for [inspect]. Call before mounting.
+ */
+#[cfg(feature = "inspect")]
+pub fn enable_inspector() {
+    async_ui_core::inspect::enable_inspector::<Backend>()
+}
+/** Snapshot the vnode tree. See [async_ui_core::inspect].
+ */
+#[cfg(feature = "inspect")]
+pub fn inspect() -> Vec<async_ui_core::inspect::VNodeInfo> {
+    async_ui_core::inspect::inspect::<Backend>()
+}
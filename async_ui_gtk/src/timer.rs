@@ -0,0 +1,24 @@
+/*! Waiting with GLib timeouts.
+ */
+use std::{future::Future, pin::Pin, time::Duration};
+
+use observables::Timer;
+
+/** Complete after `duration`.
+ */
+pub async fn sleep(duration: Duration) {
+    glib::timeout_future(duration).await
+}
+
+/** The [Timer] for [debounce][observables::ObservableAsExt::debounce] and friends, using [glib::timeout_future].
+ */
+#[derive(Clone, Copy, Default)]
+pub struct PlatformTimer;
+
+impl Timer for PlatformTimer {
+    type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    fn sleep(&self, duration: Duration) -> Self::Sleep {
+        glib::timeout_future(duration)
+    }
+}
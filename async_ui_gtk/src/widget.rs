@@ -19,7 +19,18 @@ pub struct WrappedWidget {
     pub(crate) op: WidgetOp,
 }
 
+impl std::fmt::Debug for WrappedWidget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("WrappedWidget").field(&self.widget).finish()
+    }
+}
+
 impl WrappedWidget {
+    /** The GTK widget itself.
+     */
+    pub fn widget(&self) -> &Widget {
+        &self.widget
+    }
     pub fn add_child_node(&mut self, child: &mut Self, insert_before_sibling: Option<&Self>) {
         let this = &self.inner_widget;
         match self.op {
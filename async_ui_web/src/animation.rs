@@ -1,13 +1,14 @@
-/*! For scheduling Rust-driven animations
+/*! For scheduling Rust-driven animations, and for animating elements in and out.
  *
- * This is not related to CSS animations.
+ * [Animator] is not related to CSS animations. [css_transition] is.
  */
-use std::rc::Rc;
+use std::{rc::Rc, time::Duration};
 
 use observables::{cell::ReactiveCell, ObservableAsExt};
 use wasm_bindgen::{prelude::Closure, JsCast};
+use web_sys::{Element, Event, EventTarget};
 
-use crate::WINDOW;
+use crate::{Transition, WINDOW};
 
 /** A "clock" for scheduling animation at the browser's preferred frame rate.
  *
@@ -43,3 +44,80 @@ impl Animator {
         return self.cell.as_observable().get();
     }
 }
+
+/** A [Transition] driven by CSS, for [transition][crate::transition].
+ *
+ * An entering element gets the `{name}-enter` class for its first frame, so that it can transition from there.
+ * A leaving element gets the `{name}-exit` class,
+ * and is removed on its `transitionend` or `animationend`, or after `max_duration`, whichever comes first.
+ *
+ * ```css
+ * .fade-enter, .fade-exit { opacity: 0; }
+ * .item { transition: opacity 0.2s; }
+ * ```
+ */
+pub fn css_transition(name: &str, max_duration: Duration) -> Rc<Transition> {
+    let enter_class = format!("{name}-enter");
+    let exit_class = format!("{name}-exit");
+    Rc::new(Transition {
+        enter: Box::new(move |node| {
+            let element = match node.dyn_ref::<Element>() {
+                Some(element) => element.clone(),
+                None => return Box::pin(async {}),
+            };
+            let class = enter_class.clone();
+            element.class_list().add_1(&class).ok();
+            Box::pin(async move {
+                // Only the second frame is sure to have been styled with the class.
+                let animator = Animator::new();
+                animator.next_frame().await;
+                animator.next_frame().await;
+                element.class_list().remove_1(&class).ok();
+            })
+        }),
+        exit: Box::new(move |node| {
+            let element = match node.dyn_ref::<Element>() {
+                Some(element) => element.clone(),
+                None => return Box::pin(async {}),
+            };
+            element.class_list().add_1(&exit_class).ok();
+            Box::pin(async move { animation_end(&element, max_duration).await })
+        }),
+    })
+}
+
+/** Wait for a transition or animation on `element` itself to end, but no longer than `max_duration`.
+ */
+async fn animation_end(element: &Element, max_duration: Duration) {
+    let ended = Rc::new(ReactiveCell::new(false));
+    let (ended_1, ended_2) = (ended.clone(), ended.clone());
+    let target: EventTarget = element.clone().into();
+    let on_end = Closure::<dyn Fn(Event)>::new(move |ev: Event| {
+        // Ignore the ones of descendants.
+        if ev.target().as_ref() == Some(&target) {
+            *ended_1.borrow_mut() = true;
+        }
+    });
+    let on_timeout = Closure::<dyn Fn()>::new(move || *ended_2.borrow_mut() = true);
+    let timeout = WINDOW.with(|win| {
+        win.set_timeout_with_callback_and_timeout_and_arguments_0(
+            on_timeout.as_ref().unchecked_ref(),
+            max_duration.as_millis() as i32,
+        )
+        .expect("set timeout failed")
+    });
+    for event in ["transitionend", "animationend"] {
+        element
+            .add_event_listener_with_callback(event, on_end.as_ref().unchecked_ref())
+            .expect("add listener failed");
+    }
+    let _guard = scopeguard::guard((), |_| {
+        for event in ["transitionend", "animationend"] {
+            element
+                .remove_event_listener_with_callback(event, on_end.as_ref().unchecked_ref())
+                .ok();
+        }
+        WINDOW.with(|win| win.clear_timeout_with_handle(timeout));
+    });
+    ended.as_observable().until_change().await;
+}
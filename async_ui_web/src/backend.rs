@@ -8,7 +8,7 @@ use std::{future::Future, rc::Rc};
 use wasm_bindgen::UnwrapThrowExt;
 use web_sys::Node;
 
-use crate::executor::{schedule, set_executor_future};
+use crate::executor::{out_of_budget, request_idle, schedule, set_executor_future};
 
 pub struct Backend;
 impl BackendTrait for Backend {
@@ -27,10 +27,19 @@ impl BackendTrait for Backend {
     fn del_child_node(parent: &mut Self::Node, child: &mut Self::Node) {
         parent.remove_child(child).expect_throw("remove failed");
     }
+    fn is_connected(node: &Self::Node) -> bool {
+        node.is_connected()
+    }
     fn drive_executor<F: Future<Output = ()> + 'static>(fut: F) {
         set_executor_future(Box::new(fut) as _);
         schedule();
     }
+    fn should_yield() -> bool {
+        out_of_budget()
+    }
+    fn request_idle() -> bool {
+        request_idle()
+    }
     fn initialize() {}
 
     fn get_vnode_key() -> &'static ScopedKey<Rc<VNode<Self>>> {
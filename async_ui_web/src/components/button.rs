@@ -1,20 +1,21 @@
 use futures_lite::FutureExt;
 use smallvec::SmallVec;
 use wasm_bindgen::JsCast;
-use web_sys::{HtmlButtonElement, MouseEvent};
+use web_sys::{HtmlButtonElement, MouseEvent, Node};
 
-use crate::{utils::class_list::ClassList, window::DOCUMENT, Fragment};
+use crate::{utils::class_list::ClassList, Fragment, NodeRef};
 
 use super::{
     dummy::{dummy_handler, is_dummy_handler},
-    events::{create_handler, EventsManager, QueuedEvent},
-    ElementFuture,
+    events::{create_handler, handle_events, EventsManager, QueuedEvent},
+    markup, ElementFuture,
 };
 
 pub struct ButtonProps<'c> {
     pub children: Fragment<'c>,
     pub on_press: &'c mut dyn FnMut(PressEvent),
     pub class: Option<&'c ClassList<'c>>,
+    pub node_ref: Option<&'c NodeRef>,
 }
 impl<'c> Default for ButtonProps<'c> {
     fn default() -> Self {
@@ -22,6 +23,7 @@ impl<'c> Default for ButtonProps<'c> {
             children: Default::default(),
             on_press: dummy_handler(),
             class: None,
+            node_ref: None,
         }
     }
 }
@@ -38,39 +40,26 @@ pub async fn button<'c>(
         children,
         on_press,
         class,
+        node_ref,
     }: ButtonProps<'c>,
 ) {
-    let button = DOCUMENT.with(|doc| {
-        let elem = doc.create_element("button").expect("create element failed");
-        let elem: HtmlButtonElement = elem.unchecked_into();
-        elem
-    });
+    let button: HtmlButtonElement = markup::element::<Node>("button", class).unchecked_into();
 
     let mut handlers = SmallVec::<[_; 1]>::new();
     let manager = EventsManager::new();
 
     if !is_dummy_handler(on_press) {
-        let h = create_handler(&manager, |e| QueuedEvent::Click(e));
+        let h = create_handler(&manager, QueuedEvent::Click);
         button.set_onclick(Some(h.get_function()));
         handlers.push(h);
     }
-    if let Some(class) = class {
-        class.set_dom(button.class_list());
-    }
 
-    let future = children.or(async {
-        manager.grab_waker().await;
-        loop {
-            let mut events = manager.get_queue().await;
-            for event in events.drain(..) {
-                match event {
-                    QueuedEvent::Click(native_event) => {
-                        on_press(PressEvent { native_event });
-                    }
-                    _ => {}
-                }
-            }
+    let future = children.or(handle_events(&manager, |event| {
+        if let QueuedEvent::Click(native_event) = event {
+            on_press(PressEvent { native_event });
         }
-    });
-    ElementFuture::new(future, button.into()).await
+    }));
+    ElementFuture::new(future, button.into())
+        .node_ref(node_ref)
+        .await
 }
@@ -1,15 +1,15 @@
 use futures_lite::FutureExt;
-use observables::{ObservableAs, ObservableAsExt};
+use observables::ObservableAs;
 use smallvec::SmallVec;
 use wasm_bindgen::JsCast;
-use web_sys::{Event, HtmlInputElement};
+use web_sys::{Event, HtmlInputElement, Node};
 
-use crate::{utils::class_list::ClassList, window::DOCUMENT};
+use crate::{utils::class_list::ClassList, NodeRef};
 
 use super::{
     dummy::{dummy_handler, is_dummy_handler},
-    events::{create_handler, EventsManager, QueuedEvent},
-    ElementFuture,
+    events::{create_handler, handle_events, EventsManager, QueuedEvent},
+    markup, ElementFuture,
 };
 
 pub struct CheckboxChangeEvent {
@@ -25,6 +25,7 @@ pub struct CheckboxProps<'c> {
     pub value: &'c dyn ObservableAs<bool>,
     pub on_change: &'c mut dyn FnMut(CheckboxChangeEvent),
     pub class: Option<&'c ClassList<'c>>,
+    pub node_ref: Option<&'c NodeRef>,
 }
 impl<'c> Default for CheckboxProps<'c> {
     fn default() -> Self {
@@ -32,6 +33,7 @@ impl<'c> Default for CheckboxProps<'c> {
             value: &[false],
             on_change: dummy_handler(),
             class: None,
+            node_ref: None,
         }
     }
 }
@@ -44,13 +46,10 @@ pub async fn checkbox<'c>(
         value,
         on_change,
         class,
+        node_ref,
     }: CheckboxProps<'c>,
 ) {
-    let elem: HtmlInputElement = DOCUMENT.with(|doc| {
-        let elem = doc.create_element("input").expect("create element failed");
-        elem.unchecked_into()
-    });
-    elem.set_type("checkbox");
+    let elem: HtmlInputElement = markup::checkbox::<Node>(class).unchecked_into();
     let mut handlers = SmallVec::<[_; 1]>::new();
     let manager = EventsManager::new();
     if !is_dummy_handler(on_change) {
@@ -58,27 +57,17 @@ pub async fn checkbox<'c>(
         elem.set_onchange(Some(h.get_function()));
         handlers.push(h);
     }
-    if let Some(cl) = class {
-        cl.set_dom(elem.class_list());
-    }
     let elem_1 = elem.clone();
-    let elem_2 = elem.clone();
-    let future = (async {
-        loop {
-            let mut events = manager.get_queue().await;
-            for event in events.drain(..) {
-                let checkbox_change_event = CheckboxChangeEvent {
-                    node: elem_1.clone(),
-                };
-                match event {
-                    QueuedEvent::Change() => {
-                        on_change(checkbox_change_event);
-                    }
-                    _ => {}
-                }
-            }
+    let elem_2: Node = elem.clone().into();
+    let future = handle_events(&manager, |event| {
+        if let QueuedEvent::Change() = event {
+            on_change(CheckboxChangeEvent {
+                node: elem_1.clone(),
+            });
         }
     })
-    .or(value.for_each(|v| elem_2.set_checked(*v)));
-    ElementFuture::new(future, elem.into()).await;
+    .or(markup::checkbox_value(&elem_2, value));
+    ElementFuture::new(future, elem.into())
+        .node_ref(node_ref)
+        .await;
 }
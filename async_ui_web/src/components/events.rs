@@ -7,9 +7,12 @@ use std::{
     task::{Poll, Waker},
 };
 
+use async_ui_core::executor::{run_with_priority, Priority};
 use js_sys::Function;
 use web_sys::{Event, FocusEvent, InputEvent, KeyboardEvent, MouseEvent};
 
+use crate::backend::Backend;
+
 pub(super) enum QueuedEvent {
     Click(MouseEvent),
     // MouseDown(MouseEvent),
@@ -83,7 +86,7 @@ impl EventsManager {
 use wasm_bindgen::prelude::Closure;
 use wasm_bindgen::JsCast;
 
-use crate::executor::run_now;
+use crate::executor::handle_input;
 
 pub(super) struct EventHandler<'h> {
     closure: Closure<dyn Fn(Event) + 'h>,
@@ -95,8 +98,7 @@ impl<'h> EventHandler<'h> {
     ) -> Self {
         let closure = Closure::new(move |event: Event| {
             let event: E = event.unchecked_into();
-            execute(event);
-            run_now();
+            handle_input(|| execute(event));
         });
         Self { closure }
     }
@@ -105,6 +107,22 @@ impl<'h> EventHandler<'h> {
     }
 }
 
+/** Call `handle` with each event queued in `manager`. Never completes.
+ *
+ * Events are handled in a task of their own, in the [UserInput][Priority::UserInput] lane,
+ * so that reacting to them doesn't wait behind other work, such as rendering a long list.
+ */
+pub(super) async fn handle_events(manager: &EventsManager, mut handle: impl FnMut(QueuedEvent)) {
+    run_with_priority::<Backend, _>(Priority::UserInput, async {
+        manager.grab_waker().await;
+        loop {
+            let mut events = manager.get_queue().await;
+            events.drain(..).for_each(&mut handle);
+        }
+    })
+    .await
+}
+
 pub(super) fn create_handler<
     'h,
     E: wasm_bindgen::convert::FromWasmAbi + JsCast + 'static,
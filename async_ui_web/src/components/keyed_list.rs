@@ -0,0 +1,74 @@
+use std::{future::IntoFuture, hash::Hash, marker::PhantomData};
+
+use async_ui_core::list::{keyed_items, ListModel};
+use observables::ObservableAs;
+use web_sys::Node;
+
+use crate::{
+    backend::Backend, hydration::create_element, utils::class_list::ClassList, window::DOCUMENT,
+};
+
+use super::{
+    list::{DummyObservableAs, DUMMY_USED},
+    ElementFuture,
+};
+
+pub struct KeyedListProps<'c, T: Clone, K, F: IntoFuture> {
+    pub data: &'c dyn ObservableAs<ListModel<T>>,
+    pub render: &'c dyn Fn(T) -> F,
+    /// Items with the same key are considered the same item. Keys should be unique.
+    pub key: &'c dyn Fn(&T) -> K,
+    pub class: Option<&'c ClassList<'c>>,
+}
+impl<'c, T: Clone + 'c, K, F: IntoFuture> Default for KeyedListProps<'c, T, K, F> {
+    fn default() -> Self {
+        Self {
+            data: &DummyObservableAs(PhantomData),
+            render: &|_: T| panic!("{}", DUMMY_USED),
+            key: &|_: &T| panic!("{}", DUMMY_USED),
+            class: None,
+        }
+    }
+}
+
+/** List where items are identified by key.
+ *
+ * Whenever the model changes, the keys of the new items are compared with the keys of the old ones.
+ * Items whose keys are still there keep running (and keep their state);
+ * they are moved into their new positions, with as few moves as possible.
+ * So, unlike [list][super::list], this works well even if the whole model gets replaced.
+ *
+ * ```rust
+ * # use async_ui_web::components::{keyed_list, text, KeyedListProps, ListModel};
+ * # use observables::cell::ReactiveCell;
+ * # #[derive(Clone)]
+ * # struct Story { id: u64, title: String }
+ * # async fn story_item(story: Story) { text(&[&*story.title]).await }
+ * # async fn stories_list(stories: ReactiveCell<ListModel<Story>>) {
+ * keyed_list(KeyedListProps {
+ *     data: &stories.as_observable(),
+ *     render: &|story: Story| story_item(story),
+ *     key: &|story: &Story| story.id,
+ *     ..Default::default()
+ * }).await;
+ * # }
+ * ```
+ */
+pub async fn keyed_list<'c, T: Clone + 'c, K: Hash + Eq + 'c, F: IntoFuture + 'c>(
+    KeyedListProps {
+        data,
+        render,
+        key,
+        class,
+    }: KeyedListProps<'c, T, K, F>,
+) {
+    let container_node = create_element("div");
+    if let Some(class) = class {
+        class.set_dom(container_node.class_list());
+    }
+    let container_node: Node = container_node.into();
+    let new_anchor = || -> Node { DOCUMENT.with(|doc| doc.create_comment("")).into() };
+    let inside =
+        keyed_items::<Backend, _, _, _>(container_node.clone(), &new_anchor, data, render, key);
+    ElementFuture::new(inside, container_node).await
+}
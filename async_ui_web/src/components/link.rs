@@ -1,16 +1,16 @@
 use futures_lite::FutureExt;
-use observables::{ObservableAs, ObservableAsExt};
+use observables::ObservableAs;
 use smallvec::SmallVec;
 use wasm_bindgen::JsCast;
-use web_sys::HtmlAnchorElement;
+use web_sys::{HtmlAnchorElement, Node};
 
-use crate::{utils::class_list::ClassList, window::DOCUMENT, Fragment};
+use crate::{utils::class_list::ClassList, Fragment, NodeRef};
 
 use super::{
     button::PressEvent,
     dummy::{dummy_handler, is_dummy_handler},
-    events::{create_handler, EventsManager, QueuedEvent},
-    ElementFuture,
+    events::{create_handler, handle_events, EventsManager, QueuedEvent},
+    markup, ElementFuture,
 };
 
 pub struct LinkProps<'c> {
@@ -18,6 +18,7 @@ pub struct LinkProps<'c> {
     pub href: &'c dyn ObservableAs<str>,
     pub on_press: &'c mut dyn FnMut(PressEvent),
     pub class: Option<&'c ClassList<'c>>,
+    pub node_ref: Option<&'c NodeRef>,
 }
 impl<'c> Default for LinkProps<'c> {
     fn default() -> Self {
@@ -26,6 +27,7 @@ impl<'c> Default for LinkProps<'c> {
             href: &["#"],
             on_press: dummy_handler(),
             class: None,
+            node_ref: None,
         }
     }
 }
@@ -39,50 +41,29 @@ pub async fn link<'c>(
         on_press,
         class,
         children,
+        node_ref,
     }: LinkProps<'c>,
 ) {
-    let anchor = DOCUMENT.with(|doc| {
-        let elem = doc.create_element("a").expect("create element failed");
-        let elem: HtmlAnchorElement = elem.unchecked_into();
-        elem
-    });
+    let anchor: HtmlAnchorElement = markup::element::<Node>("a", class).unchecked_into();
 
     let mut handlers = SmallVec::<[_; 1]>::new();
     let manager = EventsManager::new();
 
     if !is_dummy_handler(on_press) {
-        let h = create_handler(&manager, |e| QueuedEvent::Click(e));
+        let h = create_handler(&manager, QueuedEvent::Click);
         anchor.set_onclick(Some(h.get_function()));
         handlers.push(h);
     }
-    if let Some(class) = class {
-        class.set_dom(anchor.class_list());
-    }
-    let anchor_copy = anchor.clone();
+    let anchor_copy: Node = anchor.clone().into();
 
     let future = (children)
-        .or(async {
-            manager.grab_waker().await;
-            loop {
-                let mut events = manager.get_queue().await;
-                for event in events.drain(..) {
-                    match event {
-                        QueuedEvent::Click(native_event) => {
-                            on_press(PressEvent { native_event });
-                        }
-                        _ => {}
-                    }
-                }
-            }
-        })
-        .or(href.for_each(|href| {
-            if !href.is_empty() {
-                anchor_copy.set_href(href);
-            } else {
-                anchor_copy
-                    .remove_attribute("href")
-                    .expect("anchor remove attribute failed");
+        .or(handle_events(&manager, |event| {
+            if let QueuedEvent::Click(native_event) = event {
+                on_press(PressEvent { native_event });
             }
-        }));
-    ElementFuture::new(future, anchor.into()).await
+        }))
+        .or(markup::link_href(&anchor_copy, href));
+    ElementFuture::new(future, anchor.into())
+        .node_ref(node_ref)
+        .await
 }
@@ -1,32 +1,62 @@
-use std::{future::IntoFuture, marker::PhantomData, rc::Rc};
+use std::{collections::VecDeque, future::IntoFuture, marker::PhantomData, rc::Rc};
 
-use async_task::Task;
 pub use async_ui_core::list::ListModel;
 use async_ui_core::{
     backend::BackendTrait,
-    executor::spawn_local,
-    list::{Change, ListModelPrivateAPIs},
-    vnode::{
-        node_concrete::{ConcreteNodeVNode, RefNode},
-        VNodeTrait, WithVNode,
-    },
+    list::items,
+    vnode::{node_concrete::RefNode, Claim, VNode, VNodeTrait},
 };
-use futures_lite::pin;
-use im_rc::Vector;
 use observables::{Listenable, ObservableAs, ObservableAsExt};
-use scoped_async_spawn::SpawnGuard;
-use slab::Slab;
 use web_sys::Node;
 
-use crate::{backend::Backend, utils::class_list::ClassList, window::DOCUMENT};
+use crate::{backend::Backend, hydration, utils::class_list::ClassList, window::DOCUMENT};
 
-use super::ElementFuture;
+use super::{markup, ElementFuture};
 
-fn insert_after(parent: &Node, child: &Node, after: Option<&Node>) {
-    let before = after.map_or_else(|| parent.first_child(), |after| after.next_sibling());
-    parent
-        .insert_before(child, before.as_ref())
-        .expect("insert failed");
+/** Take the server-rendered nodes of one item: everything up to and including its anchor comment.
+ */
+fn take_existing_item(existing: &mut VecDeque<Node>) -> Option<(Node, VecDeque<Node>)> {
+    let anchor_index = existing.iter().position(|node| {
+        node.node_type() == Node::COMMENT_NODE && node.node_value().as_deref() == Some("")
+    })?;
+    let mut content: VecDeque<Node> = existing.drain(..=anchor_index).collect();
+    let anchor = content.pop_back()?;
+    Some((anchor, content))
+}
+
+/** Claim the server-rendered items in `container`, whose vnode is the current one,
+ * for a model of `len` items.
+ *
+ * Returns each item's anchor with a vnode over its nodes. Items beyond `len` are left for the list to remove.
+ */
+fn adopt_existing_items(container: &Node, len: usize) -> Vec<(Node, Rc<VNode<Backend>>)> {
+    let container_vnode = Backend::get_vnode_key().with(Clone::clone);
+    let mut existing = VecDeque::new();
+    while let Some(node) = container_vnode.claim_child_node(Default::default(), &|_| Claim::Take) {
+        existing.push_back(node);
+    }
+    let mut adopted = Vec::new();
+    while let Some((anchor, item_nodes)) = take_existing_item(&mut existing) {
+        let vnode = hydration::new_vnode(
+            RefNode::<Backend>::Sibling {
+                parent: container.clone(),
+                sibling: anchor.clone(),
+            },
+            container_vnode.get_context_map().clone(),
+            item_nodes,
+        );
+        adopted.push((anchor, vnode));
+    }
+    if adopted.len() < len {
+        hydration::warn_mismatch("a list item", "the end of the list");
+    }
+    if adopted.len() > len || !existing.is_empty() {
+        hydration::warn_mismatch("the end of the list", "more list items");
+        for node in existing.drain(..) {
+            container.remove_child(&node).ok();
+        }
+    }
+    adopted
 }
 
 pub struct ListProps<'c, T: Clone, F: IntoFuture> {
@@ -34,8 +64,8 @@ pub struct ListProps<'c, T: Clone, F: IntoFuture> {
     pub render: &'c dyn Fn(T) -> F,
     pub class: Option<&'c ClassList<'c>>,
 }
-struct DummyObservableAs<T>(PhantomData<T>);
-const DUMMY_USED: &str = "dummy prop used";
+pub(crate) struct DummyObservableAs<T>(pub(crate) PhantomData<T>);
+pub(crate) const DUMMY_USED: &str = "dummy prop used";
 impl<T: Clone> Listenable for DummyObservableAs<T> {
     fn add_waker(&self, _waker: std::task::Waker) {
         panic!("{}", DUMMY_USED)
@@ -66,123 +96,17 @@ pub async fn list<'c, T: Clone + 'c, F: IntoFuture + 'c>(
         class,
     }: ListProps<'c, T, F>,
 ) {
-    let container_node =
-        DOCUMENT.with(|doc| doc.create_element("div").expect("create element failed"));
-
-    class.map(|c| c.set_dom(container_node.class_list()));
-
-    let container_node: Node = container_node.clone().into();
+    let container_node: Node = markup::element("div", class);
+    let claimed = hydration::is_hydrating() && container_node.parent_node().is_some();
     let container_node_copy = container_node.clone();
+    let new_anchor = || -> Node { DOCUMENT.with(|doc| doc.create_comment("")).into() };
     let inside = async move {
-        let parent_vnode = Backend::get_vnode_key().with(Clone::clone);
-
-        let parent_context = parent_vnode.get_context_map();
-        let mut tasks: Slab<Task<()>> = Slab::new();
-        let guard = SpawnGuard::new();
-        pin!(guard);
-        let mut nodes: Vector<(Node, usize)> = Vector::new();
-        let mut create_item_task = |fut: F::IntoFuture, after_this: Option<&Node>| {
-            let reference_node: Node = DOCUMENT.with(|doc| doc.create_comment("")).into();
-            insert_after(&container_node, &reference_node, after_this);
-            let fut = {
-                WithVNode::new(
-                    fut,
-                    Rc::new(
-                        ConcreteNodeVNode::new(
-                            RefNode::<Backend>::Sibling {
-                                parent: container_node.clone(),
-                                sibling: reference_node.clone(),
-                            },
-                            parent_context.clone(),
-                        )
-                        .into(),
-                    ),
-                )
-            };
-            let fut = guard.as_mut().convert_future(async {
-                fut.await;
-            });
-            let task = spawn_local(fut);
-            (reference_node, task)
+        // Server-rendered items to adopt: each is its nodes followed by its anchor comment.
+        let adopted = match claimed {
+            true => adopt_existing_items(&container_node, data.visit(|model| model.len())),
+            false => Vec::new(),
         };
-        let mut last_version = data.visit(|model| {
-            let start = model.underlying_vector();
-            let mut last_node = None;
-            for item in start.iter() {
-                let fut = render(item.to_owned()).into_future();
-                let (node, task) = create_item_task(fut, last_node.as_ref());
-                last_node = Some(node.to_owned());
-                let task_id = tasks.insert(task);
-                nodes.push_back((node, task_id));
-            }
-            let model = ListModelPrivateAPIs(model);
-            model
-                .total_listeners()
-                .set(model.total_listeners().get() + 1);
-            model.get_version()
-        });
-        let _guard = scopeguard::guard((), |_| {
-            data.visit(|model| {
-                let model = ListModelPrivateAPIs(&*model);
-                model
-                    .total_listeners()
-                    .set(model.total_listeners().get() - 1);
-            });
-        });
-        let mut first = true;
-        data.for_each(|model| {
-            if first {
-                first = false;
-                return;
-            }
-            let model_priv = ListModelPrivateAPIs(model);
-            let changes = model_priv.changes_since_version(last_version);
-            for change in changes {
-                match change {
-                    Change::Splice {
-                        remove_range,
-                        replace_with,
-                    } => {
-                        let n_items = ExactSizeIterator::len(remove_range);
-                        let mut right = nodes.split_off(remove_range.start);
-                        let new_right = right.split_off(n_items);
-                        for (node, task_id) in right.into_iter() {
-                            std::mem::drop(tasks.remove(task_id));
-                            container_node.remove_child(&node).ok();
-                        }
-                        let insert_after: Option<Node> =
-                            nodes.back().map(|(node, _)| node).cloned();
-                        nodes.extend(replace_with.iter().map(|t| {
-                            let fut = render(t.to_owned()).into_future();
-                            let (node, task) = create_item_task(fut, insert_after.as_ref());
-                            let task_id = tasks.insert(task);
-                            (node, task_id)
-                        }));
-                        nodes.append(new_right);
-                    }
-                    Change::Remove { index } => {
-                        let (node, task_id) = nodes.remove(*index);
-                        std::mem::drop(tasks.remove(task_id));
-                        container_node.remove_child(&node).ok();
-                    }
-                    Change::Insert { index, value } => {
-                        let fut = render(value.to_owned()).into_future();
-                        let (node, task) = create_item_task(fut, {
-                            (*index > 0)
-                                .then(|| nodes.get(index - 1).map(|(node, _task_id)| node))
-                                .flatten()
-                        });
-                        let task_id = tasks.insert(task);
-                        nodes.insert(*index, (node, task_id));
-                    }
-                }
-            }
-            last_version = model_priv.get_version();
-            model_priv
-                .pending_listeners()
-                .set(model_priv.pending_listeners().get() - 1);
-        })
-        .await;
+        items::<Backend, _, _>(container_node, &new_anchor, adopted, data, render).await
     };
     ElementFuture::new(inside, container_node_copy).await
 }
@@ -0,0 +1,140 @@
+/*! The markup of the built-in components, shared with [server-side rendering][crate::ssr].
+ *
+ * The components create and update their nodes through the functions here, which are generic over [MarkupNode].
+ * The client runs them on DOM nodes and the `ssr` components on `SsrNode`s,
+ * so the server sends exactly the HTML that the client expects to hydrate.
+ */
+use futures_lite::FutureExt;
+use observables::{ObservableAs, ObservableAsExt};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlInputElement, HtmlTextAreaElement, Node};
+
+use crate::{hydration, utils::class_list::ClassList};
+
+pub(crate) trait MarkupNode: Clone {
+    /// Create an element, or claim an existing one when hydrating.
+    fn create_element(tag: &str) -> Self;
+    /// Create an empty text node, or claim an existing one when hydrating.
+    fn create_text() -> Self;
+    fn set_text(&self, text: &str);
+    /** The first text set on a node from [create_text][MarkupNode::create_text].
+     * On the client, a node claimed from the server's HTML is checked against it.
+     */
+    fn set_initial_text(&self, text: &str) {
+        self.set_text(text);
+    }
+    fn set_attribute(&self, name: &str, value: &str);
+    fn remove_attribute(&self, name: &str);
+    fn set_class_list(&self, class: &ClassList<'_>);
+    /** Whether a checkbox is checked.
+     * The DOM has a property for this; the server writes the `checked` attribute instead.
+     */
+    fn set_checked(&self, checked: bool);
+    /** The text in an `<input>` or `<textarea>`.
+     * The DOM has a property for this; the server writes the `value` attribute
+     * (or the content of the `<textarea>`) instead.
+     */
+    fn set_value(&self, value: &str);
+}
+
+impl MarkupNode for Node {
+    fn create_element(tag: &str) -> Self {
+        hydration::create_element(tag).into()
+    }
+    fn create_text() -> Self {
+        hydration::create_text_node().into()
+    }
+    fn set_text(&self, text: &str) {
+        self.set_node_value(Some(text));
+    }
+    fn set_initial_text(&self, text: &str) {
+        hydration::check_text(self, text);
+        self.set_text(text);
+    }
+    fn set_attribute(&self, name: &str, value: &str) {
+        self.unchecked_ref::<Element>()
+            .set_attribute(name, value)
+            .expect("set attribute failed");
+    }
+    fn remove_attribute(&self, name: &str) {
+        self.unchecked_ref::<Element>()
+            .remove_attribute(name)
+            .expect("remove attribute failed");
+    }
+    fn set_class_list(&self, class: &ClassList<'_>) {
+        class.set_dom(self.unchecked_ref::<Element>().class_list());
+    }
+    fn set_checked(&self, checked: bool) {
+        self.unchecked_ref::<HtmlInputElement>()
+            .set_checked(checked);
+    }
+    fn set_value(&self, value: &str) {
+        match self.dyn_ref::<HtmlTextAreaElement>() {
+            Some(textarea) => textarea.set_inner_text(value),
+            None => self.unchecked_ref::<HtmlInputElement>().set_value(value),
+        }
+    }
+}
+
+/** An element with the classes in `class`.
+ */
+pub(crate) fn element<N: MarkupNode>(tag: &str, class: Option<&ClassList<'_>>) -> N {
+    let node = N::create_element(tag);
+    if let Some(class) = class {
+        node.set_class_list(class);
+    }
+    node
+}
+
+/** Keep the content of the text node up to date.
+ */
+pub(crate) async fn text<N: MarkupNode>(node: &N, text: &dyn ObservableAs<str>) {
+    let mut set: fn(&N, &str) = N::set_initial_text;
+    text.for_each(|t| {
+        set(node, t);
+        set = N::set_text;
+    })
+    .await
+}
+
+/** Keep the `href` of the link up to date. An empty `href` removes the attribute.
+ */
+pub(crate) async fn link_href<N: MarkupNode>(anchor: &N, href: &dyn ObservableAs<str>) {
+    href.for_each(|href| match href.is_empty() {
+        false => anchor.set_attribute("href", href),
+        true => anchor.remove_attribute("href"),
+    })
+    .await
+}
+
+pub(crate) fn checkbox<N: MarkupNode>(class: Option<&ClassList<'_>>) -> N {
+    let elem: N = element("input", class);
+    elem.set_attribute("type", "checkbox");
+    elem
+}
+/** Keep the checkbox checked or not.
+ */
+pub(crate) async fn checkbox_value<N: MarkupNode>(elem: &N, value: &dyn ObservableAs<bool>) {
+    value.for_each(|v| elem.set_checked(*v)).await
+}
+
+pub(crate) fn text_input<N: MarkupNode>(multiline: bool, class: Option<&ClassList<'_>>) -> N {
+    element(
+        match multiline {
+            true => "textarea",
+            false => "input",
+        },
+        class,
+    )
+}
+/** Keep the text and the placeholder of the text input up to date.
+ */
+pub(crate) async fn text_input_value<N: MarkupNode>(
+    input: &N,
+    text: &dyn ObservableAs<str>,
+    placeholder: &dyn ObservableAs<str>,
+) {
+    text.for_each(|t| input.set_value(t))
+        .or(placeholder.for_each(|t| input.set_attribute("placeholder", t)))
+        .await
+}
@@ -75,7 +75,10 @@ use std::{
 
 use async_ui_core::{
     backend::BackendTrait,
+    lifecycle::Lifecycle,
+    node_ref::NodeRef,
     vnode::{
+        enter_vnode,
         node_concrete::{ConcreteNodeVNode, RefNode},
         VNode, VNodeTrait,
     },
@@ -85,9 +88,11 @@ use web_sys::Node;
 
 mod dummy;
 mod events;
+pub(crate) mod markup;
 
 mod button;
 mod checkbox;
+mod keyed_list;
 mod link;
 mod list;
 mod radio;
@@ -95,17 +100,22 @@ mod slider;
 mod text;
 mod text_input;
 mod view;
-pub use button::{button, ButtonProps};
-pub use checkbox::{checkbox, CheckboxProps};
+mod virtual_list;
+pub use button::{button, ButtonProps, PressEvent};
+pub use checkbox::{checkbox, CheckboxChangeEvent, CheckboxProps};
+pub use keyed_list::{keyed_list, KeyedListProps};
 pub use link::{link, LinkProps};
 pub use list::{list, ListModel, ListProps};
+#[cfg(feature = "ssr")]
+pub(crate) use list::{DummyObservableAs, DUMMY_USED};
 pub use radio::{radio_button, radio_group, RadioGroupProps, RadioProps};
 pub use slider::{slider, SliderProps};
 pub use text::text;
-pub use text_input::{text_input, TextInputProps};
+pub use text_input::{text_input, TextInputEvent, TextInputProps};
 pub use view::{view, ViewProps};
+pub use virtual_list::{virtual_list, RowHeight, VirtualListProps};
 
-use crate::backend::Backend;
+use crate::{backend::Backend, hydration};
 
 pin_project! {
     /** For creating your own component through web_sys.
@@ -129,6 +139,7 @@ pin_project! {
 struct ElementFutureInner {
     node: Node,
     vnodes: Option<MyAndParentVNodes>,
+    lifecycle: Lifecycle<Node>,
 }
 struct MyAndParentVNodes {
     my: Rc<VNode<Backend>>,
@@ -138,6 +149,7 @@ struct MyAndParentVNodes {
 impl Drop for ElementFutureInner {
     fn drop(&mut self) {
         if let Some(MyAndParentVNodes { parent, .. }) = &self.vnodes {
+            self.lifecycle.unmount(&self.node);
             parent.del_child_node(Default::default());
         }
     }
@@ -146,9 +158,33 @@ impl<F: Future> ElementFuture<F> {
     pub fn new(future: F, node: Node) -> Self {
         Self {
             future,
-            inner: ElementFutureInner { node, vnodes: None },
+            inner: ElementFutureInner {
+                node,
+                vnodes: None,
+                lifecycle: Lifecycle::default(),
+            },
         }
     }
+    /** Call `callback` with the node right after it is added to its parent.
+     */
+    pub fn on_mount<C: FnOnce(&Node) + 'static>(mut self, callback: C) -> Self {
+        self.inner.lifecycle.add_on_mount(callback);
+        self
+    }
+    /** Call `callback` with the node right before it is removed from its parent.
+     */
+    pub fn on_unmount<C: FnOnce(&Node) + 'static>(mut self, callback: C) -> Self {
+        self.inner.lifecycle.add_on_unmount(callback);
+        self
+    }
+    /** Fill `node_ref` with the node while it is mounted. Does nothing if `None`.
+     */
+    pub fn node_ref(mut self, node_ref: Option<&NodeRef<Backend>>) -> Self {
+        if let Some(node_ref) = node_ref {
+            node_ref.attach(&mut self.inner.lifecycle);
+        }
+        self
+    }
 }
 impl<F: Future> Future for ElementFuture<F> {
     type Output = F::Output;
@@ -157,22 +193,27 @@ impl<F: Future> Future for ElementFuture<F> {
         let vnk = Backend::get_vnode_key();
         let vnodes = this.inner.vnodes.get_or_insert_with(|| {
             let parent_vnode = vnk.with(Clone::clone);
+            // A node that is already in the DOM was claimed from server-rendered HTML.
+            let claimed = hydration::is_hydrating() && this.inner.node.parent_node().is_some();
             parent_vnode.add_child_node(this.inner.node.to_owned(), Default::default());
+            this.inner.lifecycle.mount(&this.inner.node);
             let parent_context = parent_vnode.get_context_map().clone();
-            let my = Rc::new(
-                ConcreteNodeVNode::new(
-                    RefNode::Parent {
-                        parent: this.inner.node.clone(),
-                    },
+            let my_node = RefNode::Parent {
+                parent: this.inner.node.clone(),
+            };
+            let my = match claimed {
+                true => hydration::new_vnode(
+                    my_node,
                     parent_context,
-                )
-                .into(),
-            );
+                    hydration::existing_children(&this.inner.node),
+                ),
+                false => Rc::new(ConcreteNodeVNode::new(my_node, parent_context).into()),
+            };
             MyAndParentVNodes {
                 my,
                 parent: parent_vnode,
             }
         });
-        vnk.set(&vnodes.my, || this.future.poll(cx))
+        enter_vnode(&vnodes.my, || this.future.poll(cx))
     }
 }
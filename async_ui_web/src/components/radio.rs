@@ -5,7 +5,7 @@ use observables::{cell::ReactiveCell, ObservableAs, ObservableAsExt};
 use wasm_bindgen::{prelude::Closure, JsCast};
 use web_sys::{Event, HtmlInputElement};
 
-use crate::{get_context, window::DOCUMENT, with_context, Fragment};
+use crate::{get_context, hydration::create_element, with_context, Fragment};
 
 use super::ElementFuture;
 
@@ -41,17 +41,16 @@ pub async fn radio_group<'c, E: Clone + PartialEq + 'static>(
     let group_1 = group.clone();
     with_context::<_, RadioGroup<E>>(children, group)
         .or(group_1.value.as_observable().for_each(|e| {
-            on_change.as_mut().map(|f| f(e.to_owned()));
+            if let Some(f) = on_change.as_mut() {
+                f(e.to_owned());
+            }
         }))
         .await;
 }
 
 pub async fn radio_button<E: Clone + PartialEq + 'static>(RadioProps { value }: RadioProps<E>) {
     let ctx = get_context::<RadioGroup<E>>();
-    let elem: HtmlInputElement = DOCUMENT.with(|doc| {
-        let elem = doc.create_element("input").expect("create element failed");
-        elem.unchecked_into()
-    });
+    let elem: HtmlInputElement = create_element("input").unchecked_into();
     elem.set_type("radio");
     elem.set_name(&ctx.name);
 
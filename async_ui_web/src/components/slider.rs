@@ -4,11 +4,12 @@ use smallvec::SmallVec;
 use wasm_bindgen::JsCast;
 use web_sys::{Event, HtmlInputElement};
 
-use crate::DOCUMENT;
+use crate::hydration::create_element;
+use crate::NodeRef;
 
 use super::{
     dummy::{dummy_handler, is_dummy_handler},
-    events::{create_handler, EventsManager, QueuedEvent},
+    events::{create_handler, handle_events, EventsManager, QueuedEvent},
     ElementFuture,
 };
 
@@ -28,6 +29,7 @@ pub struct SliderProps<'c> {
     pub max: &'c dyn ObservableAs<f64>,
     pub step: &'c dyn ObservableAs<f64>,
     pub on_change: &'c mut dyn FnMut(SliderChangeEvent),
+    pub node_ref: Option<&'c NodeRef>,
 }
 
 impl<'c> Default for SliderProps<'c> {
@@ -38,6 +40,7 @@ impl<'c> Default for SliderProps<'c> {
             max: &[100.0],
             step: &[1.0],
             on_change: dummy_handler(),
+            node_ref: None,
         }
     }
 }
@@ -51,12 +54,10 @@ pub async fn slider(
         max,
         step,
         on_change,
+        node_ref,
     }: SliderProps<'_>,
 ) {
-    let elem: HtmlInputElement = DOCUMENT.with(|doc| {
-        let elem = doc.create_element("input").expect("create element failed");
-        elem.unchecked_into()
-    });
+    let elem: HtmlInputElement = create_element("input").unchecked_into();
     elem.set_type("range");
 
     let elem_1 = elem.clone();
@@ -70,20 +71,11 @@ pub async fn slider(
         handlers.push(h);
     }
 
-    let future = (async {
-        loop {
-            let mut events = manager.get_queue().await;
-            for event in events.drain(..) {
-                let slider_change_event = SliderChangeEvent {
-                    node: elem_1.clone(),
-                };
-                match event {
-                    QueuedEvent::Change() => {
-                        on_change(slider_change_event);
-                    }
-                    _ => {}
-                }
-            }
+    let future = handle_events(&manager, |event| {
+        if let QueuedEvent::Change() = event {
+            on_change(SliderChangeEvent {
+                node: elem_1.clone(),
+            });
         }
     })
     .or(value.for_each(|v| elem_1.set_value_as_number(*v)))
@@ -91,5 +83,7 @@ pub async fn slider(
     .or(max.for_each(|v| elem_1.set_max(&v.to_string())))
     .or(step.for_each(|v| elem_1.set_step(&v.to_string())));
 
-    ElementFuture::new(future, elem.into()).await;
+    ElementFuture::new(future, elem.into())
+        .node_ref(node_ref)
+        .await;
 }
@@ -1,8 +1,10 @@
-use observables::{ObservableAs, ObservableAsExt};
+use observables::ObservableAs;
+use web_sys::Node;
 
-use crate::window::DOCUMENT;
-
-use super::ElementFuture;
+use super::{
+    markup::{self, MarkupNode},
+    ElementFuture,
+};
 
 /** Text - HTML text node.
  *
@@ -13,7 +15,7 @@ use super::ElementFuture;
  * See [the module documentation][super] for help with the `ObservableAs` reactivity.
  */
 pub async fn text<'c>(text: &'c dyn ObservableAs<str>) {
-    let node: web_sys::Text = DOCUMENT.with(|doc| doc.create_text_node(""));
+    let node = Node::create_text();
     let node_2 = node.clone();
-    ElementFuture::new(text.for_each(|t| node_2.set_data(t)), node.into()).await;
+    ElementFuture::new(markup::text(&node_2, text), node).await;
 }
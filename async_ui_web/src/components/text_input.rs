@@ -1,15 +1,15 @@
 use futures_lite::FutureExt;
-use observables::{ObservableAs, ObservableAsExt};
+use observables::ObservableAs;
 use smallvec::SmallVec;
 use wasm_bindgen::JsCast;
-use web_sys::{HtmlElement, HtmlInputElement, HtmlTextAreaElement};
+use web_sys::{HtmlElement, HtmlInputElement, HtmlTextAreaElement, Node};
 
-use crate::{utils::class_list::ClassList, window::DOCUMENT};
+use crate::{utils::class_list::ClassList, NodeRef};
 
 use super::{
     dummy::{dummy_handler, is_dummy_handler},
-    events::{create_handler, EventsManager, QueuedEvent},
-    ElementFuture,
+    events::{create_handler, handle_events, EventsManager, QueuedEvent},
+    markup, ElementFuture,
 };
 #[derive(Clone)]
 enum InputNode {
@@ -30,12 +30,6 @@ impl InputNode {
             InputNode::MultiLine(e) => e.inner_text(),
         }
     }
-    fn set_value(&self, value: &str) {
-        match self {
-            InputNode::OneLine(e) => e.set_value(value),
-            InputNode::MultiLine(e) => e.set_inner_text(value),
-        }
-    }
 }
 
 pub struct TextInputEvent {
@@ -56,6 +50,7 @@ pub struct TextInputProps<'c> {
     pub multiline: bool,
     pub placeholder: &'c dyn ObservableAs<str>,
     pub class: Option<&'c ClassList<'c>>,
+    pub node_ref: Option<&'c NodeRef>,
 }
 
 impl<'c> Default for TextInputProps<'c> {
@@ -69,6 +64,7 @@ impl<'c> Default for TextInputProps<'c> {
             multiline: false,
             placeholder: &[""],
             class: None,
+            node_ref: None,
         }
     }
 }
@@ -86,84 +82,67 @@ pub async fn text_input<'c>(
         multiline,
         class,
         placeholder,
+        node_ref,
     }: TextInputProps<'c>,
 ) {
-    let input = DOCUMENT.with(|doc| {
-        let elem = doc
-            .create_element(match multiline {
-                true => "textarea",
-                false => "input",
-            })
-            .expect("create element failed");
+    let input = {
+        let elem: Node = markup::text_input(multiline, class);
         match multiline {
             true => InputNode::MultiLine(elem.unchecked_into()),
             false => InputNode::OneLine(elem.unchecked_into()),
         }
-    });
+    };
 
     let mut handlers = SmallVec::<[_; 5]>::new();
     let manager = EventsManager::new();
     let input_elem = input.as_elem();
 
     if !is_dummy_handler(on_change_text) {
-        let h = create_handler(&manager, |e| QueuedEvent::Input(e));
+        let h = create_handler(&manager, QueuedEvent::Input);
         input_elem.set_oninput(Some(h.get_function()));
         handlers.push(h);
     }
     if !is_dummy_handler(on_submit) {
-        let h = create_handler(&manager, |e| QueuedEvent::KeyPress(e));
+        let h = create_handler(&manager, QueuedEvent::KeyPress);
         input_elem.set_onkeypress(Some(h.get_function()));
         handlers.push(h);
     }
     if !is_dummy_handler(on_blur) {
-        let h = create_handler(&manager, |e| QueuedEvent::Blur(e));
+        let h = create_handler(&manager, QueuedEvent::Blur);
         input_elem.set_onblur(Some(h.get_function()));
         handlers.push(h);
     }
     if !is_dummy_handler(on_focus) {
-        let h = create_handler(&manager, |e| QueuedEvent::Focus(e));
+        let h = create_handler(&manager, QueuedEvent::Focus);
         input_elem.set_onfocus(Some(h.get_function()));
         handlers.push(h);
     }
-    if let Some(class) = class {
-        class.set_dom(input.as_elem().class_list());
-    }
+    let input_node: Node = input_elem.clone().into();
 
-    let future = (async {
-        manager.grab_waker().await;
-        loop {
-            let mut events = manager.get_queue().await;
-            for event in events.drain(..) {
-                let text_input_event = TextInputEvent {
-                    node: input.clone(),
-                };
-                match event {
-                    QueuedEvent::Input(_e) => {
-                        on_change_text(text_input_event);
-                    }
-                    QueuedEvent::KeyPress(e) => {
-                        if e.key() == "Enter" {
-                            e.prevent_default();
-                            on_submit(text_input_event);
-                        }
-                    }
-                    QueuedEvent::Blur(_e) => {
-                        on_blur(text_input_event);
-                    }
-                    QueuedEvent::Focus(_e) => {
-                        on_focus(text_input_event);
-                    }
-                    _ => {}
-                }
+    let future = handle_events(&manager, |event| {
+        let text_input_event = TextInputEvent {
+            node: input.clone(),
+        };
+        match event {
+            QueuedEvent::Input(_e) => {
+                on_change_text(text_input_event);
+            }
+            QueuedEvent::KeyPress(e) if e.key() == "Enter" => {
+                e.prevent_default();
+                on_submit(text_input_event);
+            }
+            QueuedEvent::Blur(_e) => {
+                on_blur(text_input_event);
+            }
+            QueuedEvent::Focus(_e) => {
+                on_focus(text_input_event);
             }
+            _ => {}
         }
     })
-    .or(text.for_each(|t| input.set_value(t)))
-    .or(placeholder.for_each(|t| {
-        input_elem
-            .set_attribute("placeholder", t)
-            .expect("set placeholder failed")
-    }));
+    .or(markup::text_input_value(&input_node, text, placeholder));
 
-    ElementFuture::new(future, input.as_elem().clone().into()).await;
+    ElementFuture::new(future, input.as_elem().clone().into())
+        .node_ref(node_ref)
+        .await;
 }
@@ -1,11 +1,14 @@
-use crate::{utils::class_list::ClassList, window::DOCUMENT, Fragment};
+use web_sys::Node;
 
-use super::ElementFuture;
+use crate::{utils::class_list::ClassList, Fragment, NodeRef};
+
+use super::{markup, ElementFuture};
 
 pub struct ViewProps<'c> {
     pub children: Fragment<'c>,
     pub class: Option<&'c ClassList<'c>>,
     pub element_tag: &'c str,
+    pub node_ref: Option<&'c NodeRef>,
 }
 impl<'c> Default for ViewProps<'c> {
     fn default() -> Self {
@@ -13,6 +16,7 @@ impl<'c> Default for ViewProps<'c> {
             children: Default::default(),
             class: Default::default(),
             element_tag: "div",
+            node_ref: None,
         }
     }
 }
@@ -25,14 +29,9 @@ pub async fn view<'c>(
         children,
         class,
         element_tag,
+        node_ref,
     }: ViewProps<'c>,
 ) {
-    let elem = DOCUMENT.with(|doc| {
-        doc.create_element(element_tag)
-            .expect("create element failed")
-    });
-    if let Some(class) = class {
-        class.set_dom(elem.class_list());
-    }
-    ElementFuture::new(children, elem.into()).await;
+    let elem: Node = markup::element(element_tag, class);
+    ElementFuture::new(children, elem).node_ref(node_ref).await;
 }
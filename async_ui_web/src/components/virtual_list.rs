@@ -0,0 +1,642 @@
+use std::{
+    collections::VecDeque,
+    future::{pending, IntoFuture},
+    marker::PhantomData,
+    ops::Range,
+    rc::Rc,
+};
+
+use async_task::Task;
+use async_ui_core::{
+    backend::BackendTrait,
+    executor::spawn_local,
+    list::{Change, ListModel, ListModelPrivateAPIs},
+    vnode::{
+        node_concrete::{ConcreteNodeVNode, RefNode},
+        VNodeTrait, WithVNode,
+    },
+};
+use futures_lite::{pin, FutureExt};
+use observables::{cell::ReactiveCell, ObservableAs, ObservableAsExt};
+use scoped_async_spawn::SpawnGuard;
+use wasm_bindgen::JsCast;
+use web_sys::{Event, HtmlElement, Node};
+
+use crate::{
+    animation::Animator,
+    backend::Backend,
+    hydration::create_element,
+    utils::class_list::ClassList,
+    window::{DOCUMENT, WINDOW},
+};
+
+use super::{
+    events::EventHandler,
+    list::{DummyObservableAs, DUMMY_USED},
+    ElementFuture,
+};
+
+/** How tall the rows of a [virtual_list] are, in pixels.
+ */
+#[derive(Clone, Copy)]
+pub enum RowHeight {
+    /// Every row is exactly this tall. Rows are given this height.
+    Fixed(f64),
+    /// Rows are measured once rendered. Rows never rendered are assumed to be `estimate` tall.
+    Measured { estimate: f64 },
+}
+
+pub struct VirtualListProps<'c, T: Clone, F: IntoFuture> {
+    pub data: &'c dyn ObservableAs<ListModel<T>>,
+    pub render: &'c dyn Fn(T) -> F,
+    pub row_height: RowHeight,
+    /// The scrolling element the list is in.
+    /// If `None`, the list scrolls by itself; give it a height (through `class`).
+    pub scroll_container: Option<&'c HtmlElement>,
+    /// How many rows to render above and below the visible ones.
+    pub overscan: usize,
+    pub class: Option<&'c ClassList<'c>>,
+}
+impl<'c, T: Clone + 'c, F: IntoFuture> Default for VirtualListProps<'c, T, F> {
+    fn default() -> Self {
+        Self {
+            data: &DummyObservableAs(PhantomData),
+            render: &|_: T| panic!("{}", DUMMY_USED),
+            row_height: RowHeight::Measured { estimate: 20.0 },
+            scroll_container: None,
+            overscan: 3,
+            class: None,
+        }
+    }
+}
+
+/** The heights of the rows, and where each row starts.
+ *
+ * For measured heights, the tops of the rows are cached,
+ * and only recomputed from the first row whose height changed.
+ */
+struct Heights {
+    row_height: RowHeight,
+    len: usize,
+    /// For [RowHeight::Measured], the height of each row, if measured.
+    measured: Vec<Option<f64>>,
+    /// For [RowHeight::Measured], `tops[i]` is where row `i` starts (`tops[len]` is the total height).
+    /// Only the first `valid` entries are up to date.
+    tops: Vec<f64>,
+    valid: usize,
+}
+
+impl Heights {
+    fn new(row_height: RowHeight, len: usize) -> Self {
+        let mut this = Self {
+            row_height,
+            len: 0,
+            measured: Vec::new(),
+            tops: Vec::new(),
+            valid: 0,
+        };
+        this.reset(len);
+        this
+    }
+    fn is_measured(&self) -> bool {
+        matches!(self.row_height, RowHeight::Measured { .. })
+    }
+    fn invalidate_from(&mut self, index: usize) {
+        self.valid = self.valid.min(index);
+    }
+    fn reset(&mut self, len: usize) {
+        self.len = len;
+        if self.is_measured() {
+            self.measured = vec![None; len];
+            self.invalidate_from(1);
+        }
+    }
+    fn insert(&mut self, index: usize) {
+        self.len += 1;
+        if self.is_measured() {
+            self.measured.insert(index, None);
+            self.invalidate_from(index + 1);
+        }
+    }
+    fn remove(&mut self, index: usize) {
+        self.len -= 1;
+        if self.is_measured() {
+            self.measured.remove(index);
+            self.invalidate_from(index + 1);
+        }
+    }
+    fn move_row(&mut self, from: usize, to: usize) {
+        if self.is_measured() {
+            let height = self.measured.remove(from);
+            self.measured.insert(to, height);
+            self.invalidate_from(from.min(to) + 1);
+        }
+    }
+    fn reorder(&mut self, sources: &[usize]) {
+        if self.is_measured() {
+            self.measured = sources
+                .iter()
+                .map(|source| self.measured[*source])
+                .collect();
+            self.invalidate_from(1);
+        }
+    }
+    fn set_measured(&mut self, index: usize, height: f64) {
+        if let Some(slot) = self.measured.get_mut(index) {
+            if *slot != Some(height) {
+                *slot = Some(height);
+                self.invalidate_from(index + 1);
+            }
+        }
+    }
+    /** Bring `tops` up to date. */
+    fn tops(&mut self, estimate: f64) -> &[f64] {
+        self.tops.resize(self.len + 1, 0.0);
+        let start = self.valid.max(1);
+        for index in start..=self.len {
+            self.tops[index] = self.tops[index - 1] + self.measured[index - 1].unwrap_or(estimate);
+        }
+        self.valid = self.len + 1;
+        &self.tops
+    }
+    /** Where row `index` starts. `index` may be the number of rows, for the total height. */
+    fn top(&mut self, index: usize) -> f64 {
+        match self.row_height {
+            RowHeight::Fixed(height) => index as f64 * height,
+            RowHeight::Measured { estimate } => self.tops(estimate)[index],
+        }
+    }
+    /** How many rows end at or before `y`. */
+    fn count_ending_by(&mut self, y: f64) -> usize {
+        let len = self.len;
+        match self.row_height {
+            RowHeight::Fixed(height) => ((y / height).floor().max(0.0) as usize).min(len),
+            RowHeight::Measured { estimate } => {
+                self.tops(estimate)[1..].partition_point(|bottom| *bottom <= y)
+            }
+        }
+    }
+    /** How many rows start before `y`. */
+    fn count_starting_before(&mut self, y: f64) -> usize {
+        let len = self.len;
+        match self.row_height {
+            RowHeight::Fixed(height) => ((y / height).ceil().max(0.0) as usize).min(len),
+            RowHeight::Measured { estimate } => {
+                self.tops(estimate)[..len].partition_point(|top| *top < y)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Layout {
+    rows: Range<usize>,
+    space_before: f64,
+    space_after: f64,
+}
+/** Which rows to render, and how much space to leave for the others.
+ */
+fn layout(
+    heights: &mut Heights,
+    scroll_offset: f64,
+    viewport_height: f64,
+    overscan: usize,
+) -> Layout {
+    let len = heights.len;
+    let first_visible = heights.count_ending_by(scroll_offset);
+    let end_visible = heights.count_starting_before(scroll_offset + viewport_height);
+    let start = first_visible.saturating_sub(overscan);
+    let end = (end_visible.max(first_visible) + overscan).min(len);
+    Layout {
+        rows: start..end,
+        space_before: heights.top(start),
+        space_after: heights.top(len) - heights.top(end),
+    }
+}
+
+fn create_div() -> HtmlElement {
+    DOCUMENT.with(|doc| {
+        doc.create_element("div")
+            .expect("create element failed")
+            .unchecked_into()
+    })
+}
+fn set_height(element: &HtmlElement, height: f64) {
+    element
+        .style()
+        .set_property("height", &format!("{height}px"))
+        .ok();
+}
+
+struct Row {
+    element: HtmlElement,
+    _task: Task<()>,
+}
+
+/** The rendered rows, from row `first` on. `None` for rows yet to be rendered.
+ */
+#[derive(Default)]
+struct Window {
+    first: usize,
+    rows: VecDeque<Option<Row>>,
+}
+
+impl Window {
+    fn end(&self) -> usize {
+        self.first + self.rows.len()
+    }
+    fn contains(&self, index: usize) -> bool {
+        (self.first..self.end()).contains(&index)
+    }
+    /** A row was inserted into the model at `index`.
+     * Returns `row` back if the index is outside the window.
+     */
+    fn insert(&mut self, index: usize, row: Option<Row>) -> Option<Row> {
+        if index < self.first {
+            self.first += 1;
+            row
+        } else if index < self.end() {
+            self.rows.insert(index - self.first, row);
+            None
+        } else {
+            row
+        }
+    }
+    /** The row at `index` was removed from the model. Returns it if it was rendered.
+     */
+    fn remove(&mut self, index: usize) -> Option<Row> {
+        if index < self.first {
+            self.first -= 1;
+            None
+        } else if index < self.end() {
+            self.rows.remove(index - self.first).flatten()
+        } else {
+            None
+        }
+    }
+    /** The model was reordered; keep the rows that stay in the window.
+     * Returns the others.
+     */
+    fn reorder(&mut self, sources: &[usize]) -> Vec<Option<Row>> {
+        let (first, end) = (self.first, self.end());
+        let mut old: Vec<Option<Row>> = self.rows.drain(..).collect();
+        self.rows = sources[first..end]
+            .iter()
+            .map(|source| match (first..end).contains(source) {
+                true => old[source - first].take(),
+                false => None,
+            })
+            .collect();
+        old
+    }
+    /** Move the element of the row at `position` to right before the next rendered row.
+     */
+    fn place(&self, position: usize, list: &HtmlElement, bottom_spacer: &HtmlElement) {
+        if let Some(row) = &self.rows[position] {
+            let before: Node = match self.rows.range(position + 1..).flatten().next() {
+                Some(next) => next.element.clone().into(),
+                None => bottom_spacer.clone().into(),
+            };
+            list.insert_before(&row.element, Some(&before))
+                .expect("insert failed");
+        }
+    }
+}
+
+/** Virtualized List - only renders the rows that are in view.
+ *
+ * Like [list][super::list], but for very long lists.
+ * Only the rows in the visible part of the scroll container (plus `overscan` rows on each side)
+ * are rendered. Empty spacer elements take the place of the others, so the scrollbar stays right.
+ * Each row is rendered in its own `<div>`.
+ *
+ * When the model changes, rendered rows that stay in view are kept (and moved if needed).
+ *
+ * ```rust
+ * # use async_ui_web::components::{text, virtual_list, ListModel, RowHeight, VirtualListProps};
+ * # use observables::cell::ReactiveCell;
+ * # #[derive(Clone)]
+ * # struct Row { name: String }
+ * # async fn render_row(row: Row) { text(&[&*row.name]).await }
+ * # async fn table(rows: ReactiveCell<ListModel<Row>>) {
+ * virtual_list(VirtualListProps {
+ *     data: &rows.as_observable(),
+ *     render: &|row: Row| render_row(row),
+ *     row_height: RowHeight::Fixed(24.0),
+ *     class: Some(&"h-96".into()),
+ *     ..Default::default()
+ * }).await;
+ * # }
+ * ```
+ */
+pub async fn virtual_list<'c, T: Clone + 'c, F: IntoFuture + 'c>(
+    VirtualListProps {
+        data,
+        render,
+        row_height,
+        scroll_container,
+        overscan,
+        class,
+    }: VirtualListProps<'c, T, F>,
+) {
+    let list: HtmlElement = create_element("div").unchecked_into();
+    if let Some(class) = class {
+        class.set_dom(list.class_list());
+    }
+    let scroller = match scroll_container {
+        Some(container) => container.clone(),
+        None => {
+            list.style().set_property("overflow-y", "auto").ok();
+            list.clone()
+        }
+    };
+    let top_spacer = create_div();
+    let bottom_spacer = create_div();
+    list.append_child(&top_spacer).expect("insert failed");
+    list.append_child(&bottom_spacer).expect("insert failed");
+
+    let inside = async {
+        let parent_context = Backend::get_vnode_key().with(|vn| vn.get_context_map().clone());
+        let guard = SpawnGuard::new();
+        pin!(guard);
+        let mut create_row = |value: T, before: &Node| {
+            let element = create_div();
+            if let RowHeight::Fixed(height) = row_height {
+                set_height(&element, height);
+            }
+            list.insert_before(&element, Some(before))
+                .expect("insert failed");
+            let fut = WithVNode::new(
+                render(value).into_future(),
+                Rc::new(
+                    ConcreteNodeVNode::new(
+                        RefNode::<Backend>::Parent {
+                            parent: element.clone().into(),
+                        },
+                        parent_context.clone(),
+                    )
+                    .into(),
+                ),
+            );
+            let fut = guard.as_mut().convert_future(async {
+                fut.await;
+            });
+            Row {
+                element,
+                _task: spawn_local(fut),
+            }
+        };
+        let remove_row = |row: Option<Row>| {
+            if let Some(row) = row {
+                list.remove_child(&row.element).ok();
+            }
+        };
+
+        let scrolled = Rc::new(ReactiveCell::new(0u32));
+        let handler = {
+            let scrolled = scrolled.clone();
+            EventHandler::new(move |_: Event| *scrolled.borrow_mut() += 1)
+        };
+        scroller
+            .add_event_listener_with_callback("scroll", handler.get_function())
+            .ok();
+        WINDOW.with(|window| {
+            window
+                .add_event_listener_with_callback("resize", handler.get_function())
+                .ok();
+        });
+        let _listeners = scopeguard::guard((), |_| {
+            scroller
+                .remove_event_listener_with_callback("scroll", handler.get_function())
+                .ok();
+            WINDOW.with(|window| {
+                window
+                    .remove_event_listener_with_callback("resize", handler.get_function())
+                    .ok();
+            });
+        });
+
+        let mut last_version = data.visit(|model| {
+            let model = ListModelPrivateAPIs(model);
+            model
+                .total_listeners()
+                .set(model.total_listeners().get() + 1);
+            model.get_version()
+        });
+        let _registration = scopeguard::guard((), |_| {
+            data.visit(|model| {
+                let model = ListModelPrivateAPIs(model);
+                model
+                    .total_listeners()
+                    .set(model.total_listeners().get() - 1);
+            });
+        });
+
+        let animator = Animator::new();
+        let mut heights = Heights::new(row_height, data.visit(ListModel::len));
+        let mut window = Window::default();
+        loop {
+            let added_rows = data.visit(|model| {
+                let values = model.underlying_vector();
+                if heights.is_measured() {
+                    for (index, row) in (window.first..).zip(window.rows.iter()) {
+                        let height = row.as_ref().map_or(0, |row| row.element.offset_height());
+                        if height > 0 {
+                            heights.set_measured(index, height as f64);
+                        }
+                    }
+                }
+                let model_priv = ListModelPrivateAPIs(model);
+                if model_priv.get_version() != last_version {
+                    for change in model_priv.changes_since_version(last_version) {
+                        match change {
+                            Change::Splice {
+                                remove_range,
+                                replace_with,
+                            } => {
+                                for index in remove_range.clone().rev() {
+                                    heights.remove(index);
+                                    remove_row(window.remove(index));
+                                }
+                                for offset in 0..replace_with.len() {
+                                    heights.insert(remove_range.start + offset);
+                                    window.insert(remove_range.start + offset, None);
+                                }
+                            }
+                            Change::Remove { index } => {
+                                heights.remove(*index);
+                                remove_row(window.remove(*index));
+                            }
+                            Change::Insert { index, .. } => {
+                                heights.insert(*index);
+                                window.insert(*index, None);
+                            }
+                            Change::Move { from, to } => {
+                                heights.move_row(*from, *to);
+                                let row = window.remove(*from);
+                                remove_row(window.insert(*to, row));
+                                if window.contains(*to) {
+                                    window.place(*to - window.first, &list, &bottom_spacer);
+                                }
+                            }
+                            Change::Reorder { sources } => {
+                                heights.reorder(sources);
+                                window.reorder(sources).into_iter().for_each(remove_row);
+                                for position in (0..window.rows.len()).rev() {
+                                    window.place(position, &list, &bottom_spacer);
+                                }
+                            }
+                            Change::Reset => {
+                                heights.reset(values.len());
+                                window.rows.drain(..).for_each(remove_row);
+                                // Everything is up to date now.
+                                break;
+                            }
+                        }
+                    }
+                    last_version = model_priv.get_version();
+                    model_priv
+                        .pending_listeners()
+                        .set(model_priv.pending_listeners().get() - 1);
+                }
+                let scroll_offset = match scroll_container {
+                    Some(container) => {
+                        container.get_bounding_client_rect().top()
+                            - list.get_bounding_client_rect().top()
+                    }
+                    None => list.scroll_top() as f64,
+                };
+                let Layout {
+                    rows: range,
+                    space_before,
+                    space_after,
+                } = layout(
+                    &mut heights,
+                    scroll_offset.max(0.0),
+                    scroller.client_height() as f64,
+                    overscan,
+                );
+                if range.start >= window.end() || range.end <= window.first {
+                    window.rows.drain(..).for_each(remove_row);
+                    window.first = range.start;
+                }
+                while window.first < range.start {
+                    remove_row(window.rows.pop_front().flatten());
+                    window.first += 1;
+                }
+                while window.end() > range.end {
+                    remove_row(window.rows.pop_back().flatten());
+                }
+                while window.first > range.start {
+                    window.first -= 1;
+                    window.rows.push_front(None);
+                }
+                while window.end() < range.end {
+                    window.rows.push_back(None);
+                }
+                // Render the missing rows, each before the next one.
+                let mut added_rows = false;
+                let mut before: Node = bottom_spacer.clone().into();
+                for (position, slot) in window.rows.iter_mut().enumerate().rev() {
+                    let row = match slot.take() {
+                        Some(row) => row,
+                        None => {
+                            added_rows = true;
+                            create_row(values[window.first + position].clone(), &before)
+                        }
+                    };
+                    before = row.element.clone().into();
+                    *slot = Some(row);
+                }
+                set_height(&top_spacer, space_before);
+                set_height(&bottom_spacer, space_after);
+                added_rows
+            });
+            let measure_new_rows = async {
+                match (added_rows, row_height) {
+                    (true, RowHeight::Measured { .. }) => {
+                        animator.next_frame().await;
+                    }
+                    _ => pending().await,
+                }
+            };
+            scrolled
+                .as_observable()
+                .until_change()
+                .or(data.until_change())
+                .or(measure_new_rows)
+                .await;
+        }
+    };
+    ElementFuture::new(inside, list.clone().into()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{layout, Heights, Layout, RowHeight, Window};
+
+    #[test]
+    fn fixed_height_window() {
+        let mut heights = Heights::new(RowHeight::Fixed(10.0), 1000);
+        assert_eq!(
+            layout(&mut heights, 95.0, 50.0, 2),
+            Layout {
+                rows: 7..17,
+                space_before: 70.0,
+                space_after: 9830.0,
+            }
+        );
+        assert_eq!(layout(&mut heights, 90.0, 50.0, 0).rows, 9..14);
+        let mut heights = Heights::new(RowHeight::Fixed(10.0), 3);
+        assert_eq!(layout(&mut heights, 0.0, 100.0, 2).rows, 0..3);
+        let mut heights = Heights::new(RowHeight::Fixed(10.0), 0);
+        assert_eq!(layout(&mut heights, 0.0, 100.0, 2).rows, 0..0);
+    }
+
+    #[test]
+    fn variable_height_window() {
+        let mut heights = Heights::new(RowHeight::Measured { estimate: 10.0 }, 5);
+        heights.set_measured(0, 50.0);
+        heights.set_measured(3, 100.0);
+        let layout = layout(&mut heights, 55.0, 20.0, 0);
+        assert_eq!(layout.rows, 1..4);
+        assert_eq!(layout.space_before, 50.0);
+        assert_eq!(layout.space_after, 10.0);
+    }
+
+    #[test]
+    fn measured_heights_follow_their_rows() {
+        let mut heights = Heights::new(RowHeight::Measured { estimate: 10.0 }, 3);
+        heights.set_measured(1, 30.0);
+        assert_eq!(heights.top(3), 50.0);
+        heights.insert(0);
+        heights.set_measured(0, 20.0);
+        assert_eq!(heights.top(2), 30.0);
+        assert_eq!(heights.top(4), 70.0);
+        heights.move_row(2, 0);
+        assert_eq!(heights.top(1), 30.0);
+        heights.remove(0);
+        heights.reorder(&[2, 1, 0]);
+        assert_eq!(heights.top(1), 10.0);
+        assert_eq!(heights.top(3), 40.0);
+    }
+
+    #[test]
+    fn window_shifts_with_the_model() {
+        let mut window = Window {
+            first: 5,
+            rows: (0..3).map(|_| None).collect(),
+        };
+        window.insert(2, None);
+        assert_eq!((window.first, window.end()), (6, 9));
+        window.insert(7, None);
+        assert_eq!((window.first, window.end()), (6, 10));
+        window.insert(10, None);
+        assert_eq!((window.first, window.end()), (6, 10));
+        window.remove(0);
+        window.remove(6);
+        assert_eq!((window.first, window.end()), (5, 8));
+        window.reorder(&[9, 8, 7, 6, 5, 4, 3, 2, 1, 0]);
+        assert_eq!((window.first, window.end()), (5, 8));
+    }
+}
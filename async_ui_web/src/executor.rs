@@ -1,16 +1,26 @@
-/*! The executor responsible for schduling and running futures. Not relevant to users.
+/*! The executor responsible for schduling and running futures.
  *
+ * Work triggered by user input runs in a microtask, right after the event handler.
+ * The built-in components react to their events in [UserInput][async_ui_core::executor::Priority::UserInput] tasks,
+ * which run ahead of any other queued work.
+ * Any other wakeup is coalesced into the next animation frame.
+ * Each run stops after a time budget (see [set_frame_budget]) and carries on in the next frame,
+ * so that the browser gets to paint.
+ * [last_frame_metrics] tells how much work the last frame did.
  */
 use std::{
     cell::{Cell, RefCell},
     future::Future,
     pin::Pin,
     task::{Context, Waker},
+    time::Duration,
 };
 
-use wasm_bindgen::{closure::Closure, JsCast, UnwrapThrowExt};
+use async_ui_core::executor::task_poll_count;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue, UnwrapThrowExt};
+use web_sys::IdleDeadline;
 
-use crate::window::WINDOW;
+use crate::window::{DOCUMENT, WINDOW};
 
 thread_local! {
     static EXECUTOR: ExecutorSingleton = ExecutorSingleton::new()
@@ -20,8 +30,24 @@ struct ExecutorSingleton {
     future: RefCell<Option<Pin<Box<dyn Future<Output = ()>>>>>,
     scheduled: Cell<bool>,
     active: Cell<bool>,
+    /// Whether an event handler is running.
+    handling_input: Cell<bool>,
+    microtask_queued: Cell<bool>,
+    frame_requested: Cell<bool>,
+    idle_requested: Cell<bool>,
+    /// How long one run may take, in milliseconds.
+    budget: Cell<f64>,
+    /// When the current run should yield back to the browser, in milliseconds since the epoch.
+    deadline: Cell<f64>,
+    frame: Cell<FrameMetrics>,
+    last_frame: Cell<FrameMetrics>,
 }
 
+/** The default for [set_frame_budget].
+ * Leaves about half of a 60Hz frame to the browser.
+ */
+const DEFAULT_BUDGET_MS: f64 = 8.0;
+
 impl ExecutorSingleton {
     fn new() -> Self {
         let waker = waker_fn::waker_fn(schedule);
@@ -30,36 +56,179 @@ impl ExecutorSingleton {
             future: RefCell::new(None),
             scheduled: Cell::new(false),
             active: Cell::new(false),
+            handling_input: Cell::new(false),
+            microtask_queued: Cell::new(false),
+            frame_requested: Cell::new(false),
+            idle_requested: Cell::new(false),
+            budget: Cell::new(DEFAULT_BUDGET_MS),
+            deadline: Cell::new(f64::INFINITY),
+            frame: Cell::new(FrameMetrics::default()),
+            last_frame: Cell::new(FrameMetrics::default()),
         }
     }
 }
+
+/** What the executor did during one animation frame.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameMetrics {
+    /// How many times a task was polled.
+    pub polls: u64,
+    /// How many times the executor ran (in the frame itself, or in microtasks and idle callbacks before it).
+    pub runs: u32,
+    /// Time spent running, in milliseconds.
+    pub busy_ms: f64,
+    /// Whether a run stopped because it used up its budget.
+    pub yielded: bool,
+}
+
+/** Set how long the executor may run at a time before yielding back to the browser.
+ * Defaults to 8ms.
+ *
+ * Tasks are never interrupted, so a single long poll can still go over.
+ */
+pub fn set_frame_budget(budget: Duration) {
+    EXECUTOR.with(|exe| exe.budget.set(budget.as_secs_f64() * 1000.0))
+}
+
+/** Metrics for the last animation frame in which the executor had work.
+ */
+pub fn last_frame_metrics() -> FrameMetrics {
+    EXECUTOR.with(|exe| exe.last_frame.get())
+}
+
 pub(crate) fn set_executor_future(future: Box<dyn Future<Output = ()>>) {
     EXECUTOR.with(|exe| *exe.future.borrow_mut() = Some(future.into()))
 }
+
+/** Run the executor now, within the budget.
+ */
 pub fn run_now() {
-    EXECUTOR.with(|exe| {
+    let budget = EXECUTOR.with(|exe| exe.budget.get());
+    run_until(js_sys::Date::now() + budget);
+}
+fn run_until(deadline: f64) {
+    let start = js_sys::Date::now();
+    let polls_before = task_poll_count();
+    let yielded = EXECUTOR.with(|exe| {
         exe.active.set(true);
-        while exe.scheduled.replace(false) {
+        exe.deadline.set(deadline);
+        while !out_of_budget() && exe.scheduled.replace(false) {
             let mut cx = Context::from_waker(&exe.waker);
-            match exe.future.borrow_mut().as_mut() {
-                Some(fu) => {
-                    let _ = fu.as_mut().poll(&mut cx);
-                }
-                None => {}
+            if let Some(fu) = exe.future.borrow_mut().as_mut() {
+                let _ = fu.as_mut().poll(&mut cx);
             }
         }
         exe.active.set(false);
-    })
+        exe.deadline.set(f64::INFINITY);
+        let yielded = exe.scheduled.get();
+        let mut frame = exe.frame.get();
+        frame.polls += task_poll_count() - polls_before;
+        frame.runs += 1;
+        frame.busy_ms += js_sys::Date::now() - start;
+        frame.yielded |= yielded;
+        exe.frame.set(frame);
+        yielded
+    });
+    if yielded {
+        // Let the browser handle input and paint, then carry on.
+        request_frame();
+    } else {
+        // Hydration ends once everything has had its chance to claim nodes.
+        crate::hydration::finish();
+    }
+    async_ui_core::lifecycle::notify_connected();
 }
+
+/** Run `handler` as an event handler.
+ * Work it triggers runs in a microtask right after, rather than in the next frame.
+ */
+pub(crate) fn handle_input(handler: impl FnOnce()) {
+    let outer = EXECUTOR.with(|exe| exe.handling_input.replace(true));
+    handler();
+    EXECUTOR.with(|exe| exe.handling_input.set(outer));
+}
+
 pub fn schedule() {
     EXECUTOR.with(|exe| {
-        if !exe.scheduled.replace(true) && !exe.active.get() {
-            let closure = Closure::once_into_js(run_now);
-            WINDOW.with(|window| {
-                window
-                    .set_timeout_with_callback(&closure.as_ref().unchecked_ref())
-                    .expect_throw("failed to schedule task");
-            })
+        exe.scheduled.set(true);
+        if exe.active.get() {
+            // The running loop will pick it up.
+        } else if exe.handling_input.get() {
+            // Even if a frame is already requested, don't make input wait for it.
+            queue_microtask();
+        } else {
+            request_frame();
         }
     })
 }
+fn queue_microtask() {
+    if EXECUTOR.with(|exe| exe.microtask_queued.replace(true)) {
+        return;
+    }
+    let closure = Closure::once_into_js(|| {
+        EXECUTOR.with(|exe| exe.microtask_queued.set(false));
+        run_now();
+    });
+    WINDOW.with(|window| window.queue_microtask(closure.unchecked_ref()));
+}
+fn request_frame() {
+    if EXECUTOR.with(|exe| exe.frame_requested.replace(true)) {
+        return;
+    }
+    let closure = Closure::once_into_js(on_frame);
+    // Animation frames don't come while the page is hidden.
+    if DOCUMENT.with(|document| document.hidden()) {
+        WINDOW.with(|window| {
+            window
+                .set_timeout_with_callback(closure.unchecked_ref())
+                .expect_throw("failed to schedule task");
+        })
+    } else {
+        WINDOW.with(|window| {
+            window
+                .request_animation_frame(closure.unchecked_ref())
+                .expect_throw("failed to request animation frame");
+        });
+    }
+}
+fn on_frame() {
+    EXECUTOR.with(|exe| {
+        exe.frame_requested.set(false);
+        let frame = exe.frame.take();
+        if frame.runs > 0 {
+            exe.last_frame.set(frame);
+        }
+    });
+    run_now();
+}
+/** Whether the current run has used up its time budget.
+ */
+pub(crate) fn out_of_budget() -> bool {
+    EXECUTOR.with(|exe| js_sys::Date::now() >= exe.deadline.get())
+}
+/** Run idle tasks in the next `requestIdleCallback`, for as long as the browser allows.
+ *
+ * Returns `false` if the browser does not support `requestIdleCallback`.
+ */
+pub(crate) fn request_idle() -> bool {
+    let supported = WINDOW.with(|window| {
+        js_sys::Reflect::has(window, &JsValue::from_str("requestIdleCallback")).unwrap_or(false)
+    });
+    if supported && !EXECUTOR.with(|exe| exe.idle_requested.replace(true)) {
+        let closure = Closure::once_into_js(|deadline: IdleDeadline| {
+            EXECUTOR.with(|exe| exe.idle_requested.set(false));
+            let end = js_sys::Date::now() + deadline.time_remaining();
+            async_ui_core::executor::run_idle_period(|| {
+                EXECUTOR.with(|exe| exe.scheduled.set(true));
+                run_until(end);
+            });
+        });
+        WINDOW.with(|window| {
+            window
+                .request_idle_callback(closure.as_ref().unchecked_ref())
+                .expect_throw("failed to request idle callback");
+        });
+    }
+    supported
+}
@@ -0,0 +1,252 @@
+/*! Adopting server-rendered DOM instead of building it again. See [hydrate_at][crate::hydrate_at].
+ *
+ * While hydrating, every concrete vnode created over existing DOM holds that DOM's children.
+ * Components claim them (through [create_element] and [create_text_node]) rather than creating new nodes.
+ * Claims are matched by position, so a sibling that renders early does not take the nodes of one still loading.
+ * Each such vnode is finished when the executor first runs out of work after it was created,
+ * unless a [suspense][crate::suspense] inside is still waiting to claim from it;
+ * then it is finished once that suspense has shown its children.
+ * A component that finds something else where its node should be warns, naming both, and creates its own node.
+ * Existing nodes that were never claimed are removed, with a warning, when their vnode is finished.
+ * Hydration ends when there is no vnode left to finish.
+ */
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    future::{pending, IntoFuture},
+    rc::{Rc, Weak},
+};
+
+use async_ui_core::{
+    backend::BackendTrait,
+    context::ContextMap,
+    vnode::{
+        node_concrete::{ConcreteNodeVNode, RefNode},
+        node_context::try_get_context,
+        Claim, VNode, VNodeTrait,
+    },
+};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Node, Text};
+
+use crate::{backend::Backend, window::DOCUMENT};
+
+/// Content of the comment the server puts between adjacent text nodes.
+pub(crate) const TEXT_SEPARATOR: &str = "/";
+
+/** A vnode created over existing DOM, waiting to be finished.
+ * Put in the context of its descendants, so a suspense can hold it open.
+ */
+struct Subtree {
+    vnode: Weak<VNode<Backend>>,
+    holds: Cell<usize>,
+}
+
+thread_local! {
+    static HYDRATING: RefCell<Option<Vec<Rc<Subtree>>>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn is_hydrating() -> bool {
+    HYDRATING.with(|h| h.borrow().is_some())
+}
+
+pub(crate) fn start() {
+    HYDRATING.with(|h| {
+        h.borrow_mut().get_or_insert_with(Vec::new);
+    })
+}
+
+/** Finish every vnode that isn't held, removing its existing nodes that were not claimed.
+ * Ends hydration if none are left. Does nothing if not hydrating.
+ *
+ * Called whenever the executor runs out of work.
+ */
+pub(crate) fn finish() {
+    let finished = HYDRATING.with(|h| {
+        let mut bm = h.borrow_mut();
+        let subtrees = bm.as_mut()?;
+        let (held, finished): (Vec<_>, Vec<_>) =
+            subtrees.drain(..).partition(|s| s.holds.get() > 0);
+        *subtrees = held;
+        if subtrees.is_empty() {
+            *bm = None;
+        }
+        Some(finished)
+    });
+    for subtree in finished.into_iter().flatten() {
+        if let Some(VNode::ConcreteNode(concrete)) = subtree.vnode.upgrade().as_deref() {
+            for unclaimed in concrete.finish_hydration() {
+                web_sys::console::warn_1(
+                    &format!(
+                        "async_ui hydration mismatch: {} was not claimed by any component; removing it",
+                        describe(&unclaimed)
+                    )
+                    .into(),
+                );
+            }
+        }
+    }
+}
+
+struct Hold(Rc<Subtree>);
+impl Drop for Hold {
+    fn drop(&mut self) {
+        self.0.holds.set(self.0.holds.get() - 1);
+    }
+}
+
+/** Run `fallback`, keeping the nearest vnode being hydrated from being finished until this is dropped.
+ *
+ * For the fallback of a suspense: the suspended children claim nodes from that vnode,
+ * and the fallback is dropped right after they are shown.
+ */
+pub(crate) async fn hold_while_alive<F: IntoFuture>(fallback: F) {
+    let _hold = match is_hydrating() {
+        true => try_get_context::<Backend, Subtree>().map(|subtree| {
+            subtree.holds.set(subtree.holds.get() + 1);
+            Hold(subtree)
+        }),
+        false => None,
+    };
+    fallback.await;
+    pending::<()>().await
+}
+
+/** The children of `node`, to be claimed.
+ *
+ * The comments that SSR puts between adjacent text nodes are removed here.
+ */
+pub(crate) fn existing_children(node: &Node) -> VecDeque<Node> {
+    let mut children = VecDeque::new();
+    let mut next = node.first_child();
+    while let Some(child) = next {
+        next = child.next_sibling();
+        if child.node_type() == Node::COMMENT_NODE
+            && child.node_value().as_deref() == Some(TEXT_SEPARATOR)
+        {
+            node.remove_child(&child).ok();
+        } else {
+            children.push_back(child);
+        }
+    }
+    children
+}
+
+/** Create a concrete vnode whose existing children can be claimed until it is finished.
+ */
+pub(crate) fn new_vnode(
+    node: RefNode<Backend>,
+    mut context: ContextMap,
+    existing: VecDeque<Node>,
+) -> Rc<VNode<Backend>> {
+    if existing.is_empty() || !is_hydrating() {
+        return Rc::new(ConcreteNodeVNode::new_hydrating(node, context, existing).into());
+    }
+    Rc::new_cyclic(|weak| {
+        let subtree = Rc::new(Subtree {
+            vnode: weak.clone(),
+            holds: Cell::new(0),
+        });
+        HYDRATING.with(|h| {
+            if let Some(subtrees) = h.borrow_mut().as_mut() {
+                subtrees.push(subtree.clone());
+            }
+        });
+        context.insert(subtree);
+        ConcreteNodeVNode::new_hydrating(node, context, existing).into()
+    })
+}
+
+fn describe(node: &Node) -> String {
+    match node.node_type() {
+        Node::ELEMENT_NODE => format!("<{}>", node.node_name().to_lowercase()),
+        Node::TEXT_NODE => "a text node".into(),
+        Node::COMMENT_NODE => "a comment".into(),
+        _ => node.node_name(),
+    }
+}
+
+/** Claim the next existing node that matches, for the node about to be added by the current component, if hydrating.
+ *
+ * If `skip` is set, existing nodes that don't match are passed over (they may belong to another sibling,
+ * or get removed when hydration finishes) until one does. Otherwise, a node that doesn't match is left for later.
+ *
+ * When nothing is claimed, also returns the first existing node that was passed over or left.
+ */
+fn claim(skip: bool, matches: &dyn Fn(&Node) -> bool) -> Result<Node, Option<Node>> {
+    let vnk = Backend::get_vnode_key();
+    if !is_hydrating() || !vnk.is_set() {
+        return Err(None);
+    }
+    let mismatched = RefCell::new(None);
+    let claimed = vnk.with(|vnode| {
+        vnode.claim_child_node(Default::default(), &|node| {
+            if matches(node) {
+                return Claim::Take;
+            }
+            mismatched.borrow_mut().get_or_insert_with(|| node.clone());
+            match skip {
+                true => Claim::Skip,
+                false => Claim::Leave,
+            }
+        })
+    });
+    claimed.ok_or_else(|| mismatched.into_inner())
+}
+
+/** Log that the server-rendered DOM doesn't match what is being rendered now.
+ */
+pub(crate) fn warn_mismatch(expected: &str, found: &str) {
+    web_sys::console::warn_1(
+        &format!("async_ui hydration mismatch: expected {expected} but found {found}").into(),
+    );
+}
+
+/** Claim an existing element with the given tag if hydrating, otherwise create one.
+ *
+ * Warns if there were existing nodes but none of them matched.
+ */
+pub(crate) fn create_element(tag: &str) -> Element {
+    match claim(true, &|node| {
+        node.node_type() == Node::ELEMENT_NODE && node.node_name().eq_ignore_ascii_case(tag)
+    }) {
+        Ok(node) => node.unchecked_into(),
+        Err(mismatched) => {
+            if let Some(found) = mismatched {
+                warn_mismatch(
+                    &format!("<{}>", tag.to_lowercase()),
+                    &format!("{}; creating a new element", describe(&found)),
+                );
+            }
+            DOCUMENT.with(|doc| doc.create_element(tag).expect("create element failed"))
+        }
+    }
+}
+
+/** Claim an existing text node if hydrating, otherwise create an empty one.
+ *
+ * Nodes that aren't text are not passed over: empty text doesn't show up in server-rendered HTML at all,
+ * so the next node is likely a sibling's.
+ */
+pub(crate) fn create_text_node() -> Text {
+    match claim(false, &|node| node.node_type() == Node::TEXT_NODE) {
+        Ok(node) => node.unchecked_into(),
+        Err(_) => DOCUMENT.with(|doc| doc.create_text_node("")),
+    }
+}
+
+/** Warn if a claimed text node holds something other than the `text` about to be set on it.
+ *
+ * New text nodes start out empty, and the server doesn't render empty text, so only claimed nodes are checked.
+ */
+pub(crate) fn check_text(node: &Node, text: &str) {
+    if let Some(found) = node
+        .node_value()
+        .filter(|found| !found.is_empty() && found != text)
+    {
+        warn_mismatch(
+            &format!("text {text:?}"),
+            &format!("text {found:?}; replacing it"),
+        );
+    }
+}
@@ -9,7 +9,7 @@ use async_ui_core::{
 };
 use web_sys::Node;
 
-use crate::backend::Backend;
+use crate::{backend::Backend, hydration};
 
 /** Mount the given element future in a node.
  *
@@ -42,3 +42,38 @@ pub fn mount<F: IntoFuture + 'static>(root: F) {
         .unwrap();
     mount_at(root, node.into())
 }
+
+/** Like [mount_at], but adopt the existing content of the node (e.g. HTML from
+ * [render_to_string][crate::ssr::render_to_string]) instead of building everything anew.
+ *
+ * Components claim the existing nodes that match what they render.
+ * On a mismatch, a warning is logged, the existing node is removed, and the next one is tried.
+ * Existing nodes that were not claimed are removed once the executor runs out of work,
+ * or, under a [suspense][crate::suspense] that is still waiting, once it shows its children.
+ */
+pub fn hydrate_at<F: IntoFuture + 'static>(root: F, node: Node) {
+    hydration::start();
+    let existing = hydration::existing_children(&node);
+    let fut = WithVNode::new(
+        root.into_future(),
+        hydration::new_vnode(
+            RefNode::<Backend>::Parent { parent: node },
+            Default::default(),
+            existing,
+        ),
+    );
+    core_mount::<Backend, _>(fut)
+}
+
+/** Like [mount], but adopt the server-rendered content of the page's <body>.
+ * See [hydrate_at].
+ */
+pub fn hydrate<F: IntoFuture + 'static>(root: F) {
+    let node = web_sys::window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .body()
+        .unwrap();
+    hydrate_at(root, node.into())
+}
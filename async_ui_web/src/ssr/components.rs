@@ -0,0 +1,285 @@
+/*! The built-in components, for server-side rendering.
+ *
+ * These take the same props as the components in [crate::components],
+ * minus the event handlers (there are no events on the server).
+ * They build their nodes through the same code as the client components
+ * (see [MarkupNode][crate::components::markup::MarkupNode]),
+ * so the result can be hydrated on the client.
+ */
+use std::{future::IntoFuture, marker::PhantomData};
+
+use async_ui_core::list::{items, ListModel};
+use futures_lite::FutureExt;
+use observables::ObservableAs;
+
+use crate::{
+    components::{
+        markup::{self, MarkupNode},
+        DummyObservableAs, DUMMY_USED,
+    },
+    utils::class_list::ClassList,
+};
+
+use super::{ElementFuture, Fragment, SsrBackend, SsrNode};
+
+/** Text - HTML text node.
+ */
+pub async fn text<'c>(text: &'c dyn ObservableAs<str>) {
+    let node = SsrNode::create_text();
+    let node_2 = node.clone();
+    ElementFuture::new(markup::text(&node_2, text), node).await;
+}
+
+pub struct ViewProps<'c> {
+    pub children: Fragment<'c>,
+    pub class: Option<&'c ClassList<'c>>,
+    pub element_tag: &'c str,
+}
+impl<'c> Default for ViewProps<'c> {
+    fn default() -> Self {
+        Self {
+            children: Default::default(),
+            class: Default::default(),
+            element_tag: "div",
+        }
+    }
+}
+/** View - HTML <div> element
+ */
+pub async fn view<'c>(
+    ViewProps {
+        children,
+        class,
+        element_tag,
+    }: ViewProps<'c>,
+) {
+    ElementFuture::new(children, markup::element(element_tag, class)).await;
+}
+
+#[derive(Default)]
+pub struct ButtonProps<'c> {
+    pub children: Fragment<'c>,
+    pub class: Option<&'c ClassList<'c>>,
+}
+/** Button - HTML <button> element
+ */
+pub async fn button<'c>(ButtonProps { children, class }: ButtonProps<'c>) {
+    ElementFuture::new(children, markup::element("button", class)).await;
+}
+
+pub struct LinkProps<'c> {
+    pub children: Fragment<'c>,
+    pub href: &'c dyn ObservableAs<str>,
+    pub class: Option<&'c ClassList<'c>>,
+}
+impl<'c> Default for LinkProps<'c> {
+    fn default() -> Self {
+        Self {
+            children: Default::default(),
+            href: &["#"],
+            class: None,
+        }
+    }
+}
+/** Link / Anchor - HTML <a> element
+ */
+pub async fn link<'c>(
+    LinkProps {
+        children,
+        href,
+        class,
+    }: LinkProps<'c>,
+) {
+    let anchor: SsrNode = markup::element("a", class);
+    let anchor_copy = anchor.clone();
+    let future = children.or(markup::link_href(&anchor_copy, href));
+    ElementFuture::new(future, anchor).await
+}
+
+pub struct CheckboxProps<'c> {
+    pub value: &'c dyn ObservableAs<bool>,
+    pub class: Option<&'c ClassList<'c>>,
+}
+impl<'c> Default for CheckboxProps<'c> {
+    fn default() -> Self {
+        Self {
+            value: &[false],
+            class: None,
+        }
+    }
+}
+/** Checkbox - HTML <input type="checkbox">
+ */
+pub async fn checkbox<'c>(CheckboxProps { value, class }: CheckboxProps<'c>) {
+    let elem: SsrNode = markup::checkbox(class);
+    let elem_1 = elem.clone();
+    ElementFuture::new(markup::checkbox_value(&elem_1, value), elem).await;
+}
+
+pub struct TextInputProps<'c> {
+    pub text: &'c dyn ObservableAs<str>,
+    pub multiline: bool,
+    pub placeholder: &'c dyn ObservableAs<str>,
+    pub class: Option<&'c ClassList<'c>>,
+}
+impl<'c> Default for TextInputProps<'c> {
+    fn default() -> Self {
+        Self {
+            text: &[""],
+            multiline: false,
+            placeholder: &[""],
+            class: None,
+        }
+    }
+}
+/** Text Input - <input> and <textarea>
+ */
+pub async fn text_input<'c>(
+    TextInputProps {
+        text,
+        multiline,
+        placeholder,
+        class,
+    }: TextInputProps<'c>,
+) {
+    let input: SsrNode = markup::text_input(multiline, class);
+    let input_1 = input.clone();
+    ElementFuture::new(markup::text_input_value(&input_1, text, placeholder), input).await;
+}
+
+pub struct ListProps<'c, T: Clone, F: IntoFuture> {
+    pub data: &'c dyn ObservableAs<ListModel<T>>,
+    pub render: &'c dyn Fn(T) -> F,
+    pub class: Option<&'c ClassList<'c>>,
+}
+impl<'c, T: Clone + 'c, F: IntoFuture> Default for ListProps<'c, T, F> {
+    fn default() -> Self {
+        Self {
+            data: &DummyObservableAs(PhantomData),
+            render: &|_: T| panic!("{}", DUMMY_USED),
+            class: None,
+        }
+    }
+}
+/** List - renders each item followed by an anchor comment, like the client-side list.
+ *
+ * Changes to the model during rendering are applied item by item, as on the client.
+ */
+pub async fn list<'c, T: Clone + 'c, F: IntoFuture + 'c>(
+    ListProps {
+        data,
+        render,
+        class,
+    }: ListProps<'c, T, F>,
+) {
+    let container: SsrNode = markup::element("div", class);
+    let inside = items::<SsrBackend, _, _>(
+        container.clone(),
+        &SsrNode::new_comment,
+        Vec::new(),
+        data,
+        render,
+    );
+    ElementFuture::new(inside, container).await;
+}
+
+/* The client components need a DOM, so this only runs in a browser, e.g. with
+ * `wasm-pack test --headless --firefox async_ui_web`.
+ */
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use std::{future::IntoFuture, time::Duration};
+
+    use async_ui_core::list::ListModel;
+    use observables::cell::ReactiveCell;
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    use crate::{
+        components as client, executor::run_now, fragment, mount_at, ssr,
+        utils::class_list::ClassList, DOCUMENT,
+    };
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn render_on_client<F: IntoFuture + 'static>(root: F) -> String {
+        let container = DOCUMENT.with(|doc| doc.create_element("div").unwrap());
+        mount_at(root, container.clone().into());
+        run_now();
+        container.inner_html()
+    }
+
+    /* Adjacent text nodes are left out: the server puts a comment between them,
+     * which the client removes before hydrating.
+     */
+    #[wasm_bindgen_test]
+    fn same_markup_as_the_client() {
+        let on_server = ssr::render_to_string(
+            async {
+                let class = ClassList::from("story item");
+                let model = ReactiveCell::new(ListModel::from_iter(["x", "y"].into_iter()));
+                super::view(super::ViewProps {
+                    children: ssr::fragment((
+                        super::link(super::LinkProps {
+                            children: ssr::fragment((super::text(&["a < b"]),)),
+                            href: &["/?a=1&b=2"],
+                            class: Some(&class),
+                        }),
+                        super::button(Default::default()),
+                        super::checkbox(Default::default()),
+                        super::text_input(super::TextInputProps {
+                            placeholder: &["Name"],
+                            ..Default::default()
+                        }),
+                        super::text_input(super::TextInputProps {
+                            multiline: true,
+                            ..Default::default()
+                        }),
+                        super::list(super::ListProps {
+                            data: &model.as_observable(),
+                            render: &|item: &str| async move { super::text(&[item]).await },
+                            ..Default::default()
+                        }),
+                    )),
+                    class: Some(&class),
+                    element_tag: "form",
+                })
+                .await;
+            },
+            Duration::from_secs(1),
+        );
+        let on_client = render_on_client(async {
+            let class = ClassList::from("story item");
+            let model = ReactiveCell::new(ListModel::from_iter(["x", "y"].into_iter()));
+            client::view(client::ViewProps {
+                children: fragment((
+                    client::link(client::LinkProps {
+                        children: fragment((client::text(&["a < b"]),)),
+                        href: &["/?a=1&b=2"],
+                        class: Some(&class),
+                        ..Default::default()
+                    }),
+                    client::button(Default::default()),
+                    client::checkbox(Default::default()),
+                    client::text_input(client::TextInputProps {
+                        placeholder: &["Name"],
+                        ..Default::default()
+                    }),
+                    client::text_input(client::TextInputProps {
+                        multiline: true,
+                        ..Default::default()
+                    }),
+                    client::list(client::ListProps {
+                        data: &model.as_observable(),
+                        render: &|item: &str| async move { client::text(&[item]).await },
+                        ..Default::default()
+                    }),
+                )),
+                class: Some(&class),
+                element_tag: "form",
+                ..Default::default()
+            })
+            .await;
+        });
+        assert_eq!(on_server, on_client);
+    }
+}
@@ -0,0 +1,374 @@
+/*! Server-side rendering: render components to an HTML string.
+ *
+ * The SSR backend renders into a tree of [SsrNode]s instead of the DOM,
+ * so it can run outside the browser.
+ * Use the components in [ssr::components][components]; they produce the same markup
+ * as their counterparts in [crate::components].
+ *
+ * Only built with the `ssr` feature.
+ *
+ * ```rust
+ * # use std::time::Duration;
+ * # use async_ui_web::{ssr::{components::*, fragment, render_to_string}, utils::class_list::ClassList};
+ * let html = render_to_string(
+ *     async {
+ *         let class = ClassList::from("greeting");
+ *         view(ViewProps {
+ *             children: fragment((text(&["Hello World!"]),)),
+ *             class: Some(&class),
+ *             ..Default::default()
+ *         })
+ *         .await;
+ *     },
+ *     Duration::from_millis(100),
+ * );
+ * assert_eq!(html, r#"<div class="greeting">Hello World!</div>"#);
+ * ```
+ */
+pub mod components;
+mod node;
+
+use std::{
+    cell::Cell,
+    future::{Future, IntoFuture},
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use async_ui_core::{
+    backend::BackendTrait,
+    driver::ManualDriver,
+    executor::{get_driving_future, spawn_local},
+    fragment::Fragment as FragmentBase,
+    vnode::{
+        enter_vnode,
+        node_concrete::{ConcreteNodeVNode, RefNode},
+        VNode, VNodeTrait, WithVNode,
+    },
+};
+use pin_project_lite::pin_project;
+use scoped_async_spawn::GiveUnforgettableScope;
+use scoped_tls::{scoped_thread_local, ScopedKey};
+
+pub use node::SsrNode;
+
+pub type Fragment<'c> = FragmentBase<'c, SsrBackend>;
+
+pub fn fragment<'c, T: Into<Fragment<'c>>>(tuple: T) -> Fragment<'c> {
+    tuple.into()
+}
+
+pub struct SsrBackend;
+impl BackendTrait for SsrBackend {
+    type Node = SsrNode;
+
+    fn add_child_node(
+        parent: &mut Self::Node,
+        child: &mut Self::Node,
+        insert_before_sibling: Option<&Self::Node>,
+    ) {
+        parent.insert_before(child, insert_before_sibling);
+    }
+
+    fn del_child_node(parent: &mut Self::Node, child: &mut Self::Node) {
+        parent.remove_child(child);
+    }
+
+    /// There is no document on the server.
+    fn is_connected(_node: &Self::Node) -> bool {
+        false
+    }
+
+    /// Rendering is driven by [render_to_string], which steps the executor until it is done.
+    fn drive_executor<F: Future<Output = ()> + 'static>(fut: F) {
+        DRIVER.with(|driver| driver.set_future(fut));
+    }
+
+    /// Stop running tasks once the deadline given to [render_to_string] has passed,
+    /// even if some component keeps waking itself.
+    fn should_yield() -> bool {
+        DEADLINE.with(|deadline| deadline.get().is_some_and(|d| d.has_passed()))
+    }
+
+    fn initialize() {}
+
+    fn get_vnode_key() -> &'static ScopedKey<Rc<VNode<Self>>> {
+        &VNODE
+    }
+}
+
+scoped_thread_local!(
+    static VNODE: Rc<VNode<SsrBackend>>
+);
+
+thread_local! {
+    static DRIVER: ManualDriver = ManualDriver::new();
+    static DEADLINE: Cell<Option<Deadline>> = const { Cell::new(None) };
+}
+
+/** Render the given element future to HTML.
+ *
+ * The executor is polled until there is nothing left to do (all components are waiting)
+ * or until `timeout` has passed, whichever comes first.
+ * The HTML of everything rendered at that point is returned,
+ * and the future is then dropped.
+ */
+pub fn render_to_string<F: IntoFuture + 'static>(root: F, timeout: Duration) -> String {
+    let container = SsrNode::new_element("body");
+    let fut = WithVNode::new(
+        root.into_future(),
+        Rc::new(
+            ConcreteNodeVNode::new(
+                RefNode::<SsrBackend>::Parent {
+                    parent: container.clone(),
+                },
+                Default::default(),
+            )
+            .into(),
+        ),
+    );
+    let task = spawn_local(GiveUnforgettableScope::new_static(fut));
+    SsrBackend::drive_executor(get_driving_future::<SsrBackend>());
+    run_until(Deadline::after(timeout));
+    let html = container.inner_html();
+    std::mem::drop(task);
+    // Let the executor clean up the cancelled task.
+    run_until(Deadline::after(timeout));
+    html
+}
+
+/** Step the executor until it has nothing left to do or the deadline has passed.
+ */
+fn run_until(deadline: Deadline) {
+    DEADLINE.with(|d| d.set(Some(deadline)));
+    while DRIVER.with(ManualDriver::step) && !deadline.has_passed() {}
+    DEADLINE.with(|d| d.set(None));
+}
+
+/** A point in time, checked between steps of the executor.
+ *
+ * `std::time::Instant` panics on `wasm32-unknown-unknown`, so the JS clock is used there.
+ */
+#[derive(Clone, Copy)]
+struct Deadline {
+    #[cfg(not(target_arch = "wasm32"))]
+    at: std::time::Instant,
+    #[cfg(target_arch = "wasm32")]
+    at_ms: f64,
+}
+#[cfg(not(target_arch = "wasm32"))]
+impl Deadline {
+    fn after(timeout: Duration) -> Self {
+        Self {
+            at: std::time::Instant::now() + timeout,
+        }
+    }
+    fn has_passed(&self) -> bool {
+        std::time::Instant::now() >= self.at
+    }
+}
+#[cfg(target_arch = "wasm32")]
+impl Deadline {
+    fn after(timeout: Duration) -> Self {
+        Self {
+            at_ms: js_sys::Date::now() + timeout.as_secs_f64() * 1000.0,
+        }
+    }
+    fn has_passed(&self) -> bool {
+        js_sys::Date::now() >= self.at_ms
+    }
+}
+
+pin_project! {
+    /** For creating your own SSR component.
+     *
+     * Like [ElementFuture][crate::components::ElementFuture], but with an [SsrNode].
+     */
+    pub struct ElementFuture<F: Future> {
+        #[pin]
+        future: F,
+        inner: ElementFutureInner
+    }
+}
+struct ElementFutureInner {
+    node: SsrNode,
+    vnodes: Option<MyAndParentVNodes>,
+}
+struct MyAndParentVNodes {
+    my: Rc<VNode<SsrBackend>>,
+    parent: Rc<VNode<SsrBackend>>,
+}
+
+impl Drop for ElementFutureInner {
+    fn drop(&mut self) {
+        if let Some(MyAndParentVNodes { parent, .. }) = &self.vnodes {
+            parent.del_child_node(Default::default());
+        }
+    }
+}
+impl<F: Future> ElementFuture<F> {
+    pub fn new(future: F, node: SsrNode) -> Self {
+        Self {
+            future,
+            inner: ElementFutureInner { node, vnodes: None },
+        }
+    }
+}
+impl<F: Future> Future for ElementFuture<F> {
+    type Output = F::Output;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let vnk = SsrBackend::get_vnode_key();
+        let vnodes = this.inner.vnodes.get_or_insert_with(|| {
+            let parent_vnode = vnk.with(Clone::clone);
+            parent_vnode.add_child_node(this.inner.node.to_owned(), Default::default());
+            let parent_context = parent_vnode.get_context_map().clone();
+            let my = Rc::new(
+                ConcreteNodeVNode::new(
+                    RefNode::Parent {
+                        parent: this.inner.node.clone(),
+                    },
+                    parent_context,
+                )
+                .into(),
+            );
+            MyAndParentVNodes {
+                my,
+                parent: parent_vnode,
+            }
+        });
+        enter_vnode(&vnodes.my, || this.future.poll(cx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc, time::Duration};
+
+    use async_ui_core::list::ListModel;
+    use futures_lite::future::yield_now;
+    use observables::cell::ReactiveCell;
+
+    use super::{components::*, fragment, render_to_string};
+    use crate::utils::class_list::ClassList;
+
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn classes_and_attributes() {
+        let html = render_to_string(
+            async {
+                let class = ClassList::new(["story", "item"]);
+                link(LinkProps {
+                    children: fragment((text(&["a < b"]), text(&["!"]))),
+                    href: &["https://example.com/?a=1&b=\"2\""],
+                    class: Some(&class),
+                })
+                .await;
+            },
+            TIMEOUT,
+        );
+        assert_eq!(
+            html,
+            r#"<a class="story item" href="https://example.com/?a=1&amp;b=&quot;2&quot;">a &lt; b<!--/-->!</a>"#
+        );
+    }
+
+    #[test]
+    fn void_elements_and_state() {
+        let html = render_to_string(
+            async {
+                let checked = ReactiveCell::new(false);
+                checked.set(true);
+                view(ViewProps {
+                    children: fragment((
+                        checkbox(CheckboxProps {
+                            value: &checked.as_observable(),
+                            ..Default::default()
+                        }),
+                        text_input(TextInputProps {
+                            placeholder: &["Name"],
+                            ..Default::default()
+                        }),
+                    )),
+                    element_tag: "form",
+                    ..Default::default()
+                })
+                .await;
+            },
+            TIMEOUT,
+        );
+        assert_eq!(
+            html,
+            r#"<form><input type="checkbox" checked=""><input placeholder="Name"></form>"#
+        );
+    }
+
+    #[test]
+    fn list_items_with_anchors() {
+        let html = render_to_string(
+            async {
+                let model = ReactiveCell::new(ListModel::from_iter(["x", "y"].into_iter()));
+                list(ListProps {
+                    data: &model.as_observable(),
+                    render: &|item: &str| async move { text(&[item]).await },
+                    ..Default::default()
+                })
+                .await;
+            },
+            TIMEOUT,
+        );
+        assert_eq!(html, "<div>x<!---->y<!----></div>");
+    }
+
+    #[test]
+    fn list_items_are_kept_across_changes() {
+        let renders = Rc::new(Cell::new(0));
+        let renders_1 = renders.clone();
+        let html = render_to_string(
+            async move {
+                let model = ReactiveCell::new(ListModel::from_iter(["x"].into_iter()));
+                let render = |item: &'static str| {
+                    renders_1.set(renders_1.get() + 1);
+                    async move { text(&[item]).await }
+                };
+                fragment((
+                    list(ListProps {
+                        data: &model.as_observable(),
+                        render: &render,
+                        ..Default::default()
+                    }),
+                    async {
+                        yield_now().await;
+                        model.borrow_mut().push("y");
+                    },
+                ))
+                .await;
+            },
+            TIMEOUT,
+        );
+        assert_eq!(html, "<div>x<!---->y<!----></div>");
+        assert_eq!(renders.get(), 2);
+    }
+
+    #[test]
+    fn stops_at_the_deadline() {
+        let html = render_to_string(
+            async {
+                view(ViewProps {
+                    children: fragment((text(&["loading"]), async {
+                        loop {
+                            yield_now().await;
+                        }
+                    })),
+                    ..Default::default()
+                })
+                .await;
+            },
+            Duration::from_millis(10),
+        );
+        assert_eq!(html, "<div>loading</div>");
+    }
+}
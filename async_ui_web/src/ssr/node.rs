@@ -0,0 +1,259 @@
+use std::{
+    cell::RefCell,
+    fmt::Write,
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    components::markup::MarkupNode, hydration::TEXT_SEPARATOR, utils::class_list::ClassList,
+};
+
+/** A node in the tree built during server-side rendering.
+ *
+ * Cloning an `SsrNode` gives another handle to the same node,
+ * just like cloning a `web_sys::Node`.
+ */
+#[derive(Clone)]
+pub struct SsrNode(Rc<RefCell<NodeData>>);
+
+struct NodeData {
+    kind: NodeKind,
+    children: Vec<SsrNode>,
+    parent: Weak<RefCell<NodeData>>,
+}
+
+enum NodeKind {
+    Element {
+        tag: String,
+        attributes: Vec<(String, String)>,
+        classes: Vec<String>,
+    },
+    Text(String),
+    Comment,
+}
+
+// https://html.spec.whatwg.org/multipage/syntax.html#void-elements
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+fn escape_into(out: &mut String, value: &str, in_attribute: bool) {
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if in_attribute => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+}
+
+impl SsrNode {
+    fn new(kind: NodeKind) -> Self {
+        Self(Rc::new(RefCell::new(NodeData {
+            kind,
+            children: Vec::new(),
+            parent: Weak::new(),
+        })))
+    }
+    pub fn new_element(tag: &str) -> Self {
+        Self::new(NodeKind::Element {
+            tag: tag.to_owned(),
+            attributes: Vec::new(),
+            classes: Vec::new(),
+        })
+    }
+    pub fn new_text(text: &str) -> Self {
+        Self::new(NodeKind::Text(text.to_owned()))
+    }
+    /// An empty comment, used as a position marker (like the anchors of `list`).
+    pub fn new_comment() -> Self {
+        Self::new(NodeKind::Comment)
+    }
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+    pub fn set_text(&self, text: &str) {
+        if let NodeKind::Text(t) = &mut self.0.borrow_mut().kind {
+            *t = text.to_owned();
+        }
+    }
+    /// Set an attribute, replacing the previous value if there was one.
+    /// Does nothing on text and comment nodes.
+    pub fn set_attribute(&self, name: &str, value: &str) {
+        if let NodeKind::Element { attributes, .. } = &mut self.0.borrow_mut().kind {
+            match attributes.iter_mut().find(|(n, _)| n == name) {
+                Some((_, v)) => *v = value.to_owned(),
+                None => attributes.push((name.to_owned(), value.to_owned())),
+            }
+        }
+    }
+    pub fn remove_attribute(&self, name: &str) {
+        if let NodeKind::Element { attributes, .. } = &mut self.0.borrow_mut().kind {
+            attributes.retain(|(n, _)| n != name);
+        }
+    }
+    pub fn get_attribute(&self, name: &str) -> Option<String> {
+        match &self.0.borrow().kind {
+            NodeKind::Element { attributes, .. } => attributes
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, v)| v.to_owned()),
+            _ => None,
+        }
+    }
+    pub(crate) fn add_class(&self, class_name: &str) {
+        if let NodeKind::Element { classes, .. } = &mut self.0.borrow_mut().kind {
+            if !classes.iter().any(|c| c == class_name) {
+                classes.push(class_name.to_owned());
+            }
+        }
+    }
+    pub(crate) fn remove_class(&self, class_name: &str) {
+        if let NodeKind::Element { classes, .. } = &mut self.0.borrow_mut().kind {
+            classes.retain(|c| c != class_name);
+        }
+    }
+    pub(crate) fn clear_classes(&self) {
+        if let NodeKind::Element { classes, .. } = &mut self.0.borrow_mut().kind {
+            classes.clear();
+        }
+    }
+    pub fn children(&self) -> Vec<SsrNode> {
+        self.0.borrow().children.clone()
+    }
+    pub fn parent(&self) -> Option<SsrNode> {
+        self.0.borrow().parent.upgrade().map(SsrNode)
+    }
+    pub(crate) fn insert_before(&self, child: &SsrNode, before: Option<&SsrNode>) {
+        if let Some(old_parent) = child.parent() {
+            old_parent.remove_child(child);
+        }
+        let mut bm = self.0.borrow_mut();
+        let index = match before {
+            Some(before) => bm
+                .children
+                .iter()
+                .position(|ch| ch.ptr_eq(before))
+                .expect("insert failed"),
+            None => bm.children.len(),
+        };
+        bm.children.insert(index, child.clone());
+        child.0.borrow_mut().parent = Rc::downgrade(&self.0);
+    }
+    pub(crate) fn remove_child(&self, child: &SsrNode) {
+        let mut bm = self.0.borrow_mut();
+        let index = bm
+            .children
+            .iter()
+            .position(|ch| ch.ptr_eq(child))
+            .expect("remove failed");
+        bm.children.remove(index);
+        child.0.borrow_mut().parent = Weak::new();
+    }
+    /** Serialize this node and everything inside it to HTML.
+     */
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        self.write_html(&mut out);
+        out
+    }
+    /** Serialize the children of this node to HTML, without the node itself.
+     */
+    pub fn inner_html(&self) -> String {
+        let mut out = String::new();
+        self.write_children_html(&mut out);
+        out
+    }
+    fn write_children_html(&self, out: &mut String) {
+        let mut last_was_text = false;
+        for ch in self.0.borrow().children.iter() {
+            let is_text = matches!(ch.0.borrow().kind, NodeKind::Text(_));
+            if is_text && last_was_text {
+                // The HTML parser would merge adjacent text nodes into one.
+                // Keep them apart so the client can match them up again.
+                write!(out, "<!--{TEXT_SEPARATOR}-->").unwrap();
+            }
+            last_was_text = is_text;
+            ch.write_html(out);
+        }
+    }
+    fn write_html(&self, out: &mut String) {
+        let bm = self.0.borrow();
+        match &bm.kind {
+            NodeKind::Text(text) => escape_into(out, text, false),
+            NodeKind::Comment => out.push_str("<!---->"),
+            NodeKind::Element {
+                tag,
+                attributes,
+                classes,
+            } => {
+                write!(out, "<{tag}").unwrap();
+                if !classes.is_empty() {
+                    out.push_str(" class=\"");
+                    escape_into(out, &classes.join(" "), true);
+                    out.push('"');
+                }
+                for (name, value) in attributes.iter() {
+                    write!(out, " {name}=\"").unwrap();
+                    escape_into(out, value, true);
+                    out.push('"');
+                }
+                out.push('>');
+                if VOID_ELEMENTS.contains(&tag.as_str()) {
+                    return;
+                }
+                let tag = tag.to_owned();
+                std::mem::drop(bm);
+                self.write_children_html(out);
+                write!(out, "</{tag}>").unwrap();
+            }
+        }
+    }
+}
+
+impl MarkupNode for SsrNode {
+    fn create_element(tag: &str) -> Self {
+        Self::new_element(tag)
+    }
+    fn create_text() -> Self {
+        Self::new_text("")
+    }
+    fn set_text(&self, text: &str) {
+        SsrNode::set_text(self, text)
+    }
+    fn set_attribute(&self, name: &str, value: &str) {
+        SsrNode::set_attribute(self, name, value)
+    }
+    fn remove_attribute(&self, name: &str) {
+        SsrNode::remove_attribute(self, name)
+    }
+    fn set_class_list(&self, class: &ClassList<'_>) {
+        class.set_ssr(self.clone());
+    }
+    fn set_checked(&self, checked: bool) {
+        match checked {
+            true => self.set_attribute("checked", ""),
+            false => self.remove_attribute("checked"),
+        }
+    }
+    /// An empty value is left out, as the client would not write it either.
+    fn set_value(&self, value: &str) {
+        let is_textarea =
+            matches!(&self.0.borrow().kind, NodeKind::Element { tag, .. } if tag == "textarea");
+        if is_textarea {
+            for child in self.children() {
+                self.remove_child(&child);
+            }
+            if !value.is_empty() {
+                self.insert_before(&SsrNode::new_text(value), None);
+            }
+        } else if value.is_empty() {
+            self.remove_attribute("value");
+        } else {
+            self.set_attribute("value", value);
+        }
+    }
+}
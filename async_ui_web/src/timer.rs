@@ -0,0 +1,40 @@
+/*! Waiting with `setTimeout`.
+ */
+use std::{future::Future, pin::Pin, rc::Rc, time::Duration};
+
+use observables::{cell::ReactiveCell, ObservableAsExt, Timer};
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+use crate::WINDOW;
+
+/** Complete after `duration`.
+ */
+pub async fn sleep(duration: Duration) {
+    let done = Rc::new(ReactiveCell::new(false));
+    let done_1 = done.clone();
+    let on_timeout = Closure::<dyn Fn()>::new(move || *done_1.borrow_mut() = true);
+    let timeout = WINDOW.with(|win| {
+        win.set_timeout_with_callback_and_timeout_and_arguments_0(
+            on_timeout.as_ref().unchecked_ref(),
+            duration.as_millis() as i32,
+        )
+        .expect("set timeout failed")
+    });
+    let _guard = scopeguard::guard((), |_| {
+        WINDOW.with(|win| win.clear_timeout_with_handle(timeout))
+    });
+    done.as_observable().until_change().await;
+}
+
+/** The [Timer] for [debounce][ObservableAsExt::debounce] and friends, using [sleep].
+ */
+#[derive(Clone, Copy, Default)]
+pub struct PlatformTimer;
+
+impl Timer for PlatformTimer {
+    type Sleep = Pin<Box<dyn Future<Output = ()>>>;
+
+    fn sleep(&self, duration: Duration) -> Self::Sleep {
+        Box::pin(sleep(duration))
+    }
+}
@@ -6,6 +6,9 @@ use std::{borrow::Cow, cell::RefCell, collections::HashSet, hash::Hash};
 use smallvec::SmallVec;
 use web_sys::DomTokenList;
 
+#[cfg(feature = "ssr")]
+use crate::ssr::SsrNode;
+
 /** A type for representing the classes an HTML element have.
  *
  * The built-in components take this as prop.
@@ -30,7 +33,35 @@ struct Inner<'a> {
 }
 enum DomEnum {
     None,
-    Inserted(SmallVec<[DomTokenList; 1]>),
+    Inserted(SmallVec<[ClassTarget; 1]>),
+}
+enum ClassTarget {
+    Dom(DomTokenList),
+    #[cfg(feature = "ssr")]
+    Ssr(SsrNode),
+}
+impl ClassTarget {
+    fn add(&self, class_name: &str) {
+        match self {
+            ClassTarget::Dom(dom) => dom.add_1(class_name).expect("ClassList add failed"),
+            #[cfg(feature = "ssr")]
+            ClassTarget::Ssr(node) => node.add_class(class_name),
+        }
+    }
+    fn remove(&self, class_name: &str) {
+        match self {
+            ClassTarget::Dom(dom) => dom.remove_1(class_name).expect("ClassList remove failed"),
+            #[cfg(feature = "ssr")]
+            ClassTarget::Ssr(node) => node.remove_class(class_name),
+        }
+    }
+    fn clear(&self) {
+        match self {
+            ClassTarget::Dom(dom) => dom.set_value(""),
+            #[cfg(feature = "ssr")]
+            ClassTarget::Ssr(node) => node.clear_classes(),
+        }
+    }
 }
 
 impl<'a> ClassList<'a> {
@@ -46,8 +77,7 @@ impl<'a> ClassList<'a> {
         let mut bm = self.inner.borrow_mut();
         let v = class_name.into();
         if let DomEnum::Inserted(dom) = &bm.dom {
-            dom.iter()
-                .for_each(|dom| dom.add_1(&*v).expect("ClassList add failed"));
+            dom.iter().for_each(|dom| dom.add(&v));
         }
         bm.rust.insert(v);
     }
@@ -55,8 +85,7 @@ impl<'a> ClassList<'a> {
         let mut bm = self.inner.borrow_mut();
         let v = class_name.into();
         if let DomEnum::Inserted(dom) = &bm.dom {
-            dom.iter()
-                .for_each(|dom| dom.remove_1(&*v).expect("ClassList remove failed"));
+            dom.iter().for_each(|dom| dom.remove(&v));
         }
         bm.rust.remove(&v);
     }
@@ -68,13 +97,11 @@ impl<'a> ClassList<'a> {
         let v = class_name.into();
         if bm.rust.remove(&v) {
             if let DomEnum::Inserted(dom) = &bm.dom {
-                dom.iter()
-                    .for_each(|dom| dom.remove_1(&*v).expect("ClassList remove failed"));
+                dom.iter().for_each(|dom| dom.remove(&v));
             }
         } else {
             if let DomEnum::Inserted(dom) = &bm.dom {
-                dom.iter()
-                    .for_each(|dom| dom.add_1(&*v).expect("ClassList add failed"));
+                dom.iter().for_each(|dom| dom.add(&v));
             }
             bm.rust.insert(v);
         }
@@ -89,15 +116,22 @@ impl<'a> ClassList<'a> {
         let mut bm = self.inner.borrow_mut();
         if let DomEnum::Inserted(doms) = &bm.dom {
             for dom in doms.iter() {
-                dom.set_value("");
+                dom.clear();
             }
         }
         bm.rust = SmallSet::new();
     }
     pub(crate) fn set_dom(&self, dom: DomTokenList) {
+        self.add_target(ClassTarget::Dom(dom));
+    }
+    #[cfg(feature = "ssr")]
+    pub(crate) fn set_ssr(&self, node: SsrNode) {
+        self.add_target(ClassTarget::Ssr(node));
+    }
+    fn add_target(&self, dom: ClassTarget) {
         let mut bm = self.inner.borrow_mut();
         bm.rust.for_each(|item| {
-            dom.add_1(&*item).expect("ClassList add failed");
+            dom.add(item);
         });
         match &mut bm.dom {
             DomEnum::None => {
@@ -1,16 +1,11 @@
 use std::{collections::VecDeque, error::Error};
 
-#[cfg(feature = "gtk")]
-use async_ui_gtk as async_ui;
-#[cfg(not(feature = "gtk"))]
-use async_ui_web as async_ui;
-
 use async_ui::{
-    components::{button, list, text, view, ButtonProps, ListModel, ListProps, ViewProps},
+    components::{
+        button, link, list, text, view, ButtonProps, LinkProps, ListModel, ListProps, ViewProps,
+    },
     fragment,
 };
-#[cfg(not(feature = "gtk"))]
-use async_ui_web::components::{link, LinkProps};
 use observables::cell::ReactiveCell;
 
 pub async fn root() -> Result<(), Box<dyn Error>> {
@@ -32,25 +27,18 @@ pub async fn root() -> Result<(), Box<dyn Error>> {
             .body_json()
             .await?;
 
-        #[cfg(not(feature = "gtk"))]
-        use {link as item_wrap, LinkProps as ItemWrapProps};
-        #[cfg(feature = "gtk")]
-        use {view as item_wrap, ViewProps as ItemWrapProps};
-
-        item_wrap(ItemWrapProps {
+        link(LinkProps {
             children: fragment((
                 view(ViewProps {
                     children: fragment((text(&[story.title]),)),
-                    #[cfg(not(feature = "gtk"))]
-                    class: Some(&"story-title".into()),
+                    class: &["story-title"],
                     ..Default::default()
                 }),
                 view(ViewProps {
                     children: fragment((
                         view(ViewProps {
                             children: fragment((text(&[format!("by: {}", story.by)]),)),
-                            #[cfg(not(feature = "gtk"))]
-                            class: Some(&"story-author".into()),
+                            class: &["story-author"],
                             ..Default::default()
                         }),
                         view(ViewProps {
@@ -58,14 +46,11 @@ pub async fn root() -> Result<(), Box<dyn Error>> {
                             ..Default::default()
                         }),
                     )),
-                    #[cfg(not(feature = "gtk"))]
-                    class: Some(&"story-info-bar".into()),
+                    class: &["story-info-bar"],
                     ..Default::default()
                 }),
             )),
-            #[cfg(not(feature = "gtk"))]
-            class: Some(&"story-item".into()),
-            #[cfg(not(feature = "gtk"))]
+            class: &["story-item"],
             href: &[story.url],
             ..Default::default()
         })
@@ -87,13 +72,11 @@ pub async fn root() -> Result<(), Box<dyn Error>> {
                         bm.push(item);
                     }
                 },
-                #[cfg(not(feature = "gtk"))]
-                class: Some(&"load-more-button".into()),
+                class: &["load-more-button"],
                 ..Default::default()
             }),
         )),
-        #[cfg(feature = "gtk")]
-        width: 640,
+        width: Some(640),
         ..Default::default()
     })
     .await;
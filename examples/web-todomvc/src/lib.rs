@@ -391,6 +391,7 @@ async fn bottom_part(store: &Store<State>) {
                 children: fragment((radio_button(RadioProps { value: filter }), text(&[label]))),
                 class: Some(&classes),
                 element_tag: "label",
+                ..Default::default()
             })
             .or(async {
                 loop {
@@ -4,28 +4,31 @@ use std::{
     task::Waker,
 };
 
-use super::Inner;
+use super::Meta;
 
 pub struct ReactiveCellBorrowMut<'b, T> {
-    pub(super) reference: RefMut<'b, Inner<T>>,
+    pub(super) reference: RefMut<'b, T>,
+    pub(super) meta: &'b Meta,
 }
 
 impl<'b, T> DerefMut for ReactiveCellBorrowMut<'b, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.reference.data
+        &mut self.reference
     }
 }
 
 impl<'b, T> Deref for ReactiveCellBorrowMut<'b, T> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
-        &self.reference.data
+        &self.reference
     }
 }
 
 impl<'b, T> Drop for ReactiveCellBorrowMut<'b, T> {
     fn drop(&mut self) {
-        self.reference.version = self.reference.version.incremented();
-        self.reference.listeners.drain(..).for_each(Waker::wake);
+        let version = &self.meta.version;
+        version.set(version.get().incremented());
+        let listeners = std::mem::take(&mut *self.meta.listeners.borrow_mut());
+        listeners.into_iter().for_each(Waker::wake);
     }
 }
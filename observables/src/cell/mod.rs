@@ -1,21 +1,29 @@
 mod borrow_mut;
-use std::{borrow::Borrow, cell::RefCell, fmt::Debug, marker::PhantomData, task::Waker};
+use std::{
+    borrow::Borrow,
+    cell::{Cell, RefCell},
+    fmt::Debug,
+    marker::PhantomData,
+    rc::Rc,
+    task::Waker,
+};
 
 use smallvec::SmallVec;
 
-use crate::{Listenable, ObservableBase, Version};
+use crate::{computed::track, Listenable, ObservableBase, Version};
 
 use self::borrow_mut::ReactiveCellBorrowMut;
 
 pub struct ReactiveCell<T> {
-    inner: RefCell<Inner<T>>,
+    data: RefCell<T>,
+    meta: Rc<Meta>,
 }
 
 impl<T: Debug> Debug for ReactiveCell<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut d = f.debug_tuple("ReactiveCell");
-        match self.inner.try_borrow() {
-            Ok(inside) => d.field(&inside.data).finish(),
+        match self.data.try_borrow() {
+            Ok(inside) => d.field(&*inside).finish(),
             Err(_) => {
                 // https://doc.rust-lang.org/src/core/fmt/mod.rs.html#2618
                 struct BorrowedPlaceholder;
@@ -30,24 +38,37 @@ impl<T: Debug> Debug for ReactiveCell<T> {
     }
 }
 
-struct Inner<T> {
-    data: T,
-    listeners: SmallVec<[Waker; 2]>,
-    version: Version,
+/** Listeners and version of a cell, kept in an [Rc] so [Computed][crate::computed::Computed]
+ * can hold on to it as a dependency.
+ */
+struct Meta {
+    listeners: RefCell<SmallVec<[Waker; 2]>>,
+    version: Cell<Version>,
+}
+
+impl Listenable for Meta {
+    fn add_waker(&self, waker: Waker) {
+        self.listeners.borrow_mut().push(waker);
+    }
+    fn get_version(&self) -> Version {
+        self.version.get()
+    }
 }
 
 impl<T> ReactiveCell<T> {
     pub fn new(data: T) -> Self {
-        let inner = RefCell::new(Inner {
-            data,
-            listeners: SmallVec::new(),
-            version: Version::new(),
-        });
-        Self { inner }
+        Self {
+            data: RefCell::new(data),
+            meta: Rc::new(Meta {
+                listeners: RefCell::new(SmallVec::new()),
+                version: Cell::new(Version::new()),
+            }),
+        }
     }
     pub fn borrow_mut<'b>(&'b self) -> ReactiveCellBorrowMut<'b, T> {
         ReactiveCellBorrowMut {
-            reference: self.inner.borrow_mut(),
+            reference: self.data.borrow_mut(),
+            meta: &self.meta,
         }
     }
     pub fn set(&self, value: T) {
@@ -66,16 +87,18 @@ pub struct ReactiveCellObservable<T, A: Borrow<ReactiveCell<T>>> {
 }
 impl<T, A: Borrow<ReactiveCell<T>>> Listenable for ReactiveCellObservable<T, A> {
     fn add_waker(&self, waker: Waker) {
-        self.inner.borrow().inner.borrow_mut().listeners.push(waker);
+        self.inner.borrow().meta.add_waker(waker);
     }
     fn get_version(&self) -> Version {
-        self.inner.borrow().inner.borrow().version
+        self.inner.borrow().meta.get_version()
     }
 }
 impl<T, A: Borrow<ReactiveCell<T>>> ObservableBase for ReactiveCellObservable<T, A> {
     type Data = T;
 
     fn visit_base<'b, F: FnOnce(&Self::Data) -> U, U>(&'b self, f: F) -> U {
-        f(&self.inner.borrow().inner.borrow().data)
+        let cell = self.inner.borrow();
+        track(&cell.meta);
+        f(&cell.data.borrow())
     }
 }
@@ -0,0 +1,181 @@
+/*! Derived values that find their own dependencies.
+ *
+ * A [Computed] runs its closure inside a tracking scope. Every source visited during the run
+ * (currently [ReactiveCell][crate::cell::ReactiveCell]s and other [Computed]s) is recorded along with its [Version].
+ * The result is cached until one of those versions advances.
+ *
+ * Observables that don't call [track] (e.g. channels or signals) are not picked up;
+ * join them in with [map2][crate::map2] or similar instead.
+ */
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashSet,
+    rc::Rc,
+    task::Waker,
+};
+
+use scopeguard::ScopeGuard;
+
+use crate::{Listenable, ObservableBase, Version};
+
+type Dependencies = Vec<(Rc<dyn Listenable>, Version)>;
+
+#[derive(Default)]
+struct Scope {
+    deps: Dependencies,
+    /// The sources in `deps`, to skip repeats quickly.
+    seen: HashSet<*const ()>,
+}
+
+thread_local! {
+    static SCOPE: RefCell<Option<Scope>> = const { RefCell::new(None) };
+}
+
+/** Record `source` as a dependency of the [Computed] currently running, if any.
+ *
+ * Call this when visiting an observable source to have it tracked.
+ */
+pub fn track<L: Listenable + 'static>(source: &Rc<L>) {
+    let source: Rc<dyn Listenable> = source.clone();
+    track_dyn(&source);
+}
+
+fn track_dyn(source: &Rc<dyn Listenable>) {
+    // Sources may be visited from destructors while the thread exits, after `SCOPE` is gone;
+    // nothing is being computed then.
+    let _ = SCOPE.try_with(|scope| {
+        if let Some(scope) = scope.borrow_mut().as_mut() {
+            if scope.seen.insert(Rc::as_ptr(source) as *const ()) {
+                scope.deps.push((source.clone(), source.get_version()));
+            }
+        }
+    });
+}
+
+fn run_tracked<R>(f: impl FnOnce() -> R) -> (R, Dependencies) {
+    let outer = SCOPE.with(|scope| scope.replace(Some(Scope::default())));
+    // Put the outer scope back even if `f` panics.
+    let outer = scopeguard::guard(outer, |outer| {
+        SCOPE.with(|scope| *scope.borrow_mut() = outer)
+    });
+    let result = f();
+    let inner = SCOPE.with(|scope| scope.replace(ScopeGuard::into_inner(outer)));
+    (result, inner.map(|scope| scope.deps).unwrap_or_default())
+}
+
+struct State<T> {
+    value: T,
+    deps: Dependencies,
+}
+
+/** An observable value computed by a closure, recomputed only when what it read has changed.
+ *
+ * ```rust
+ * # use std::cell::Cell;
+ * # use observables::{cell::ReactiveCell, computed::Computed, ObservableAsExt};
+ * let items = ReactiveCell::new(vec![1, 2, 3, 4]);
+ * let runs = Cell::new(0);
+ * let evens = Computed::new(|| {
+ *     runs.set(runs.get() + 1);
+ *     items
+ *         .as_observable()
+ *         .visit(|items: &Vec<i32>| items.iter().filter(|x| *x % 2 == 0).count())
+ * });
+ * assert_eq!(evens.get(), 2);
+ * assert_eq!(evens.get(), 2);
+ * assert_eq!(runs.get(), 1);
+ * items.borrow_mut().push(6);
+ * assert_eq!(evens.get(), 3);
+ * assert_eq!(runs.get(), 2);
+ * ```
+ */
+pub struct Computed<'a, T> {
+    compute: Box<dyn Fn() -> T + 'a>,
+    state: RefCell<Option<State<T>>>,
+    /// Own counter rather than a sum of the dependencies', since those may change between runs.
+    version: Cell<Version>,
+}
+
+impl<'a, T> Computed<'a, T> {
+    pub fn new(compute: impl Fn() -> T + 'a) -> Self {
+        Self {
+            compute: Box::new(compute),
+            state: RefCell::new(None),
+            version: Cell::new(Version::new()),
+        }
+    }
+    fn is_stale(&self) -> bool {
+        match &*self.state.borrow() {
+            Some(state) => state.deps.iter().any(|(d, v)| d.get_version() > *v),
+            None => true,
+        }
+    }
+    fn refresh(&self) {
+        if !self.is_stale() {
+            return;
+        }
+        let (value, deps) = run_tracked(|| (self.compute)());
+        *self.state.borrow_mut() = Some(State { value, deps });
+        self.version.set(self.version.get().incremented());
+    }
+}
+
+impl<'a, T> Listenable for Computed<'a, T> {
+    fn add_waker(&self, waker: Waker) {
+        self.refresh();
+        if let Some(state) = &*self.state.borrow() {
+            state
+                .deps
+                .iter()
+                .for_each(|(d, _)| d.add_waker(waker.clone()));
+        }
+    }
+    fn get_version(&self) -> Version {
+        let version = self.version.get();
+        if self.is_stale() {
+            // The next run will increment it.
+            version.incremented()
+        } else {
+            version
+        }
+    }
+}
+
+impl<'a, T> ObservableBase for Computed<'a, T> {
+    type Data = T;
+
+    fn visit_base<'b, F: FnOnce(&Self::Data) -> U, U>(&'b self, f: F) -> U {
+        self.refresh();
+        let state = self.state.borrow();
+        let state = state.as_ref().unwrap();
+        // Whoever depends on us depends on what we read.
+        state.deps.iter().for_each(|(d, _)| track_dyn(d));
+        f(&state.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use crate::{cell::ReactiveCell, ObservableAsExt};
+
+    use super::{Computed, SCOPE};
+
+    #[test]
+    fn scope_is_restored_after_a_panic() {
+        let cell = ReactiveCell::new(1);
+        let failing = Computed::new(|| {
+            let value: i32 = cell.as_observable().get();
+            if value == 1 {
+                panic!("cannot compute");
+            }
+            value
+        });
+        let outer = Computed::new(|| failing.get());
+        assert!(catch_unwind(AssertUnwindSafe(|| outer.get())).is_err());
+        SCOPE.with(|scope| assert!(scope.borrow().is_none()));
+        cell.set(2);
+        assert_eq!(outer.get(), 2);
+    }
+}
@@ -1,9 +1,18 @@
-use std::{borrow::Borrow, task::Waker};
+use std::{borrow::Borrow, future::Future, task::Waker, time::Duration};
 
-use transformers::{for_each::ForEach, map::Map};
+pub use transformers::combine::{combine, map2, map3, zip, Combine, Map2, Map3};
+pub use transformers::timed::Timer;
+use transformers::{
+    filter::{Distinct, Filter, SkipInitial},
+    for_each::{ForEach, ForEachAsync},
+    map::Map,
+    timed::Timed,
+};
 pub use version::Version;
 mod impls;
 mod next_change;
+#[cfg(test)]
+mod test_executor;
 mod transformers;
 mod version;
 pub use next_change::NextChangeFuture;
@@ -11,8 +20,11 @@ pub use next_change::NextChangeFuture;
 #[cfg(feature = "async-channel")]
 pub mod async_channel;
 pub mod cell;
+pub mod computed;
 #[cfg(feature = "futures-signals")]
 pub mod futures_signals;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 pub trait Listenable {
     fn add_waker(&self, waker: Waker);
@@ -69,16 +81,114 @@ pub trait ObservableAsExt<Z: ?Sized>: ObservableAs<Z> {
     {
         ForEach::new(self, handler)
     }
-    // fn for_each_async<H, F>(
-    //     &self,
-    //     handler: H,
-    // ) -> ForEachAsync<Self, H, F>
-    // where
-    //     H: FnMut(&Z) -> F,
-    //     F: Future<Output = ()>,
-    //     Self: Sized
-    // {
-    // }
+    /** Run the async `handler` with the value now and on every change.
+     *
+     * When the value changes while the previous future is still running,
+     * that future is dropped (cancelled) and a new one started.
+     */
+    fn for_each_async<H, F>(self, handler: H) -> ForEachAsync<Self, Z, H, F>
+    where
+        H: FnMut(&Z) -> F,
+        F: Future<Output = ()>,
+        Self: Sized,
+    {
+        ForEachAsync::new(self, handler, true)
+    }
+    /** Like [for_each_async][ObservableAsExt::for_each_async],
+     * but let the running future finish before handling the next change.
+     *
+     * Changes that come in meanwhile are coalesced: the handler runs once more, with the latest value.
+     */
+    fn for_each_async_queued<H, F>(self, handler: H) -> ForEachAsync<Self, Z, H, F>
+    where
+        H: FnMut(&Z) -> F,
+        F: Future<Output = ()>,
+        Self: Sized,
+    {
+        ForEachAsync::new(self, handler, false)
+    }
+    /** Follow the value only once it has stopped changing for `duration`.
+     *
+     * ```rust
+     * # use std::time::Duration;
+     * # fn example<T: observables::Timer>(text: &dyn observables::ObservableAs<str>, timer: T) {
+     * # use observables::ObservableAsExt;
+     * let query = text.debounce(Duration::from_millis(300), timer);
+     * # }
+     * ```
+     */
+    fn debounce<T: Timer>(self, duration: Duration, timer: T) -> Timed<Self, Z, T>
+    where
+        Z: ToOwned,
+        Self: Sized,
+    {
+        Timed::debounce(self, timer, duration)
+    }
+    /** Follow the value at most once every `duration`.
+     *
+     * A change is taken right away if there hasn't been one for `duration`,
+     * otherwise the latest value is taken once that much time has passed.
+     */
+    fn throttle<T: Timer>(self, duration: Duration, timer: T) -> Timed<Self, Z, T>
+    where
+        Z: ToOwned,
+        Self: Sized,
+    {
+        Timed::throttle(self, timer, duration)
+    }
+    /** Take the latest value every `interval`, if it has changed.
+     */
+    fn sample<T: Timer>(self, interval: Duration, timer: T) -> Timed<Self, Z, T>
+    where
+        Z: ToOwned,
+        Self: Sized,
+    {
+        Timed::sample(self, timer, interval)
+    }
+    /** Only change when the value is no longer equal to the previous one.
+     *
+     * Setting a [ReactiveCell][cell::ReactiveCell] to the value it already has
+     * still wakes its listeners; this stops such changes from going further.
+     *
+     * ```rust
+     * # use observables::{cell::ReactiveCell, Listenable, ObservableAsExt};
+     * let cell = ReactiveCell::new(1);
+     * let distinct = cell.as_observable().distinct();
+     * let version = distinct.get_version();
+     * cell.set(1);
+     * assert!(distinct.get_version() == version);
+     * cell.set(2);
+     * assert!(distinct.get_version() > version);
+     * ```
+     */
+    fn distinct(self) -> Distinct<Self, Z>
+    where
+        Z: ToOwned + PartialEq,
+        Self: Sized,
+    {
+        Distinct::new(self)
+    }
+    /** Only change when the new value satisfies `predicate`; keep the last accepted value otherwise.
+     *
+     * Starts with `fallback` if the initial value does not satisfy `predicate`.
+     */
+    fn filter<P>(self, predicate: P, fallback: Z::Owned) -> Filter<Self, Z, P>
+    where
+        Z: ToOwned,
+        P: Fn(&Z) -> bool,
+        Self: Sized,
+    {
+        Filter::new(self, predicate, fallback)
+    }
+    /** Pretend there is no value until the first change,
+     * so that [for_each][ObservableAsExt::for_each] and the like only run on changes.
+     */
+    fn skip_initial(self) -> SkipInitial<Self, Z>
+    where
+        Self: Sized,
+    {
+        SkipInitial::new(self)
+    }
 }
 impl<Z, O> ObservableAs<Z> for O
 where
@@ -18,7 +18,8 @@ pin_project! {
         I: ?Sized,
     {
         inner: A,
-        start_version: Version,
+        // Recorded on the first poll. Not a null version, as the observable may have none yet.
+        start_version: Option<Version>,
         _phantom: PhantomData<Box<I>>,
     }
 }
@@ -31,13 +32,18 @@ where
     pub fn new(observable: A) -> Self {
         Self {
             inner: observable,
-            start_version: Version::new_null(),
+            start_version: None,
             _phantom: PhantomData,
         }
     }
     pub fn observable(&self) -> &A {
         &self.inner
     }
+    /** Wait for the next change from now on, as if just created.
+     */
+    pub(crate) fn restart(self: Pin<&mut Self>) {
+        *self.project().start_version = None;
+    }
 }
 
 impl<I, A> Future for NextChangeFuture<I, A>
@@ -51,11 +57,12 @@ where
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.project();
         let inner: &I = (&*this.inner).borrow();
-        if this.start_version.is_null() {
-            *this.start_version = inner.get_version();
+        let start_version = *this.start_version.get_or_insert_with(|| {
+            let version = inner.get_version();
             inner.add_waker(cx.waker().to_owned());
-        }
-        if inner.get_version() > *this.start_version {
+            version
+        });
+        if inner.get_version() > start_version {
             Poll::Ready(())
         } else {
             Poll::Pending
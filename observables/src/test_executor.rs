@@ -0,0 +1,79 @@
+/*! Just enough of an executor for the tests of this crate:
+ * one future, polled on this thread whenever it has been woken.
+ */
+use std::{
+    cell::RefCell,
+    future::{poll_fn, Future},
+    pin::Pin,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Wake, Waker},
+};
+
+#[derive(Default)]
+struct Woken(AtomicBool);
+impl Wake for Woken {
+    fn wake(self: Arc<Self>) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+pub(crate) struct Executor<'a> {
+    future: Option<Pin<Box<dyn Future<Output = ()> + 'a>>>,
+    woken: Arc<Woken>,
+}
+
+impl<'a> Executor<'a> {
+    /** Poll `future` until it stalls. */
+    pub(crate) fn run(future: impl Future<Output = ()> + 'a) -> Self {
+        let mut this = Self {
+            future: Some(Box::pin(future)),
+            woken: Default::default(),
+        };
+        this.woken.0.store(true, Ordering::SeqCst);
+        this.run_until_stalled();
+        this
+    }
+    /** Poll the future for as long as it keeps being woken. */
+    pub(crate) fn run_until_stalled(&mut self) {
+        let waker = Waker::from(self.woken.clone());
+        while self.woken.0.swap(false, Ordering::SeqCst) {
+            let Some(future) = self.future.as_mut() else {
+                return;
+            };
+            if future
+                .as_mut()
+                .poll(&mut Context::from_waker(&waker))
+                .is_ready()
+            {
+                self.future = None;
+            }
+        }
+    }
+}
+
+/** A future that waits until [open][Gate::open] is called. */
+#[derive(Clone, Default)]
+pub(crate) struct Gate(Rc<RefCell<(bool, Vec<Waker>)>>);
+impl Gate {
+    pub(crate) fn open(&self) {
+        let mut bm = self.0.borrow_mut();
+        bm.0 = true;
+        bm.1.drain(..).for_each(Waker::wake);
+    }
+    pub(crate) async fn wait(&self) {
+        poll_fn(|cx| {
+            let mut bm = self.0.borrow_mut();
+            if bm.0 {
+                Poll::Ready(())
+            } else {
+                bm.1.push(cx.waker().to_owned());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
@@ -0,0 +1,59 @@
+/*! Helpers for testing code that uses observables.
+ *
+ * Only built with the `testing` feature; add it to your dev-dependencies.
+ */
+use std::{
+    cell::RefCell,
+    future::{poll_fn, Future},
+    pin::Pin,
+    rc::Rc,
+    task::{Poll, Waker},
+    time::Duration,
+};
+
+use crate::Timer;
+
+/** A [Timer] that only moves forward when told to, for testing time-based observables.
+ */
+#[derive(Clone, Default)]
+pub struct TestClock(Rc<RefCell<ClockInner>>);
+
+#[derive(Default)]
+struct ClockInner {
+    now: Duration,
+    sleepers: Vec<Waker>,
+}
+
+impl TestClock {
+    pub fn now(&self) -> Duration {
+        self.0.borrow().now
+    }
+    /** Move the clock forward, waking the sleeps that are due.
+     */
+    pub fn advance(&self, by: Duration) {
+        let sleepers = {
+            let mut inner = self.0.borrow_mut();
+            inner.now += by;
+            std::mem::take(&mut inner.sleepers)
+        };
+        sleepers.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl Timer for TestClock {
+    type Sleep = Pin<Box<dyn Future<Output = ()>>>;
+
+    fn sleep(&self, duration: Duration) -> Self::Sleep {
+        let deadline = self.now() + duration;
+        let clock = self.0.clone();
+        Box::pin(poll_fn(move |cx| {
+            let mut inner = clock.borrow_mut();
+            if inner.now >= deadline {
+                Poll::Ready(())
+            } else {
+                inner.sleepers.push(cx.waker().to_owned());
+                Poll::Pending
+            }
+        }))
+    }
+}
@@ -0,0 +1,273 @@
+use std::{cell::RefCell, task::Waker};
+
+use crate::{Listenable, ObservableBase, Version};
+
+/** Observe the result of `mapper` applied to the values of `a` and `b`.
+ *
+ * ```rust
+ * # use observables::{cell::ReactiveCell, map2, ObservableAs, ObservableAsExt};
+ * let first = ReactiveCell::new(String::from("Ada"));
+ * let last = ReactiveCell::new(String::from("Lovelace"));
+ * let full = map2(first.as_observable(), last.as_observable(), |f, l| format!("{f} {l}"));
+ * // Usable as a prop, e.g. for `text`.
+ * let prop: &dyn ObservableAs<str> = &full;
+ * assert_eq!(prop.get(), "Ada Lovelace");
+ * ```
+ */
+pub fn map2<A, B, O, M>(a: A, b: B, mapper: M) -> Map2<A, B, O, M>
+where
+    A: ObservableBase,
+    B: ObservableBase,
+    M: Fn(&A::Data, &B::Data) -> O,
+{
+    Map2 {
+        a,
+        b,
+        mapper,
+        last_value: RefCell::new(None),
+    }
+}
+
+/** Like [map2], with three inputs.
+ */
+pub fn map3<A, B, C, O, M>(a: A, b: B, c: C, mapper: M) -> Map3<A, B, C, O, M>
+where
+    A: ObservableBase,
+    B: ObservableBase,
+    C: ObservableBase,
+    M: Fn(&A::Data, &B::Data, &C::Data) -> O,
+{
+    Map3 {
+        a,
+        b,
+        c,
+        mapper,
+        last_value: RefCell::new(None),
+    }
+}
+
+/** Observe a tuple of observables as a tuple of their latest (cloned) values.
+ *
+ * Tuples of 2 to 6 observables are supported.
+ *
+ * ```rust
+ * # use observables::{cell::ReactiveCell, combine, ObservableAsExt};
+ * let a = ReactiveCell::new(1);
+ * let b = ReactiveCell::new('x');
+ * let c = ReactiveCell::new(true);
+ * let all = combine((a.as_observable(), b.as_observable(), c.as_observable()));
+ * assert_eq!(all.get(), (1, 'x', true));
+ * ```
+ */
+pub fn combine<T>(observables: T) -> Combine<T>
+where
+    Combine<T>: ObservableBase,
+{
+    Combine(observables)
+}
+
+/** Observe two observables as a pair of (cloned) values. Same as `combine((a, b))`.
+ */
+pub fn zip<A, B>(a: A, b: B) -> Combine<(A, B)>
+where
+    A: ObservableBase,
+    B: ObservableBase,
+    A::Data: Clone,
+    B::Data: Clone,
+{
+    Combine((a, b))
+}
+
+pub struct Map2<A, B, O, M>
+where
+    A: ObservableBase,
+    B: ObservableBase,
+    M: Fn(&A::Data, &B::Data) -> O,
+{
+    a: A,
+    b: B,
+    mapper: M,
+    last_value: RefCell<Option<O>>,
+}
+
+impl<A, B, O, M> ObservableBase for Map2<A, B, O, M>
+where
+    A: ObservableBase,
+    B: ObservableBase,
+    M: Fn(&A::Data, &B::Data) -> O,
+{
+    type Data = O;
+
+    fn visit_base<'b, F: FnOnce(&Self::Data) -> U, U>(&'b self, f: F) -> U {
+        let value = self
+            .a
+            .visit_base(|a| self.b.visit_base(|b| (self.mapper)(a, b)));
+        *self.last_value.borrow_mut() = Some(value);
+        f(self.last_value.borrow().as_ref().unwrap())
+    }
+}
+
+impl<A, B, O, M> Listenable for Map2<A, B, O, M>
+where
+    A: ObservableBase,
+    B: ObservableBase,
+    M: Fn(&A::Data, &B::Data) -> O,
+{
+    fn add_waker(&self, waker: Waker) {
+        self.a.add_waker(waker.clone());
+        self.b.add_waker(waker);
+    }
+
+    fn get_version(&self) -> Version {
+        self.a.get_version().combined(self.b.get_version())
+    }
+}
+
+pub struct Map3<A, B, C, O, M>
+where
+    A: ObservableBase,
+    B: ObservableBase,
+    C: ObservableBase,
+    M: Fn(&A::Data, &B::Data, &C::Data) -> O,
+{
+    a: A,
+    b: B,
+    c: C,
+    mapper: M,
+    last_value: RefCell<Option<O>>,
+}
+
+impl<A, B, C, O, M> ObservableBase for Map3<A, B, C, O, M>
+where
+    A: ObservableBase,
+    B: ObservableBase,
+    C: ObservableBase,
+    M: Fn(&A::Data, &B::Data, &C::Data) -> O,
+{
+    type Data = O;
+
+    fn visit_base<'b, F: FnOnce(&Self::Data) -> U, U>(&'b self, f: F) -> U {
+        let value = self.a.visit_base(|a| {
+            self.b
+                .visit_base(|b| self.c.visit_base(|c| (self.mapper)(a, b, c)))
+        });
+        *self.last_value.borrow_mut() = Some(value);
+        f(self.last_value.borrow().as_ref().unwrap())
+    }
+}
+
+impl<A, B, C, O, M> Listenable for Map3<A, B, C, O, M>
+where
+    A: ObservableBase,
+    B: ObservableBase,
+    C: ObservableBase,
+    M: Fn(&A::Data, &B::Data, &C::Data) -> O,
+{
+    fn add_waker(&self, waker: Waker) {
+        self.a.add_waker(waker.clone());
+        self.b.add_waker(waker.clone());
+        self.c.add_waker(waker);
+    }
+
+    fn get_version(&self) -> Version {
+        self.a
+            .get_version()
+            .combined(self.b.get_version())
+            .combined(self.c.get_version())
+    }
+}
+
+/** See [combine].
+ */
+pub struct Combine<T>(T);
+
+macro_rules! impl_combine {
+    ($($name:ident $index:tt),*) => {
+        impl<$($name),*> Listenable for Combine<($($name,)*)>
+        where
+            $($name: ObservableBase,)*
+        {
+            fn add_waker(&self, waker: Waker) {
+                $(self.0.$index.add_waker(waker.clone());)*
+            }
+            fn get_version(&self) -> Version {
+                Version::new_null()$(.combined(self.0.$index.get_version()))*
+            }
+        }
+        impl<$($name),*> ObservableBase for Combine<($($name,)*)>
+        where
+            $($name: ObservableBase, $name::Data: Clone,)*
+        {
+            type Data = ($($name::Data,)*);
+
+            fn visit_base<'b, F: FnOnce(&Self::Data) -> U, U>(&'b self, f: F) -> U {
+                f(&($(self.0.$index.visit_base(Clone::clone),)*))
+            }
+        }
+    };
+}
+impl_combine!(A 0, B 1);
+impl_combine!(A 0, B 1, C 2);
+impl_combine!(A 0, B 1, C 2, D 3);
+impl_combine!(A 0, B 1, C 2, D 3, E 4);
+impl_combine!(A 0, B 1, C 2, D 3, E 4, G 5);
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::{Wake, Waker},
+    };
+
+    use crate::{cell::ReactiveCell, combine, map2, Listenable, ObservableAsExt};
+
+    #[derive(Default)]
+    struct CountWakes(AtomicUsize);
+    impl Wake for CountWakes {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn map2_wakes_on_either_input() {
+        let (a, b) = (ReactiveCell::new(1), ReactiveCell::new(10));
+        let sum = map2(a.as_observable(), b.as_observable(), |a, b| a + b);
+        let mut version = sum.get_version();
+        for (cell, value) in [(&a, 2), (&b, 20)] {
+            let wakes = Arc::new(CountWakes::default());
+            sum.add_waker(Waker::from(wakes.clone()));
+            cell.set(value);
+            assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+            assert!(sum.get_version() > version);
+            version = sum.get_version();
+        }
+        assert_eq!(sum.get(), 22);
+    }
+
+    #[test]
+    fn combine_of_six_reports_changes() {
+        let cells: Vec<ReactiveCell<i32>> = (0..6).map(ReactiveCell::new).collect();
+        let all = combine((
+            cells[0].as_observable(),
+            cells[1].as_observable(),
+            cells[2].as_observable(),
+            cells[3].as_observable(),
+            cells[4].as_observable(),
+            cells[5].as_observable(),
+        ));
+        assert_eq!(all.get(), (0, 1, 2, 3, 4, 5));
+        for (index, cell) in cells.iter().enumerate() {
+            let version = all.get_version();
+            let wakes = Arc::new(CountWakes::default());
+            all.add_waker(Waker::from(wakes.clone()));
+            cell.set(10 + index as i32);
+            assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+            assert!(all.get_version() > version);
+        }
+        assert_eq!(all.get(), (10, 11, 12, 13, 14, 15));
+    }
+}
@@ -0,0 +1,302 @@
+use std::{
+    borrow::Borrow,
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+    sync::Arc,
+    task::Waker,
+};
+
+use crate::{Listenable, ObservableAs, ObservableAsExt, ObservableBase, Version};
+
+use super::listeners::Listeners;
+
+struct State<O> {
+    value: Option<O>,
+    /// Used instead of the initial value of the source if that is not accepted.
+    fallback: Option<O>,
+    version: Version,
+    /// Version of the source when we last looked.
+    seen: Version,
+}
+
+/** Holds on to the last accepted value of the source, with its own version.
+ * Shared by [Distinct] and [Filter].
+ */
+struct Suppress<W, Z>
+where
+    W: ObservableAs<Z>,
+    Z: ToOwned + ?Sized,
+{
+    wrapped: W,
+    state: RefCell<State<Z::Owned>>,
+    listeners: Arc<Listeners>,
+    _phantom: PhantomData<Box<Z>>,
+}
+
+impl<W, Z> Suppress<W, Z>
+where
+    W: ObservableAs<Z>,
+    Z: ToOwned + ?Sized,
+{
+    fn new(wrapped: W, fallback: Option<Z::Owned>) -> Self {
+        Self {
+            wrapped,
+            state: RefCell::new(State {
+                value: None,
+                fallback,
+                version: Version::new_null(),
+                seen: Version::new_null(),
+            }),
+            listeners: Arc::new(Listeners::new()),
+            _phantom: PhantomData,
+        }
+    }
+    /** Look at the source if it has changed. `accept` gets the last accepted value (if any) and the new one.
+     */
+    fn update(&self, accept: impl FnOnce(Option<&Z::Owned>, &Z) -> bool) {
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        let source_version = self.wrapped.get_version();
+        if state.value.is_some() && source_version <= state.seen {
+            return;
+        }
+        state.seen = source_version;
+        self.wrapped.add_waker(Waker::from(self.listeners.clone()));
+        let value = if self.wrapped.visit(|new| accept(state.value.as_ref(), new)) {
+            Some(self.wrapped.visit(ToOwned::to_owned))
+        } else if state.value.is_none() {
+            let fallback = state.fallback.take();
+            assert!(
+                fallback.is_some(),
+                "initial value rejected with no fallback"
+            );
+            fallback
+        } else {
+            None
+        };
+        if let Some(value) = value {
+            state.value = Some(value);
+            state.version = state.version.incremented();
+            self.listeners.drain_and_wake();
+        }
+    }
+    fn add_waker(&self, waker: Waker) {
+        self.listeners.add(waker);
+    }
+    fn get_version(&self) -> Version {
+        self.state.borrow().version
+    }
+    fn visit<F: FnOnce(&Z::Owned) -> U, U>(&self, f: F) -> U {
+        f(self.state.borrow().value.as_ref().unwrap())
+    }
+}
+
+/** See [distinct][ObservableAsExt::distinct].
+ */
+pub struct Distinct<W, Z>(Suppress<W, Z>)
+where
+    W: ObservableAs<Z>,
+    Z: ToOwned + PartialEq + ?Sized;
+
+impl<W, Z> Distinct<W, Z>
+where
+    W: ObservableAs<Z>,
+    Z: ToOwned + PartialEq + ?Sized,
+{
+    pub(crate) fn new(wrapped: W) -> Self {
+        Self(Suppress::new(wrapped, None))
+    }
+    fn update(&self) {
+        self.0
+            .update(|old, new| !old.is_some_and(|old| old.borrow() == new));
+    }
+}
+
+impl<W, Z> Listenable for Distinct<W, Z>
+where
+    W: ObservableAs<Z>,
+    Z: ToOwned + PartialEq + ?Sized,
+{
+    fn add_waker(&self, waker: Waker) {
+        self.update();
+        self.0.add_waker(waker);
+    }
+    fn get_version(&self) -> Version {
+        self.update();
+        self.0.get_version()
+    }
+}
+
+impl<W, Z> ObservableBase for Distinct<W, Z>
+where
+    W: ObservableAs<Z>,
+    Z: ToOwned + PartialEq + ?Sized,
+{
+    type Data = Z::Owned;
+
+    fn visit_base<'b, F: FnOnce(&Self::Data) -> U, U>(&'b self, f: F) -> U {
+        self.update();
+        self.0.visit(f)
+    }
+}
+
+/** See [filter][ObservableAsExt::filter].
+ */
+pub struct Filter<W, Z, P>
+where
+    W: ObservableAs<Z>,
+    Z: ToOwned + ?Sized,
+    P: Fn(&Z) -> bool,
+{
+    inner: Suppress<W, Z>,
+    predicate: P,
+}
+
+impl<W, Z, P> Filter<W, Z, P>
+where
+    W: ObservableAs<Z>,
+    Z: ToOwned + ?Sized,
+    P: Fn(&Z) -> bool,
+{
+    pub(crate) fn new(wrapped: W, predicate: P, fallback: Z::Owned) -> Self {
+        Self {
+            inner: Suppress::new(wrapped, Some(fallback)),
+            predicate,
+        }
+    }
+    fn update(&self) {
+        self.inner.update(|_, new| (self.predicate)(new));
+    }
+}
+
+impl<W, Z, P> Listenable for Filter<W, Z, P>
+where
+    W: ObservableAs<Z>,
+    Z: ToOwned + ?Sized,
+    P: Fn(&Z) -> bool,
+{
+    fn add_waker(&self, waker: Waker) {
+        self.update();
+        self.inner.add_waker(waker);
+    }
+    fn get_version(&self) -> Version {
+        self.update();
+        self.inner.get_version()
+    }
+}
+
+impl<W, Z, P> ObservableBase for Filter<W, Z, P>
+where
+    W: ObservableAs<Z>,
+    Z: ToOwned + ?Sized,
+    P: Fn(&Z) -> bool,
+{
+    type Data = Z::Owned;
+
+    fn visit_base<'b, F: FnOnce(&Self::Data) -> U, U>(&'b self, f: F) -> U {
+        self.update();
+        self.inner.visit(f)
+    }
+}
+
+/** See [skip_initial][ObservableAsExt::skip_initial].
+ */
+pub struct SkipInitial<W, Z>
+where
+    W: ObservableAs<Z>,
+    Z: ?Sized,
+{
+    wrapped: W,
+    /// Version of the source when first asked. Null until then.
+    initial: Cell<Version>,
+    _phantom: PhantomData<Box<Z>>,
+}
+
+impl<W, Z> SkipInitial<W, Z>
+where
+    W: ObservableAs<Z>,
+    Z: ?Sized,
+{
+    pub(crate) fn new(wrapped: W) -> Self {
+        Self {
+            wrapped,
+            initial: Cell::new(Version::new_null()),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<W, Z> Listenable for SkipInitial<W, Z>
+where
+    W: ObservableAs<Z>,
+    Z: ?Sized,
+{
+    fn add_waker(&self, waker: Waker) {
+        self.wrapped.add_waker(waker);
+    }
+    fn get_version(&self) -> Version {
+        let version = self.wrapped.get_version();
+        if self.initial.get().is_null() {
+            self.initial.set(version);
+        }
+        if version > self.initial.get() {
+            version
+        } else {
+            Version::new_null()
+        }
+    }
+}
+
+impl<W, Z> ObservableBase for SkipInitial<W, Z>
+where
+    W: ObservableAs<Z>,
+    Z: ?Sized,
+{
+    type Data = Z;
+
+    fn visit_base<'b, F: FnOnce(&Self::Data) -> U, U>(&'b self, f: F) -> U {
+        self.wrapped.visit(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use crate::{cell::ReactiveCell, test_executor::Executor, ObservableAsExt};
+
+    #[test]
+    fn skip_initial_and_distinct() {
+        let value = ReactiveCell::new(1);
+        let seen = RefCell::new(Vec::new());
+        let mut executor = Executor::run(
+            value
+                .as_observable()
+                .distinct()
+                .skip_initial()
+                .for_each(|v: &i32| seen.borrow_mut().push(*v)),
+        );
+        for v in [1, 2, 2, 3] {
+            value.set(v);
+            executor.run_until_stalled();
+        }
+        assert_eq!(*seen.borrow(), [2, 3]);
+    }
+
+    #[test]
+    fn filter_starts_with_fallback() {
+        let value = ReactiveCell::new(-1);
+        let seen = RefCell::new(Vec::new());
+        let mut executor = Executor::run(
+            value
+                .as_observable()
+                .filter(|v: &i32| *v >= 0, 0)
+                .for_each(|v: &i32| seen.borrow_mut().push(*v)),
+        );
+        for v in [2, -3, 4] {
+            value.set(v);
+            executor.run_until_stalled();
+        }
+        assert_eq!(*seen.borrow(), [0, 2, 4]);
+    }
+}
@@ -1,6 +1,6 @@
 use pin_project_lite::pin_project;
 
-use crate::{ObservableAsExt, Version};
+use crate::{NextChangeFuture, ObservableAsExt, Version};
 
 use super::super::ObservableAs;
 use std::{future::Future, marker::PhantomData, task::Poll};
@@ -15,6 +15,8 @@ pin_project! {
         wrapped: W,
         handler: H,
         last_version: Version,
+        // Whether we are listening while there is no value yet.
+        registered: bool,
         _phantom: PhantomData<I>
     }
 }
@@ -30,6 +32,7 @@ where
             wrapped,
             handler,
             last_version: Version::new_null(),
+            registered: false,
             _phantom: PhantomData,
         }
     }
@@ -49,23 +52,131 @@ where
             *this.last_version = version;
             this.wrapped.visit(this.handler);
             this.wrapped.add_waker(cx.waker().to_owned());
+        } else if version.is_null() && !std::mem::replace(this.registered, true) {
+            // Nothing to handle yet (see `skip_initial`), but we want to hear about the first value.
+            this.wrapped.add_waker(cx.waker().to_owned());
         }
         Poll::Pending
     }
 }
 
-// pin_project! {
-// 	pub struct ForEachAsync<W, I, H, F>
-// 	where
-// 		W: ObservableAs<I>,
-// 		H: FnMut(&I) -> F,
-// 		F: Future<Output = ()>,
-// 		I: ?Sized,
-// 	{
-// 		wrapped: W,
-// 		handler: H,
-// 		last_version: Version,
-// 		lastest_future: F,
-// 		_phantom: PhantomData<I>
-// 	}
-// }
+pin_project! {
+    pub struct ForEachAsync<W, I, H, F>
+    where
+        W: ObservableAs<I>,
+        H: FnMut(&I) -> F,
+        F: Future<Output = ()>,
+        I: ?Sized,
+    {
+        #[pin]
+        change: NextChangeFuture<W, W>,
+        handler: H,
+        started: bool,
+        // Drop the running future when a new value comes in, rather than let it finish first.
+        cancel_previous: bool,
+        #[pin]
+        latest_future: Option<F>,
+        _phantom: PhantomData<I>
+    }
+}
+
+impl<W, I, H, F> ForEachAsync<W, I, H, F>
+where
+    W: ObservableAs<I>,
+    H: FnMut(&I) -> F,
+    F: Future<Output = ()>,
+    I: ?Sized,
+{
+    pub(crate) fn new(wrapped: W, handler: H, cancel_previous: bool) -> Self {
+        Self {
+            change: NextChangeFuture::new(wrapped),
+            handler,
+            started: false,
+            cancel_previous,
+            latest_future: None,
+            _phantom: PhantomData,
+        }
+    }
+}
+impl<W, I, H, F> Future for ForEachAsync<W, I, H, F>
+where
+    W: ObservableAs<I>,
+    H: FnMut(&I) -> F,
+    F: Future<Output = ()>,
+    I: ?Sized,
+{
+    type Output = ();
+
+    fn poll(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        loop {
+            if *this.cancel_previous || this.latest_future.is_none() {
+                let changed = if std::mem::replace(this.started, true) {
+                    this.change.as_mut().poll(cx).is_ready()
+                } else {
+                    // The initial value counts, unless there is none yet (see `skip_initial`).
+                    !this.change.observable().get_version().is_null()
+                        || this.change.as_mut().poll(cx).is_ready()
+                };
+                if changed {
+                    // Start watching from the value about to be handled.
+                    this.change.as_mut().restart();
+                    let _ = this.change.as_mut().poll(cx);
+                    let future = this.change.observable().visit(&mut *this.handler);
+                    this.latest_future.set(Some(future));
+                }
+            }
+            match this.latest_future.as_mut().as_pin_mut() {
+                Some(future) => {
+                    if future.poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    this.latest_future.set(None);
+                }
+                None => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use crate::{
+        cell::ReactiveCell,
+        test_executor::{Executor, Gate},
+        ObservableAsExt,
+    };
+
+    #[test]
+    fn for_each_async_cancels_or_queues() {
+        for cancel in [true, false] {
+            let query = ReactiveCell::new(1);
+            let (gate, done) = (Gate::default(), RefCell::new(Vec::new()));
+            let handler = |q: &i32| {
+                let (q, gate, done) = (*q, gate.clone(), &done);
+                async move {
+                    gate.wait().await;
+                    done.borrow_mut().push(q);
+                }
+            };
+            let obs = query.as_observable();
+            let mut executor = Executor::run(async {
+                if cancel {
+                    obs.for_each_async(handler).await
+                } else {
+                    obs.for_each_async_queued(handler).await
+                }
+            });
+            query.set(2);
+            executor.run_until_stalled();
+            query.set(3);
+            executor.run_until_stalled();
+            gate.open();
+            executor.run_until_stalled();
+            let expected: &[i32] = if cancel { &[3] } else { &[1, 3] };
+            assert_eq!(*done.borrow(), expected);
+        }
+    }
+}
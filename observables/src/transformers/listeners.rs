@@ -0,0 +1,35 @@
+use std::{
+    sync::{Arc, Mutex},
+    task::{Wake, Waker},
+};
+
+/** Listeners of an observable that doesn't change every time its source does.
+ *
+ * As a [Waker] (given to the source), it wakes everyone without forgetting them,
+ * so they get to check again whether we actually changed.
+ */
+pub(crate) struct Listeners(Mutex<Vec<Waker>>);
+impl Listeners {
+    pub(crate) fn new() -> Self {
+        Self(Mutex::new(Vec::new()))
+    }
+    pub(crate) fn add(&self, waker: Waker) {
+        let mut wakers = self.0.lock().unwrap();
+        if !wakers.iter().any(|w| w.will_wake(&waker)) {
+            wakers.push(waker);
+        }
+    }
+    pub(crate) fn drain_and_wake(&self) {
+        let wakers = std::mem::take(&mut *self.0.lock().unwrap());
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+impl Wake for Listeners {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+    fn wake_by_ref(self: &Arc<Self>) {
+        let wakers = self.0.lock().unwrap().clone();
+        wakers.iter().for_each(Waker::wake_by_ref);
+    }
+}
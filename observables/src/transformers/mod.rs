@@ -1,2 +1,6 @@
+pub mod combine;
+pub mod filter;
 pub mod for_each;
+mod listeners;
 pub mod map;
+pub mod timed;
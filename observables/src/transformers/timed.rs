@@ -0,0 +1,262 @@
+use std::{
+    cell::RefCell,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Waker},
+    time::Duration,
+};
+
+use crate::{Listenable, ObservableAs, ObservableAsExt, ObservableBase, Version};
+
+use super::listeners::Listeners;
+
+/** Something to wait on, for [debounce][ObservableAsExt::debounce] and friends.
+ *
+ * The UI backends each provide one.
+ */
+pub trait Timer {
+    type Sleep: Future<Output = ()>;
+    fn sleep(&self, duration: Duration) -> Self::Sleep;
+}
+
+#[derive(Clone, Copy)]
+enum Mode {
+    Debounce,
+    Throttle,
+    Sample,
+}
+
+struct State<O, S> {
+    value: Option<O>,
+    version: Version,
+    /// Version of the source when we last looked.
+    seen: Version,
+    /// Version of the source that `value` came from.
+    taken: Version,
+    sleep: Option<Pin<Box<S>>>,
+}
+
+/** An observable that lags behind another according to time.
+ * See [debounce][ObservableAsExt::debounce], [throttle][ObservableAsExt::throttle], and [sample][ObservableAsExt::sample].
+ *
+ * It has no task of its own: the timer and the source are looked at whenever it is visited or polled for its version,
+ * which its listeners do when woken.
+ */
+pub struct Timed<W, Z, T>
+where
+    W: ObservableAs<Z>,
+    Z: ToOwned + ?Sized,
+    T: Timer,
+{
+    wrapped: W,
+    timer: T,
+    duration: Duration,
+    mode: Mode,
+    state: RefCell<State<Z::Owned, T::Sleep>>,
+    listeners: Arc<Listeners>,
+    _phantom: PhantomData<Box<Z>>,
+}
+
+impl<W, Z, T> Timed<W, Z, T>
+where
+    W: ObservableAs<Z>,
+    Z: ToOwned + ?Sized,
+    T: Timer,
+{
+    fn new(wrapped: W, timer: T, duration: Duration, mode: Mode) -> Self {
+        Self {
+            wrapped,
+            timer,
+            duration,
+            mode,
+            state: RefCell::new(State {
+                value: None,
+                version: Version::new_null(),
+                seen: Version::new_null(),
+                taken: Version::new_null(),
+                sleep: None,
+            }),
+            listeners: Arc::new(Listeners::new()),
+            _phantom: PhantomData,
+        }
+    }
+    pub(crate) fn debounce(wrapped: W, timer: T, duration: Duration) -> Self {
+        Self::new(wrapped, timer, duration, Mode::Debounce)
+    }
+    pub(crate) fn throttle(wrapped: W, timer: T, duration: Duration) -> Self {
+        Self::new(wrapped, timer, duration, Mode::Throttle)
+    }
+    pub(crate) fn sample(wrapped: W, timer: T, interval: Duration) -> Self {
+        Self::new(wrapped, timer, interval, Mode::Sample)
+    }
+    fn start_sleep(&self, state: &mut State<Z::Owned, T::Sleep>) {
+        state.sleep = Some(Box::pin(self.timer.sleep(self.duration)));
+    }
+    fn take(&self, state: &mut State<Z::Owned, T::Sleep>, source_version: Version) {
+        state.value = Some(self.wrapped.visit(ToOwned::to_owned));
+        state.taken = source_version;
+        state.version = state.version.incremented();
+        self.listeners.drain_and_wake();
+    }
+    fn update(&self) {
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        let waker = Waker::from(self.listeners.clone());
+        let source_version = self.wrapped.get_version();
+        if state.value.is_none() {
+            state.value = Some(self.wrapped.visit(ToOwned::to_owned));
+            state.version = Version::new();
+            state.seen = source_version;
+            state.taken = source_version;
+            self.wrapped.add_waker(waker.clone());
+            if let Mode::Sample = self.mode {
+                self.start_sleep(state);
+            }
+        } else if source_version > state.seen {
+            state.seen = source_version;
+            self.wrapped.add_waker(waker.clone());
+            match self.mode {
+                Mode::Debounce => self.start_sleep(state),
+                Mode::Throttle if state.sleep.is_none() => {
+                    self.take(state, source_version);
+                    self.start_sleep(state);
+                }
+                Mode::Throttle | Mode::Sample => {}
+            }
+        }
+        let expired = match state.sleep.as_mut() {
+            Some(sleep) => sleep
+                .as_mut()
+                .poll(&mut Context::from_waker(&waker))
+                .is_ready(),
+            None => false,
+        };
+        if expired {
+            state.sleep = None;
+            match self.mode {
+                Mode::Debounce => self.take(state, source_version),
+                Mode::Throttle if state.seen > state.taken => {
+                    self.take(state, source_version);
+                    self.start_sleep(state);
+                }
+                Mode::Throttle => {}
+                Mode::Sample => {
+                    // Taking wakes the listeners, whose next poll polls the new sleep.
+                    // Otherwise the new sleep waits for the next change of the source to be polled,
+                    // so an idle source costs no wakeups.
+                    if source_version > state.taken {
+                        self.take(state, source_version);
+                    }
+                    self.start_sleep(state);
+                }
+            }
+        }
+    }
+}
+
+impl<W, Z, T> Listenable for Timed<W, Z, T>
+where
+    W: ObservableAs<Z>,
+    Z: ToOwned + ?Sized,
+    T: Timer,
+{
+    fn add_waker(&self, waker: Waker) {
+        self.update();
+        self.listeners.add(waker);
+    }
+    fn get_version(&self) -> Version {
+        self.update();
+        self.state.borrow().version
+    }
+}
+
+impl<W, Z, T> ObservableBase for Timed<W, Z, T>
+where
+    W: ObservableAs<Z>,
+    Z: ToOwned + ?Sized,
+    T: Timer,
+{
+    type Data = Z::Owned;
+
+    fn visit_base<'b, F: FnOnce(&Self::Data) -> U, U>(&'b self, f: F) -> U {
+        self.update();
+        f(self.state.borrow().value.as_ref().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, time::Duration};
+
+    use crate::{cell::ReactiveCell, test_executor::Executor, testing::TestClock, ObservableAsExt};
+
+    const fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn debounce_and_throttle() {
+        let clock = TestClock::default();
+        let value = ReactiveCell::new(0);
+        let (debounced, throttled) = (RefCell::new(Vec::new()), RefCell::new(Vec::new()));
+        let mut debounce = Executor::run(
+            value
+                .as_observable()
+                .debounce(ms(100), clock.clone())
+                .for_each(|v: &i32| debounced.borrow_mut().push(*v)),
+        );
+        let mut throttle = Executor::run(
+            value
+                .as_observable()
+                .throttle(ms(100), clock.clone())
+                .for_each(|v: &i32| throttled.borrow_mut().push(*v)),
+        );
+        let mut run_until_stalled = || {
+            debounce.run_until_stalled();
+            throttle.run_until_stalled();
+        };
+        for v in 1..=4 {
+            value.set(v);
+            run_until_stalled();
+            clock.advance(ms(40));
+            run_until_stalled();
+        }
+        clock.advance(ms(100));
+        run_until_stalled();
+        assert_eq!(*debounced.borrow(), [0, 4]);
+        assert_eq!(*throttled.borrow(), [0, 1, 3, 4]);
+    }
+
+    #[test]
+    fn sample() {
+        let clock = TestClock::default();
+        let value = ReactiveCell::new(0);
+        let sampled = RefCell::new(Vec::new());
+        let mut executor = Executor::run(
+            value
+                .as_observable()
+                .sample(ms(100), clock.clone())
+                .for_each(|v: &i32| sampled.borrow_mut().push(*v)),
+        );
+        value.set(1);
+        executor.run_until_stalled();
+        clock.advance(ms(40));
+        value.set(2);
+        executor.run_until_stalled();
+        assert_eq!(*sampled.borrow(), [0]);
+        clock.advance(ms(60));
+        executor.run_until_stalled();
+        assert_eq!(*sampled.borrow(), [0, 2]);
+        // Nothing changed, so nothing is taken.
+        clock.advance(ms(300));
+        executor.run_until_stalled();
+        assert_eq!(*sampled.borrow(), [0, 2]);
+        value.set(3);
+        executor.run_until_stalled();
+        clock.advance(ms(100));
+        executor.run_until_stalled();
+        assert_eq!(*sampled.borrow(), [0, 2, 3]);
+    }
+}
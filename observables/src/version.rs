@@ -8,6 +8,16 @@ impl Version {
     pub fn incremented(self) -> Self {
         Self(self.0 + 1)
     }
+    /** A version that goes up whenever either of the two does,
+     * for observables derived from several others.
+     *
+     * This relies on versions only ever going up (never being reset or replaced by a lower one):
+     * then the sum goes up too, and never repeats an earlier value.
+     * Saturates rather than overflows, which is out of reach in practice.
+     */
+    pub fn combined(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
     pub const fn new_null() -> Self {
         Self(0)
     }